@@ -1,7 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::sync::Arc;
 use tokio::runtime::Runtime;
 use url::Url;
-use youtube_source_rs::cipher::SignatureCipherManager;
+use youtube_source_rs::cipher::{AdvancedSignatureCipher, DecipherMemo, SignatureCipherManager};
 
 fn bench_cipher_creation(c: &mut Criterion) {
     let mut group = c.benchmark_group("cipher_creation");
@@ -78,6 +79,33 @@ fn bench_basic_cipher_operations(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_memoized_decipher(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memoized_decipher");
+
+    let script = include_str!("../tests/fixtures/layout_corpus/split_lookup_table.js");
+    let cipher = AdvancedSignatureCipher::from_script(script).unwrap();
+    let memoized = cipher.clone().with_memo(Arc::new(DecipherMemo::default()));
+
+    // Every format of a video carries the same n value
+    let n_value = "Kt7cVQGZq4OnNqyP";
+    let signature = "AOq0QJ8wRAIgXmPlOPSBkkUs1bYFYlJCfe29xx8j7v1pDL0QwbdV96sCIEzpWqMGkFR20CFOg51Tp";
+
+    group.bench_function("n_parameter_javascript", |b| {
+        b.iter(|| black_box(cipher.transform_n_parameter(n_value).unwrap()))
+    });
+    group.bench_function("n_parameter_memoized", |b| {
+        b.iter(|| black_box(memoized.transform_n_parameter(n_value).unwrap()))
+    });
+    group.bench_function("signature_javascript", |b| {
+        b.iter(|| black_box(cipher.decipher_signature(signature).unwrap()))
+    });
+    group.bench_function("signature_memoized", |b| {
+        b.iter(|| black_box(memoized.decipher_signature(signature).unwrap()))
+    });
+
+    group.finish();
+}
+
 fn bench_url_operations(c: &mut Criterion) {
     let mut group = c.benchmark_group("url_operations");
 
@@ -217,6 +245,7 @@ criterion_group!(
     benches,
     bench_cipher_creation,
     bench_basic_cipher_operations,
+    bench_memoized_decipher,
     bench_url_operations,
     bench_regex_operations,
     bench_json_operations
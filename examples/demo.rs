@@ -48,8 +48,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🎵 Testing item loading:");
 
     let test_identifiers = vec![
-        "dQw4w9WgXcQ",               // Direct video ID
-        "rust programming tutorial", // Search query
+        "dQw4w9WgXcQ",                        // Direct video ID
+        "ytsearch:rust programming tutorial", // Search query
     ];
 
     for identifier in test_identifiers {
@@ -110,6 +110,7 @@ async fn test_format_resolution(
         signature_key: Some("sig".to_string()),
         is_default_audio_track: true,
         is_drc: false,
+        language: None,
     };
 
     let mock_player_url = Url::parse("https://www.youtube.com/s/player/test.js")?;
@@ -102,6 +102,11 @@ pub struct Format {
 pub struct AudioTrack {
     #[serde(rename = "audioIsDefault")]
     pub audio_is_default: Option<bool>,
+
+    pub id: Option<String>,
+
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
 }
 
 /// Player configuration
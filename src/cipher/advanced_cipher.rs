@@ -1,51 +1,118 @@
-use super::{ExtractedCipher, JavaScriptEngine, ScriptParser};
-use crate::{Result, StreamFormat};
+use super::memo::{DecipherMemo, MemoKind};
+use super::{ExtractedCipher, JavaScriptEngine, JsContextGuard, ScriptParser, SolvedValues};
+use crate::{Result, StreamFormat, YoutubeError};
+use std::sync::Arc;
 use std::time::Instant;
+use thiserror::Error;
 use url::Url;
 
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CipherError {
+    /// The n function returned its input, or its exception marker, so the
+    /// stream would be throttled
+    #[error("N parameter transformation failed: '{input}' -> '{output}'")]
+    NTransformFailed { input: String, output: String },
+}
+
 /// Advanced signature cipher that uses JavaScript execution for real cipher operations
 #[derive(Debug, Clone)]
 pub struct AdvancedSignatureCipher {
     pub extracted_cipher: ExtractedCipher,
+    /// All extracted pieces joined into the script evaluated by `js_engine`
+    script: Arc<str>,
     js_engine: JavaScriptEngine,
+    memo: Option<Arc<DecipherMemo>>,
 }
 
 impl AdvancedSignatureCipher {
     /// Create a new advanced signature cipher from a player script
     pub fn from_script(script: &str) -> Result<Self> {
         let extracted_cipher = ScriptParser::extract_cipher_from_script(script)?;
-        let js_engine = JavaScriptEngine::new()?;
-
-        Ok(Self {
-            extracted_cipher,
-            js_engine,
-        })
+        Self::from_extracted_cipher(extracted_cipher)
     }
 
     /// Create from pre-extracted cipher information
     pub fn from_extracted_cipher(extracted_cipher: ExtractedCipher) -> Result<Self> {
-        let js_engine = JavaScriptEngine::new()?;
+        Self::with_engine(extracted_cipher, JavaScriptEngine::new()?)
+    }
+
+    /// Create from pre-extracted cipher information, running it in the
+    /// context pool of `js_engine`
+    ///
+    /// The extracted pieces are evaluated into a context straight away, so a
+    /// script that does not compile is rejected here.
+    pub fn with_engine(
+        extracted_cipher: ExtractedCipher,
+        js_engine: JavaScriptEngine,
+    ) -> Result<Self> {
+        let script = format!(
+            "{}\n{}\n{}\n{}",
+            extracted_cipher.global_vars,
+            extracted_cipher.sig_actions,
+            extracted_cipher.sig_function,
+            extracted_cipher.n_function
+        );
+        js_engine.prewarm(&script)?;
 
         Ok(Self {
             extracted_cipher,
+            script: Arc::from(script),
             js_engine,
+            memo: None,
         })
     }
 
+    /// Look results up in `memo` before running JavaScript, and store new ones
+    pub fn with_memo(mut self, memo: Arc<DecipherMemo>) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
     /// Decipher a URL by applying signature and N parameter transformations
     pub fn decipher_url(&self, format: &StreamFormat) -> Result<Url> {
+        let mut context = self.js_engine.checkout(&self.script)?;
+        self.decipher_url_in(&mut context, format)
+    }
+
+    /// Decipher the URLs of many formats, e.g. all formats of one video, with
+    /// a single context checkout
+    pub fn decipher_urls(&self, formats: &[StreamFormat]) -> Result<Vec<Url>> {
+        let mut context = self.js_engine.checkout(&self.script)?;
+        formats
+            .iter()
+            .map(|format| self.decipher_url_in(&mut context, format))
+            .collect()
+    }
+
+    /// Decipher signatures and transform n values with a single context
+    /// checkout, e.g. to answer a [`SolveRequest`](super::SolveRequest)
+    pub fn solve_all(&self, signatures: &[String], n_values: &[String]) -> Result<SolvedValues> {
+        let mut context = self.js_engine.checkout(&self.script)?;
+        Ok(SolvedValues {
+            signatures: signatures
+                .iter()
+                .map(|signature| self.call_sig(&mut context, signature))
+                .collect::<Result<_>>()?,
+            n: n_values
+                .iter()
+                .map(|n_param| self.call_n(&mut context, n_param))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    fn decipher_url_in(&self, context: &mut JsContextGuard, format: &StreamFormat) -> Result<Url> {
         let mut url = format.url.clone();
 
         // Handle signature decryption if present
         if let Some(signature) = &format.signature {
-            let deciphered_signature = self.decipher_signature(signature)?;
-            url = self.build_url_with_signature(format, &deciphered_signature)?;
+            let deciphered_signature = self.call_sig(context, signature)?;
+            url = Self::build_url_with_signature(format, &deciphered_signature)?;
         }
 
         // Handle N parameter transformation if present
         if let Some(n_param) = &format.n_parameter {
-            let transformed_n = self.transform_n_parameter(n_param)?;
-            url = self.add_n_parameter_to_url(url, &transformed_n)?;
+            let transformed_n = self.call_n(context, n_param)?;
+            url = Self::add_n_parameter_to_url(url, &transformed_n)?;
         }
 
         Ok(url)
@@ -53,20 +120,18 @@ impl AdvancedSignatureCipher {
 
     /// Decipher a signature using JavaScript execution
     pub fn decipher_signature(&self, signature: &str) -> Result<String> {
-        let start_time = Instant::now();
+        let mut context = self.js_engine.checkout(&self.script)?;
+        self.call_sig(&mut context, signature)
+    }
 
-        // Prepare the JavaScript environment
-        let script = format!(
-            "{}\n{}\n{}",
-            self.extracted_cipher.global_vars,
-            self.extracted_cipher.sig_actions,
-            self.extracted_cipher.sig_function
-        );
+    fn call_sig(&self, context: &mut JsContextGuard, signature: &str) -> Result<String> {
+        if let Some(result) = self.memoized(MemoKind::Signature, signature) {
+            return Ok(result);
+        }
+        let start_time = Instant::now();
 
         // Execute the signature function
-        let result = self
-            .js_engine
-            .execute_cipher_function(&script, "sig", signature)?;
+        let result = context.call("sig", signature)?;
 
         // Log performance
         let execution_time = start_time.elapsed();
@@ -84,23 +149,24 @@ impl AdvancedSignatureCipher {
             );
         }
 
+        self.memoize(MemoKind::Signature, signature, &result);
         Ok(result)
     }
 
     /// Transform N parameter using JavaScript execution
     pub fn transform_n_parameter(&self, n_param: &str) -> Result<String> {
-        let start_time = Instant::now();
+        let mut context = self.js_engine.checkout(&self.script)?;
+        self.call_n(&mut context, n_param)
+    }
 
-        // Prepare the JavaScript environment
-        let script = format!(
-            "{}\n{}",
-            self.extracted_cipher.global_vars, self.extracted_cipher.n_function
-        );
+    fn call_n(&self, context: &mut JsContextGuard, n_param: &str) -> Result<String> {
+        if let Some(result) = self.memoized(MemoKind::NParameter, n_param) {
+            return Ok(result);
+        }
+        let start_time = Instant::now();
 
         // Execute the N parameter function
-        let result = self
-            .js_engine
-            .execute_n_transform_function(&script, "n", n_param)?;
+        let result = context.call("n", n_param)?;
 
         // Log performance and validate result
         let execution_time = start_time.elapsed();
@@ -111,31 +177,45 @@ impl AdvancedSignatureCipher {
             );
         }
 
-        // Validate N parameter transformation
-        if result == n_param {
-            log::warn!(
-                "N parameter transformation returned same value: '{n_param}' -> '{result}' (possible short-circuit)"
-            );
-        } else if result.starts_with("enhanced_except_")
+        let result = Self::check_n_result(n_param, result)?;
+        log::debug!(
+            "N parameter transformed in {}ms: '{}' -> '{}'",
+            execution_time.as_millis(),
+            n_param,
+            result
+        );
+        self.memoize(MemoKind::NParameter, n_param, &result);
+        Ok(result)
+    }
+
+    /// Reject an n result that equals its input or carries the exception
+    /// marker, either of which gets the stream throttled
+    pub(crate) fn check_n_result(n_param: &str, result: String) -> Result<String> {
+        if result == n_param
+            || result.starts_with("enhanced_except_")
             || result.ends_with(&format!("_w8_{n_param}"))
         {
-            log::warn!(
-                "N parameter transformation failed with exception pattern: '{n_param}' -> '{result}'"
-            );
-        } else {
-            log::debug!(
-                "N parameter transformed in {}ms: '{}' -> '{}'",
-                execution_time.as_millis(),
-                n_param,
-                result
-            );
+            return Err(CipherError::NTransformFailed {
+                input: n_param.to_string(),
+                output: result,
+            }
+            .into());
         }
-
         Ok(result)
     }
 
+    fn memoized(&self, kind: MemoKind, input: &str) -> Option<String> {
+        self.memo.as_ref()?.get(kind, self.get_timestamp(), input)
+    }
+
+    fn memoize(&self, kind: MemoKind, input: &str, result: &str) {
+        if let Some(memo) = &self.memo {
+            memo.insert(kind, self.get_timestamp(), input, result.to_string());
+        }
+    }
+
     /// Build URL with deciphered signature
-    fn build_url_with_signature(&self, format: &StreamFormat, signature: &str) -> Result<Url> {
+    pub(crate) fn build_url_with_signature(format: &StreamFormat, signature: &str) -> Result<Url> {
         let mut url = format.url.clone();
 
         if let Some(signature_key) = &format.signature_key {
@@ -162,7 +242,7 @@ impl AdvancedSignatureCipher {
     }
 
     /// Add N parameter to URL
-    fn add_n_parameter_to_url(&self, mut url: Url, n_value: &str) -> Result<Url> {
+    pub(crate) fn add_n_parameter_to_url(mut url: Url, n_value: &str) -> Result<Url> {
         let mut query_pairs: Vec<(String, String)> = url
             .query_pairs()
             .map(|(k, v)| (k.to_string(), v.to_string()))
@@ -192,6 +272,11 @@ impl AdvancedSignatureCipher {
         &self.extracted_cipher.timestamp
     }
 
+    /// Whether both ciphers come from the same load, as clones of one another
+    pub(crate) fn same_load(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.script, &other.script)
+    }
+
     /// Test the cipher with sample data
     pub fn test_cipher(&self) -> Result<()> {
         // Test signature decryption with a sample signature
@@ -204,14 +289,13 @@ impl AdvancedSignatureCipher {
             log::warn!("Cipher test warning: signature unchanged (may be identity function)");
         }
 
-        // Test N parameter transformation
+        // Test N parameter transformation; a failing one is only caught at
+        // playback time, where the script can be refreshed
         let test_n = "abc123def456";
-        let n_result = self.transform_n_parameter(test_n)?;
-
-        if n_result != test_n {
-            log::info!("N parameter test passed: transformation working");
-        } else {
-            log::warn!("N parameter test warning: value unchanged (may be identity function)");
+        match self.transform_n_parameter(test_n) {
+            Ok(_) => log::info!("N parameter test passed: transformation working"),
+            Err(YoutubeError::CipherFailed(e)) => log::warn!("N parameter test warning: {e}"),
+            Err(e) => return Err(e),
         }
 
         Ok(())
@@ -289,4 +373,75 @@ mod tests {
         let result = cipher.test_cipher();
         assert!(result.is_ok());
     }
+
+    fn ciphered_format(itag: u32, signature: &str, n: &str) -> StreamFormat {
+        StreamFormat {
+            info: None,
+            content_type: "audio/webm; codecs=\"opus\"".to_string(),
+            itag,
+            bitrate: 128000,
+            content_length: 0,
+            audio_channels: 2,
+            url: Url::parse(&format!("https://example.com/videoplayback?itag={itag}")).unwrap(),
+            n_parameter: Some(n.to_string()),
+            signature: Some(signature.to_string()),
+            signature_key: Some("sig".to_string()),
+            is_default_audio_track: true,
+            is_drc: false,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_batch_decipher_uses_one_context() {
+        let mut cipher_info = create_test_cipher();
+        cipher_info.global_vars.push_str("\nvar calls = 0;");
+        cipher_info.n_function = r#"var n = function(c) {
+            calls += 1;
+            return c + '_' + calls;
+        }"#
+        .to_string();
+        let cipher = AdvancedSignatureCipher::from_extracted_cipher(cipher_info).unwrap();
+
+        let formats: Vec<_> = (0..3)
+            .map(|i| ciphered_format(250 + i, "abcdef", "nnn"))
+            .collect();
+        let urls = cipher.decipher_urls(&formats).unwrap();
+        assert_eq!(cipher.js_engine.idle_contexts(&cipher.script), 1);
+
+        // A single context saw every call, so the counter runs 1..=3
+        for (i, url) in urls.iter().enumerate() {
+            let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+            assert_eq!(query["itag"], (250 + i).to_string());
+            assert_eq!(query["sig"], cipher.decipher_signature("abcdef").unwrap());
+            assert_eq!(query["n"], format!("nnn_{}", i + 1));
+        }
+    }
+
+    #[test]
+    fn test_rejects_script_that_does_not_compile() {
+        let mut cipher_info = create_test_cipher();
+        cipher_info.n_function = "var n = function(c) {".to_string();
+        assert!(AdvancedSignatureCipher::from_extracted_cipher(cipher_info).is_err());
+    }
+
+    #[test]
+    fn test_failed_n_transform_is_an_error() {
+        let mut cipher_info = create_test_cipher();
+        cipher_info.n_function = r#"var n = function(c) {
+            return c.length > 3 ? c : 'enhanced_except_AAAA_w8_' + c;
+        }"#
+        .to_string();
+        let cipher = AdvancedSignatureCipher::from_extracted_cipher(cipher_info).unwrap();
+
+        for input in ["abcdef", "abc"] {
+            let Err(YoutubeError::CipherFailed(CipherError::NTransformFailed { output, .. })) =
+                cipher.transform_n_parameter(input)
+            else {
+                panic!("expected a failed n transformation for {input}");
+            };
+            assert!(output.ends_with(input));
+        }
+        assert!(cipher.test_cipher().is_ok());
+    }
 }
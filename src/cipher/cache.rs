@@ -0,0 +1,466 @@
+use super::ExtractedCipher;
+use crate::{Result, YoutubeError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use url::Url;
+
+/// Default lifetime of cached cipher entries
+///
+/// Player scripts never change once published under a hash, so entries only
+/// expire to bound the cache; a day keeps restarts cheap.
+pub const DEFAULT_CIPHER_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default number of player scripts kept by a cache
+pub const DEFAULT_CIPHER_CACHE_MAX_ENTRIES: usize = 16;
+
+/// Identifies the cipher of one player script
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CipherCacheKey {
+    /// Player script hash, e.g. `e7567ecf` from `/s/player/e7567ecf/...`
+    pub player_hash: String,
+    pub signature_timestamp: String,
+}
+
+impl CipherCacheKey {
+    pub fn new(player_hash: impl Into<String>, signature_timestamp: impl Into<String>) -> Self {
+        Self {
+            player_hash: player_hash.into(),
+            signature_timestamp: signature_timestamp.into(),
+        }
+    }
+}
+
+/// Cipher pieces extracted from a player script, without the script itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedCipher {
+    pub key: CipherCacheKey,
+    pub global_vars: String,
+    pub sig_actions: String,
+    pub sig_function: String,
+    pub n_function: String,
+    pub cached_at: SystemTime,
+}
+
+impl CachedCipher {
+    pub fn from_extracted(player_hash: impl Into<String>, cipher: &ExtractedCipher) -> Self {
+        Self {
+            key: CipherCacheKey::new(player_hash, cipher.timestamp.clone()),
+            global_vars: cipher.global_vars.clone(),
+            sig_actions: cipher.sig_actions.clone(),
+            sig_function: cipher.sig_function.clone(),
+            n_function: cipher.n_function.clone(),
+            cached_at: SystemTime::now(),
+        }
+    }
+
+    /// Rebuild the extracted cipher; `raw_script` is left empty
+    pub fn to_extracted(&self) -> ExtractedCipher {
+        ExtractedCipher {
+            timestamp: self.key.signature_timestamp.clone(),
+            global_vars: self.global_vars.clone(),
+            sig_actions: self.sig_actions.clone(),
+            sig_function: self.sig_function.clone(),
+            n_function: self.n_function.clone(),
+            raw_script: String::new(),
+        }
+    }
+
+    pub fn is_expired(&self, ttl: Duration) -> bool {
+        self.cached_at.elapsed().unwrap_or_default() >= ttl
+    }
+}
+
+/// Storage backend for extracted player script ciphers
+///
+/// Entries are stored under their full [`CipherCacheKey`] and looked up by
+/// player hash, since the signature timestamp is only known after the script
+/// has been parsed.
+#[async_trait]
+pub trait CipherCache: Send + Sync + std::fmt::Debug {
+    /// Newest unexpired entry for a player hash
+    async fn get(&self, player_hash: &str) -> Option<CachedCipher>;
+
+    /// Store an entry, evicting the oldest ones beyond the size limit
+    async fn put(&self, entry: CachedCipher) -> Result<()>;
+
+    /// Drop every entry for a player hash
+    async fn remove(&self, player_hash: &str) -> Result<()>;
+
+    /// Number of stored entries, including expired ones not yet evicted
+    async fn len(&self) -> usize;
+
+    async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// How long entries stay valid
+    fn ttl(&self) -> Duration;
+}
+
+/// Player hash of a script URL, used as the cache key
+///
+/// Falls back to a stable hash of the whole URL for non-standard locations.
+pub fn player_hash(player_script_url: &Url) -> String {
+    let mut segments = player_script_url.path_segments().into_iter().flatten();
+    while let Some(segment) = segments.next() {
+        if segment == "player" {
+            if let Some(hash) = segments.next().filter(|hash| is_safe_key(hash)) {
+                return hash.to_string();
+            }
+        }
+    }
+
+    // FNV-1a, so keys stay the same across builds and restarts
+    let hash = player_script_url
+        .as_str()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    format!("{hash:016x}")
+}
+
+fn is_safe_key(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Entries to evict so that at most `max_entries` remain, oldest first
+fn eviction_order(
+    mut entries: Vec<(CipherCacheKey, SystemTime)>,
+    max_entries: usize,
+) -> Vec<CipherCacheKey> {
+    let excess = entries.len().saturating_sub(max_entries);
+    entries.sort_by_key(|(_, cached_at)| *cached_at);
+    entries
+        .into_iter()
+        .take(excess)
+        .map(|(key, _)| key)
+        .collect()
+}
+
+/// In-process cipher cache, lost on restart
+#[derive(Debug)]
+pub struct MemoryCipherCache {
+    entries: Mutex<HashMap<CipherCacheKey, CachedCipher>>,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl Default for MemoryCipherCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryCipherCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl: DEFAULT_CIPHER_CACHE_TTL,
+            max_entries: DEFAULT_CIPHER_CACHE_MAX_ENTRIES,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+}
+
+#[async_trait]
+impl CipherCache for MemoryCipherCache {
+    async fn get(&self, player_hash: &str) -> Option<CachedCipher> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| !entry.is_expired(self.ttl));
+        entries
+            .values()
+            .filter(|entry| entry.key.player_hash == player_hash)
+            .max_by_key(|entry| entry.cached_at)
+            .cloned()
+    }
+
+    async fn put(&self, entry: CachedCipher) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(entry.key.clone(), entry);
+
+        let ages = entries
+            .values()
+            .map(|entry| (entry.key.clone(), entry.cached_at))
+            .collect();
+        for key in eviction_order(ages, self.max_entries) {
+            entries.remove(&key);
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, player_hash: &str) -> Result<()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key, _| key.player_hash != player_hash);
+        Ok(())
+    }
+
+    async fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    fn ttl(&self) -> Duration {
+        self.ttl
+    }
+}
+
+/// Cipher cache persisted as one JSON file per entry in a directory
+///
+/// Files are named `<player hash>-<signature timestamp>.json` and written
+/// atomically, so several processes can share a directory.
+#[derive(Debug)]
+pub struct FileCipherCache {
+    directory: PathBuf,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl FileCipherCache {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            ttl: DEFAULT_CIPHER_CACHE_TTL,
+            max_entries: DEFAULT_CIPHER_CACHE_MAX_ENTRIES,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn entry_path(&self, key: &CipherCacheKey) -> Result<PathBuf> {
+        if !is_safe_key(&key.player_hash) || !is_safe_key(&key.signature_timestamp) {
+            return Err(YoutubeError::Cipher(format!(
+                "Cannot use {key:?} as a cache file name"
+            )));
+        }
+        Ok(self.directory.join(format!(
+            "{}-{}.json",
+            key.player_hash, key.signature_timestamp
+        )))
+    }
+
+    /// All readable entries in the directory with their paths
+    ///
+    /// Unreadable or corrupt files are skipped; they are replaced on the next put.
+    async fn read_entries(&self) -> Vec<(PathBuf, CachedCipher)> {
+        let mut entries = Vec::new();
+        let Ok(mut dir) = tokio::fs::read_dir(&self.directory).await else {
+            return entries;
+        };
+
+        while let Ok(Some(file)) = dir.next_entry().await {
+            let path = file.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let Ok(content) = tokio::fs::read(&path).await else {
+                continue;
+            };
+            match serde_json::from_slice::<CachedCipher>(&content) {
+                Ok(entry) => entries.push((path, entry)),
+                Err(e) => log::debug!("Ignoring corrupt cipher cache file {}: {e}", path.display()),
+            }
+        }
+        entries
+    }
+}
+
+#[async_trait]
+impl CipherCache for FileCipherCache {
+    async fn get(&self, player_hash: &str) -> Option<CachedCipher> {
+        let mut newest: Option<CachedCipher> = None;
+
+        for (path, entry) in self.read_entries().await {
+            if entry.key.player_hash != player_hash {
+                continue;
+            }
+            if entry.is_expired(self.ttl) {
+                let _ = tokio::fs::remove_file(&path).await;
+                continue;
+            }
+            if newest
+                .as_ref()
+                .is_none_or(|newest| entry.cached_at > newest.cached_at)
+            {
+                newest = Some(entry);
+            }
+        }
+        newest
+    }
+
+    async fn put(&self, entry: CachedCipher) -> Result<()> {
+        let path = self.entry_path(&entry.key)?;
+        tokio::fs::create_dir_all(&self.directory).await?;
+
+        let content = serde_json::to_vec(&entry)?;
+        let temp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        tokio::fs::write(&temp_path, content).await?;
+        tokio::fs::rename(&temp_path, &path).await?;
+
+        let entries = self.read_entries().await;
+        let ages = entries
+            .iter()
+            .map(|(_, entry)| (entry.key.clone(), entry.cached_at))
+            .collect();
+        for key in eviction_order(ages, self.max_entries) {
+            let _ = tokio::fs::remove_file(self.entry_path(&key)?).await;
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, player_hash: &str) -> Result<()> {
+        for (path, entry) in self.read_entries().await {
+            if entry.key.player_hash == player_hash {
+                tokio::fs::remove_file(&path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn len(&self) -> usize {
+        self.read_entries().await.len()
+    }
+
+    fn ttl(&self) -> Duration {
+        self.ttl
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(player_hash: &str, timestamp: &str, age: Duration) -> CachedCipher {
+        CachedCipher {
+            key: CipherCacheKey::new(player_hash, timestamp),
+            global_vars: "var a=\"x\".split(\"\")".to_string(),
+            sig_actions: "var B={}".to_string(),
+            sig_function: format!("function(a){{return a}} // {player_hash}"),
+            n_function: "function(a){return a}".to_string(),
+            cached_at: SystemTime::now() - age,
+        }
+    }
+
+    #[test]
+    fn test_player_hash() {
+        let url =
+            Url::parse("https://www.youtube.com/s/player/e7567ecf/player_ias.vflset/en_US/base.js")
+                .unwrap();
+        assert_eq!(player_hash(&url), "e7567ecf");
+
+        let other = Url::parse("https://example.com/base.js").unwrap();
+        assert_eq!(player_hash(&other), player_hash(&other));
+        assert_eq!(player_hash(&other).len(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache_ttl_and_limit() {
+        let cache = MemoryCipherCache::new()
+            .with_ttl(Duration::from_secs(60))
+            .with_max_entries(2);
+
+        cache
+            .put(entry("old", "1", Duration::from_secs(120)))
+            .await
+            .unwrap();
+        assert!(cache.get("old").await.is_none());
+
+        cache
+            .put(entry("a", "1", Duration::from_secs(30)))
+            .await
+            .unwrap();
+        cache
+            .put(entry("b", "2", Duration::from_secs(20)))
+            .await
+            .unwrap();
+        cache
+            .put(entry("c", "3", Duration::from_secs(10)))
+            .await
+            .unwrap();
+        assert_eq!(cache.len().await, 2);
+        assert!(cache.get("a").await.is_none());
+        assert_eq!(cache.get("c").await.unwrap().key.signature_timestamp, "3");
+
+        cache.remove("c").await.unwrap();
+        assert!(cache.get("c").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_file_cache_survives_new_instance() {
+        let directory = tempfile::tempdir().unwrap();
+        let stored = entry("e7567ecf", "20073", Duration::ZERO);
+
+        FileCipherCache::new(directory.path())
+            .put(stored.clone())
+            .await
+            .unwrap();
+        assert!(directory.path().join("e7567ecf-20073.json").exists());
+
+        let reopened = FileCipherCache::new(directory.path());
+        let loaded = reopened.get("e7567ecf").await.unwrap();
+        assert_eq!(loaded, stored);
+        assert_eq!(loaded.to_extracted().timestamp, "20073");
+
+        let expired = FileCipherCache::new(directory.path()).with_ttl(Duration::ZERO);
+        assert!(expired.get("e7567ecf").await.is_none());
+        assert!(expired.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_file_cache_limit_and_corrupt_files() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(directory.path().join("broken-1.json"), "{not json").unwrap();
+
+        let cache = FileCipherCache::new(directory.path()).with_max_entries(2);
+        cache
+            .put(entry("a", "1", Duration::from_secs(30)))
+            .await
+            .unwrap();
+        cache
+            .put(entry("b", "2", Duration::from_secs(20)))
+            .await
+            .unwrap();
+        cache
+            .put(entry("c", "3", Duration::from_secs(10)))
+            .await
+            .unwrap();
+
+        assert_eq!(cache.len().await, 2);
+        assert!(cache.get("a").await.is_none());
+        assert!(cache.get("b").await.is_some());
+
+        assert!(cache
+            .put(entry("../escape", "1", Duration::ZERO))
+            .await
+            .is_err());
+    }
+}
@@ -1,6 +1,11 @@
 use crate::Result;
-use rquickjs::{Context, Function, Runtime, Value};
-use std::time::Instant;
+use rquickjs::context::intrinsic;
+use rquickjs::{CatchResultExt, CaughtError, Context, Ctx, Function, Object, Runtime, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -15,35 +20,435 @@ pub enum JavaScriptEngineError {
     CompilationError(String),
     #[error("Execution timeout")]
     ExecutionTimeout,
+    #[error("JavaScript memory limit exceeded")]
+    MemoryLimitExceeded,
+}
+
+/// Limits and pool sizes for [`JavaScriptEngine`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaScriptEngineConfig {
+    /// Idle contexts kept per player script
+    pub contexts_per_script: usize,
+    /// Player scripts that keep idle contexts; the least recently used is evicted
+    pub max_scripts: usize,
+    /// Heap limit of each context's runtime, in bytes
+    pub memory_limit: usize,
+    /// Stack limit of each context's runtime, in bytes
+    pub max_stack_size: usize,
+    /// Longest a single script evaluation or function call may run
+    pub execution_timeout: Duration,
+}
+
+impl Default for JavaScriptEngineConfig {
+    fn default() -> Self {
+        Self {
+            contexts_per_script: 4,
+            max_scripts: 4,
+            memory_limit: 64 * 1024 * 1024,
+            max_stack_size: 1024 * 1024,
+            execution_timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+impl JavaScriptEngineConfig {
+    pub fn set_contexts_per_script(mut self, contexts_per_script: usize) -> Self {
+        self.contexts_per_script = contexts_per_script;
+        self
+    }
+
+    pub fn set_max_scripts(mut self, max_scripts: usize) -> Self {
+        self.max_scripts = max_scripts;
+        self
+    }
+
+    pub fn set_memory_limit(mut self, memory_limit: usize) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    pub fn set_max_stack_size(mut self, max_stack_size: usize) -> Self {
+        self.max_stack_size = max_stack_size;
+        self
+    }
+
+    pub fn set_execution_timeout(mut self, execution_timeout: Duration) -> Self {
+        self.execution_timeout = execution_timeout;
+        self
+    }
+}
+
+/// The only built-ins a player script gets besides the base objects (`Object`,
+/// `Array`, `String`, `Math`, ...): no promises, proxies, typed arrays or
+/// timers, and nothing that reaches outside the runtime
+type SandboxIntrinsics = (
+    intrinsic::Eval,
+    intrinsic::Date,
+    intrinsic::RegExpCompiler,
+    intrinsic::RegExp,
+    intrinsic::Json,
+);
+
+/// Deadline checked by a runtime's interrupt handler
+#[derive(Debug, Default)]
+struct Watchdog {
+    deadline: Mutex<Option<Instant>>,
+    fired: AtomicBool,
+}
+
+impl Watchdog {
+    fn arm(&self, timeout: Duration) {
+        self.fired.store(false, Ordering::Relaxed);
+        *self.deadline.lock().unwrap() = Some(Instant::now() + timeout);
+    }
+
+    fn disarm(&self) {
+        *self.deadline.lock().unwrap() = None;
+    }
+
+    fn should_interrupt(&self) -> bool {
+        let expired = self
+            .deadline
+            .lock()
+            .unwrap()
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if expired {
+            self.fired.store(true, Ordering::Relaxed);
+        }
+        expired
+    }
+
+    /// Map a failed evaluation to a timeout or memory error where that is the cause
+    fn classify(
+        &self,
+        error: CaughtError<'_>,
+        other: fn(String) -> JavaScriptEngineError,
+    ) -> JavaScriptEngineError {
+        if self.fired.load(Ordering::Relaxed) {
+            return JavaScriptEngineError::ExecutionTimeout;
+        }
+        let message = error.to_string();
+        if matches!(error, CaughtError::Error(rquickjs::Error::Allocation))
+            || message.contains("out of memory")
+        {
+            JavaScriptEngineError::MemoryLimitExceeded
+        } else {
+            other(message)
+        }
+    }
+}
+
+/// Global the sandbox handle of an evaluated script is kept under; defined
+/// read-only, so the script cannot replace it
+const SANDBOX_GLOBAL: &str = "__cipherSandbox";
+
+/// Run once after the player script: snapshots the global arrays (such as the
+/// lookup arrays n functions rotate in place) so they can be put back after
+/// every call, and caches the functions that calls look up
+const SANDBOX_SCRIPT: &str = r#"
+Object.defineProperty(globalThis, "__cipherSandbox", {
+    value: (function (global) {
+        var push = Array.prototype.push;
+        var functions = Object.create(null);
+        var arrays = Object.getOwnPropertyNames(global)
+            .filter(function (name) { return Array.isArray(global[name]); })
+            .map(function (name) {
+                return { name: name, array: global[name], items: global[name].slice() };
+            });
+        return Object.freeze({
+            lookup: function (name) {
+                if (!(name in functions)) functions[name] = global[name];
+                return functions[name];
+            },
+            restore: function () {
+                arrays.forEach(function (entry) {
+                    entry.array.length = 0;
+                    push.apply(entry.array, entry.items);
+                    global[entry.name] = entry.array;
+                });
+            },
+        });
+    })(globalThis),
+});
+"#;
+
+/// A context with one player script evaluated into it
+///
+/// The script is evaluated once, with a minimal set of built-ins; calls reuse
+/// its compiled functions. Global arrays are put back the way the script left
+/// them after every call, so lookup arrays an n function mutates never leak
+/// into the next call.
+///
+/// Each context owns its runtime so limits, interrupts and a failed call only
+/// ever affect that one context. `context` is declared first so it is dropped
+/// before its runtime.
+struct PooledContext {
+    context: Context,
+    _runtime: Runtime,
+    watchdog: Arc<Watchdog>,
+}
+
+impl PooledContext {
+    fn new(config: &JavaScriptEngineConfig, script: &str) -> Result<Self> {
+        let runtime =
+            Runtime::new().map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
+        runtime.set_memory_limit(config.memory_limit);
+        runtime.set_max_stack_size(config.max_stack_size);
+
+        let watchdog = Arc::new(Watchdog::default());
+        let handler_watchdog = watchdog.clone();
+        runtime.set_interrupt_handler(Some(Box::new(move || handler_watchdog.should_interrupt())));
+
+        let context = Context::custom::<SandboxIntrinsics>(&runtime)
+            .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
+
+        watchdog.arm(config.execution_timeout);
+        let evaluated = context.with(|ctx| {
+            ctx.eval::<(), _>(script)
+                .and_then(|()| ctx.eval::<(), _>(SANDBOX_SCRIPT))
+                .catch(&ctx)
+                .map_err(|e| watchdog.classify(e, JavaScriptEngineError::CompilationError))
+        });
+        watchdog.disarm();
+        evaluated?;
+
+        Ok(Self {
+            context,
+            _runtime: runtime,
+            watchdog,
+        })
+    }
+
+    fn call(&self, timeout: Duration, function_name: &str, argument: &str) -> Result<String> {
+        self.watchdog.arm(timeout);
+        let result = self
+            .context
+            .with(|ctx| call_function(&ctx, &self.watchdog, function_name, argument));
+        self.watchdog.disarm();
+        Ok(result?)
+    }
+}
+
+fn call_function(
+    ctx: &Ctx<'_>,
+    watchdog: &Watchdog,
+    function_name: &str,
+    argument: &str,
+) -> std::result::Result<String, JavaScriptEngineError> {
+    let runtime_error =
+        |e: CaughtError<'_>| watchdog.classify(e, JavaScriptEngineError::RuntimeError);
+    let sandbox: Object = ctx
+        .globals()
+        .get(SANDBOX_GLOBAL)
+        .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
+    let lookup: Function = sandbox
+        .get("lookup")
+        .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
+    let function = lookup
+        .call::<_, Value>((function_name,))
+        .catch(ctx)
+        .map_err(runtime_error)?
+        .into_function()
+        .ok_or_else(|| JavaScriptEngineError::FunctionNotFound(function_name.to_string()))?;
+
+    let result: Value = function
+        .call((argument,))
+        .catch(ctx)
+        .map_err(runtime_error)?;
+
+    let restore: Function = sandbox
+        .get("restore")
+        .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
+    restore
+        .call::<_, ()>(())
+        .catch(ctx)
+        .map_err(runtime_error)?;
+
+    result
+        .as_string()
+        .ok_or(JavaScriptEngineError::InvalidReturnType)?
+        .to_string()
+        .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))
+}
+
+/// Idle contexts of one player script
+struct ScriptContexts {
+    key: u64,
+    idle: Vec<PooledContext>,
+}
+
+struct ContextPool {
+    config: JavaScriptEngineConfig,
+    /// Least recently used script first
+    scripts: Mutex<Vec<ScriptContexts>>,
+}
+
+impl ContextPool {
+    fn take(&self, key: u64) -> Option<PooledContext> {
+        let mut scripts = self.scripts.lock().unwrap();
+        let index = scripts.iter().position(|entry| entry.key == key)?;
+        let entry = scripts.remove(index);
+        scripts.push(entry);
+        scripts.last_mut()?.idle.pop()
+    }
+
+    fn give_back(&self, key: u64, context: PooledContext) {
+        let mut scripts = self.scripts.lock().unwrap();
+        let entry = match scripts.iter().position(|entry| entry.key == key) {
+            Some(index) => &mut scripts[index],
+            None => {
+                if scripts.len() >= self.config.max_scripts {
+                    // Dropping the evicted contexts frees their runtimes
+                    scripts.remove(0);
+                }
+                scripts.push(ScriptContexts {
+                    key,
+                    idle: Vec::new(),
+                });
+                scripts.last_mut().unwrap()
+            }
+        };
+        if entry.idle.len() < self.config.contexts_per_script {
+            entry.idle.push(context);
+        }
+    }
+
+    fn idle_contexts(&self, key: u64) -> usize {
+        let scripts = self.scripts.lock().unwrap();
+        scripts
+            .iter()
+            .find(|entry| entry.key == key)
+            .map_or(0, |entry| entry.idle.len())
+    }
+}
+
+fn script_key(script: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    script.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A context checked out of the pool for one player script
+///
+/// Returned to the pool on drop, unless one of its calls failed: a context
+/// that was interrupted or ran out of memory may be left half way through a
+/// function and is discarded instead.
+pub struct JsContextGuard {
+    pool: Arc<ContextPool>,
+    key: u64,
+    context: Option<PooledContext>,
+    failed: bool,
+}
+
+impl std::fmt::Debug for JsContextGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsContextGuard")
+            .field("key", &self.key)
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
+
+impl JsContextGuard {
+    /// Call a global function of the script with a single string argument
+    pub fn call(&mut self, function_name: &str, argument: &str) -> Result<String> {
+        let context = self
+            .context
+            .as_ref()
+            .expect("context is only taken on drop");
+        let start_time = Instant::now();
+        let result = context.call(self.pool.config.execution_timeout, function_name, argument);
+        if result.is_err() {
+            self.failed = true;
+        }
+
+        let execution_time = start_time.elapsed();
+        if execution_time.as_millis() > 100 {
+            log::warn!(
+                "JavaScript function {function_name} took {}ms, target is <50ms",
+                execution_time.as_millis()
+            );
+        }
+
+        result
+    }
+}
+
+impl Drop for JsContextGuard {
+    fn drop(&mut self) {
+        if let Some(context) = self.context.take() {
+            if !self.failed {
+                self.pool.give_back(self.key, context);
+            }
+        }
+    }
 }
 
 /// JavaScript engine wrapper for executing YouTube cipher operations
+///
+/// Keeps a pool of sandboxed contexts per player script with the script
+/// already evaluated, so its functions are compiled once and reused. Clones
+/// share the pool.
+#[derive(Clone)]
 pub struct JavaScriptEngine {
-    runtime: Runtime,
+    pool: Arc<ContextPool>,
 }
 
 impl std::fmt::Debug for JavaScriptEngine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("JavaScriptEngine")
-            .field("runtime", &"<QuickJS Runtime>")
+            .field("config", &self.pool.config)
             .finish()
     }
 }
 
-impl Clone for JavaScriptEngine {
-    fn clone(&self) -> Self {
-        // Create a new runtime for the cloned instance
-        Self::new().expect("Failed to create cloned JavaScript engine")
-    }
-}
-
 impl JavaScriptEngine {
     /// Create a new JavaScript engine instance
     pub fn new() -> Result<Self> {
-        let runtime =
-            Runtime::new().map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
+        Ok(Self::with_config(JavaScriptEngineConfig::default()))
+    }
+
+    pub fn with_config(config: JavaScriptEngineConfig) -> Self {
+        Self {
+            pool: Arc::new(ContextPool {
+                config,
+                scripts: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &JavaScriptEngineConfig {
+        &self.pool.config
+    }
+
+    /// Check out a context with `script` evaluated, creating one if none is idle
+    pub fn checkout(&self, script: &str) -> Result<JsContextGuard> {
+        let key = script_key(script);
+        let context = match self.pool.take(key) {
+            Some(context) => context,
+            None => PooledContext::new(&self.pool.config, script)?,
+        };
+
+        Ok(JsContextGuard {
+            pool: self.pool.clone(),
+            key,
+            context: Some(context),
+            failed: false,
+        })
+    }
+
+    /// Evaluate `script` into an idle context ahead of its first call
+    pub fn prewarm(&self, script: &str) -> Result<()> {
+        let key = script_key(script);
+        if self.pool.idle_contexts(key) == 0 {
+            self.checkout(script)?;
+        }
+        Ok(())
+    }
 
-        Ok(Self { runtime })
+    /// Number of idle contexts pooled for `script`
+    pub fn idle_contexts(&self, script: &str) -> usize {
+        self.pool.idle_contexts(script_key(script))
     }
 
     /// Execute a cipher function with the given signature
@@ -53,45 +458,7 @@ impl JavaScriptEngine {
         function_name: &str,
         signature: &str,
     ) -> Result<String> {
-        let start_time = Instant::now();
-
-        let context = Context::full(&self.runtime)
-            .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
-
-        context.with(|ctx| {
-            // Execute the script to define functions
-            ctx.eval::<(), _>(script)
-                .map_err(|e| JavaScriptEngineError::CompilationError(e.to_string()))?;
-
-            // Get the cipher function
-            let function: Function = ctx
-                .globals()
-                .get(function_name)
-                .map_err(|_| JavaScriptEngineError::FunctionNotFound(function_name.to_string()))?;
-
-            // Call the function with the signature
-            let result: Value = function
-                .call((signature,))
-                .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
-
-            // Convert result to string
-            let deciphered_signature: String = result
-                .as_string()
-                .ok_or(JavaScriptEngineError::InvalidReturnType)?
-                .to_string()
-                .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
-
-            // Check for timeout (should be <50ms for performance)
-            let execution_time = start_time.elapsed();
-            if execution_time.as_millis() > 100 {
-                log::warn!(
-                    "Cipher execution took {}ms, target is <50ms",
-                    execution_time.as_millis()
-                );
-            }
-
-            Ok(deciphered_signature)
-        })
+        self.checkout(script)?.call(function_name, signature)
     }
 
     /// Execute an N parameter transformation function
@@ -101,45 +468,7 @@ impl JavaScriptEngine {
         function_name: &str,
         n_parameter: &str,
     ) -> Result<String> {
-        let start_time = Instant::now();
-
-        let context = Context::full(&self.runtime)
-            .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
-
-        context.with(|ctx| {
-            // Execute the script to define functions
-            ctx.eval::<(), _>(script)
-                .map_err(|e| JavaScriptEngineError::CompilationError(e.to_string()))?;
-
-            // Get the N transform function
-            let function: Function = ctx
-                .globals()
-                .get(function_name)
-                .map_err(|_| JavaScriptEngineError::FunctionNotFound(function_name.to_string()))?;
-
-            // Call the function with the N parameter
-            let result: Value = function
-                .call((n_parameter,))
-                .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
-
-            // Convert result to string
-            let transformed_n: String = result
-                .as_string()
-                .ok_or(JavaScriptEngineError::InvalidReturnType)?
-                .to_string()
-                .map_err(|e| JavaScriptEngineError::RuntimeError(e.to_string()))?;
-
-            // Check for timeout
-            let execution_time = start_time.elapsed();
-            if execution_time.as_millis() > 100 {
-                log::warn!(
-                    "N transform execution took {}ms, target is <50ms",
-                    execution_time.as_millis()
-                );
-            }
-
-            Ok(transformed_n)
-        })
+        self.checkout(script)?.call(function_name, n_parameter)
     }
 
     /// Test the JavaScript engine with a simple operation
@@ -250,4 +579,195 @@ mod tests {
         let result = engine.test_engine();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_context_is_reused_between_calls() {
+        let engine = JavaScriptEngine::new().unwrap();
+        let script = r#"
+            var calls = 0;
+            function count(input) {
+                calls += 1;
+                return input + calls;
+            }
+        "#;
+
+        engine.prewarm(script).unwrap();
+        assert_eq!(engine.idle_contexts(script), 1);
+
+        // The script is not evaluated again, so the counter keeps going
+        assert_eq!(
+            engine
+                .execute_cipher_function(script, "count", "a")
+                .unwrap(),
+            "a1"
+        );
+        assert_eq!(
+            engine
+                .execute_cipher_function(script, "count", "b")
+                .unwrap(),
+            "b2"
+        );
+
+        let mut context = engine.checkout(script).unwrap();
+        assert_eq!(engine.idle_contexts(script), 0);
+        assert_eq!(context.call("count", "c").unwrap(), "c3");
+        assert_eq!(context.call("count", "d").unwrap(), "d4");
+        drop(context);
+        assert_eq!(engine.idle_contexts(script), 1);
+    }
+
+    #[test]
+    fn test_mutated_global_arrays_do_not_leak() {
+        let engine = JavaScriptEngine::new().unwrap();
+        // Like n functions, which rotate a global lookup array in place, or
+        // replace it altogether
+        let script = r#"
+            var table = ["a", "b", "c"];
+            var alias = table;
+            function rotate(input) {
+                table.push(table.shift());
+                return input + table.join("") + alias.length;
+            }
+            function replace(input) {
+                table = [input];
+                return input;
+            }
+        "#;
+
+        for input in ["x", "y", "z"] {
+            assert_eq!(
+                engine
+                    .execute_n_transform_function(script, "rotate", input)
+                    .unwrap(),
+                format!("{input}bca3")
+            );
+        }
+
+        let mut context = engine.checkout(script).unwrap();
+        assert_eq!(context.call("replace", "q").unwrap(), "q");
+        assert_eq!(context.call("rotate", "x").unwrap(), "xbca3");
+    }
+
+    #[test]
+    fn test_only_sandbox_intrinsics_are_available() {
+        let engine = JavaScriptEngine::new().unwrap();
+        let script = r#"
+            function globals(input) {
+                return [
+                    typeof String, typeof Math, typeof RegExp, typeof JSON, typeof Date,
+                    typeof Promise, typeof Proxy, typeof Uint8Array, typeof Map,
+                    typeof WeakRef, typeof BigInt, typeof performance,
+                ].join(",");
+            }
+        "#;
+
+        assert_eq!(
+            engine
+                .execute_cipher_function(script, "globals", "")
+                .unwrap(),
+            "function,object,function,object,function,\
+             undefined,undefined,undefined,undefined,undefined,undefined,undefined"
+        );
+    }
+
+    #[test]
+    fn test_pool_is_bounded() {
+        let engine = JavaScriptEngine::with_config(
+            JavaScriptEngineConfig::default()
+                .set_contexts_per_script(2)
+                .set_max_scripts(2),
+        );
+        let scripts: Vec<String> = (0..3)
+            .map(|i| format!("function f(x) {{ return x + {i}; }}"))
+            .collect();
+
+        let checked_out: Vec<_> = (0..3)
+            .map(|_| engine.checkout(&scripts[0]).unwrap())
+            .collect();
+        drop(checked_out);
+        assert_eq!(engine.idle_contexts(&scripts[0]), 2);
+
+        engine.prewarm(&scripts[1]).unwrap();
+        engine.prewarm(&scripts[2]).unwrap();
+        assert_eq!(engine.idle_contexts(&scripts[0]), 0);
+        assert_eq!(engine.idle_contexts(&scripts[1]), 1);
+        assert_eq!(engine.idle_contexts(&scripts[2]), 1);
+    }
+
+    #[test]
+    fn test_execution_timeout() {
+        let engine = JavaScriptEngine::with_config(
+            JavaScriptEngineConfig::default().set_execution_timeout(Duration::from_millis(50)),
+        );
+        let script = r#"
+            function spin(input) {
+                while (true) {}
+            }
+        "#;
+
+        let start = Instant::now();
+        let result = engine.execute_cipher_function(script, "spin", "x");
+        assert!(matches!(
+            result,
+            Err(crate::YoutubeError::JavaScriptEngine(
+                JavaScriptEngineError::ExecutionTimeout
+            ))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // The interrupted context is not handed out again
+        assert_eq!(engine.idle_contexts(script), 0);
+    }
+
+    #[test]
+    fn test_timeout_while_evaluating_script() {
+        let engine = JavaScriptEngine::with_config(
+            JavaScriptEngineConfig::default().set_execution_timeout(Duration::from_millis(50)),
+        );
+
+        let result = engine.checkout("for (;;) {}");
+        assert!(matches!(
+            result,
+            Err(crate::YoutubeError::JavaScriptEngine(
+                JavaScriptEngineError::ExecutionTimeout
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        let engine = JavaScriptEngine::with_config(
+            JavaScriptEngineConfig::default()
+                .set_memory_limit(4 * 1024 * 1024)
+                .set_execution_timeout(Duration::from_secs(10)),
+        );
+        let script = r#"
+            function grow(input) {
+                var chunks = [];
+                while (true) {
+                    chunks.push(new Array(65536).fill(input));
+                }
+            }
+        "#;
+
+        let result = engine.execute_cipher_function(script, "grow", "x");
+        assert!(matches!(
+            result,
+            Err(crate::YoutubeError::JavaScriptEngine(
+                JavaScriptEngineError::MemoryLimitExceeded
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_missing_function() {
+        let engine = JavaScriptEngine::new().unwrap();
+        let result = engine.execute_cipher_function("var x = 1;", "sig", "x");
+        assert!(matches!(
+            result,
+            Err(crate::YoutubeError::JavaScriptEngine(
+                JavaScriptEngineError::FunctionNotFound(_)
+            ))
+        ));
+    }
 }
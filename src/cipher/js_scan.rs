@@ -0,0 +1,540 @@
+//! Lightweight JavaScript tokenizer for locating functions in player scripts
+//!
+//! This is not a parser: it only knows enough of the grammar to skip strings,
+//! template literals, regular expressions and comments, so that brackets can
+//! be matched and functions found by their structure rather than by layout.
+
+use std::collections::HashMap;
+
+/// Marker the n function returns, prefixed to its input, when it throws
+pub(crate) const N_FUNCTION_MARKER: &str = "enhanced_except_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Ident,
+    Number,
+    String,
+    Template,
+    Regex,
+    Punct,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Keywords after which a `/` starts a regular expression, not a division
+const REGEX_PREFIX_KEYWORDS: &[&str] = &[
+    "return", "typeof", "case", "do", "else", "in", "of", "new", "delete", "void", "throw",
+];
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '$' || !c.is_ascii()
+}
+
+fn is_ident_char(c: char) -> bool {
+    is_ident_start(c) || c.is_ascii_digit()
+}
+
+/// Split `source` into tokens, dropping whitespace and comments
+///
+/// Punctuation is returned one character at a time. Unterminated literals
+/// run to the end of the source.
+pub(crate) fn tokenize(source: &str) -> Vec<Token> {
+    let bytes = source.as_bytes();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = source[i..].chars().next().unwrap();
+        let start = i;
+
+        let kind = match c {
+            _ if c.is_whitespace() => {
+                i += c.len_utf8();
+                continue;
+            }
+            '/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                continue;
+            }
+            '/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = source[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |n| i + n + 4);
+                continue;
+            }
+            '"' | '\'' => {
+                i = skip_string(bytes, i);
+                TokenKind::String
+            }
+            '`' => {
+                i = skip_template(bytes, i);
+                TokenKind::Template
+            }
+            '/' if regex_allowed(source, tokens.last()) => {
+                i = skip_regex(bytes, i);
+                TokenKind::Regex
+            }
+            _ if c.is_ascii_digit()
+                || (c == '.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)) =>
+            {
+                i += source[i..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+                    .unwrap_or(bytes.len() - i);
+                TokenKind::Number
+            }
+            _ if is_ident_start(c) => {
+                i += source[i..]
+                    .find(|c: char| !is_ident_char(c))
+                    .unwrap_or(bytes.len() - i);
+                TokenKind::Ident
+            }
+            _ => {
+                i += c.len_utf8();
+                TokenKind::Punct
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            start,
+            end: i,
+        });
+    }
+
+    tokens
+}
+
+fn regex_allowed(source: &str, previous: Option<&Token>) -> bool {
+    match previous {
+        None => true,
+        Some(token) => match token.kind {
+            TokenKind::Ident => REGEX_PREFIX_KEYWORDS.contains(&&source[token.start..token.end]),
+            TokenKind::Punct => !matches!(&source[token.start..token.end], ")" | "]"),
+            _ => false,
+        },
+    }
+}
+
+/// Index just past the string literal starting at `start`
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Index just past the template literal starting at `start`
+fn skip_template(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    let mut depth = 0usize;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' if depth == 0 => return i + 1,
+            b'$' if depth == 0 && bytes.get(i + 1) == Some(&b'{') => {
+                depth = 1;
+                i += 2;
+            }
+            b'{' if depth > 0 => {
+                depth += 1;
+                i += 1;
+            }
+            b'}' if depth > 0 => {
+                depth -= 1;
+                i += 1;
+            }
+            b'"' | b'\'' if depth > 0 => i = skip_string(bytes, i),
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Index just past the regular expression literal starting at `start`
+fn skip_regex(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    let mut in_class = false;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' => return i,
+            b'[' => {
+                in_class = true;
+                i += 1;
+            }
+            b']' => {
+                in_class = false;
+                i += 1;
+            }
+            b'/' if !in_class => {
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                return i;
+            }
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Tokenized source with helpers for walking it
+pub(crate) struct Scanner<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+}
+
+/// A `function` expression or declaration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FunctionSpan {
+    /// Token index of the `function` keyword
+    pub first: usize,
+    /// Token index of the closing brace of the body
+    pub last: usize,
+    pub params: Vec<String>,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            tokens: tokenize(source),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn text(&self, index: usize) -> &'a str {
+        let token = self.tokens[index];
+        &self.source[token.start..token.end]
+    }
+
+    pub fn kind(&self, index: usize) -> TokenKind {
+        self.tokens[index].kind
+    }
+
+    fn is(&self, index: usize, text: &str) -> bool {
+        index < self.tokens.len() && self.text(index) == text
+    }
+
+    /// Source text from token `first` through token `last`
+    pub fn slice(&self, first: usize, last: usize) -> &'a str {
+        &self.source[self.tokens[first].start..self.tokens[last].end]
+    }
+
+    /// Index of the bracket closing the one at `open`
+    pub fn matching_close(&self, open: usize) -> Option<usize> {
+        let mut stack = Vec::new();
+        for index in open..self.tokens.len() {
+            if self.kind(index) != TokenKind::Punct {
+                continue;
+            }
+            match self.text(index) {
+                "(" => stack.push(")"),
+                "[" => stack.push("]"),
+                "{" => stack.push("}"),
+                close @ (")" | "]" | "}") => {
+                    if stack.pop() != Some(close) {
+                        return None;
+                    }
+                    if stack.is_empty() {
+                        return Some(index);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Every function in the source, outer functions before the ones they contain
+    pub fn functions(&self) -> Vec<FunctionSpan> {
+        let mut functions = Vec::new();
+        for first in 0..self.tokens.len() {
+            if self.kind(first) != TokenKind::Ident || self.text(first) != "function" {
+                continue;
+            }
+
+            let mut open = first + 1;
+            if open < self.len() && self.kind(open) == TokenKind::Ident {
+                open += 1;
+            }
+            if !self.is(open, "(") {
+                continue;
+            }
+            let Some(close) = self.matching_close(open) else {
+                continue;
+            };
+            if !self.is(close + 1, "{") {
+                continue;
+            }
+            let Some(last) = self.matching_close(close + 1) else {
+                continue;
+            };
+
+            let params = (open + 1..close)
+                .filter(|&index| self.kind(index) == TokenKind::Ident)
+                .map(|index| self.text(index).to_string())
+                .collect();
+            functions.push(FunctionSpan {
+                first,
+                last,
+                params,
+            });
+        }
+        functions
+    }
+
+    /// String lookup tables assigned anywhere in the source, by name
+    ///
+    /// Recognises `NAME="a;b;c".split(";")` and `NAME=["a","b","c"]`.
+    pub fn lookup_tables(&self) -> HashMap<String, Vec<String>> {
+        let mut tables = HashMap::new();
+        for index in 0..self.len().saturating_sub(2) {
+            if self.kind(index) != TokenKind::Ident || !self.is(index + 1, "=") {
+                continue;
+            }
+            // `a==b` and `a=>b` are not assignments
+            if self.is(index + 2, "=") || self.is(index + 2, ">") {
+                continue;
+            }
+            if let Some(values) = self.table_at(index + 2) {
+                tables.insert(self.text(index).to_string(), values);
+            }
+        }
+        tables
+    }
+
+    fn table_at(&self, index: usize) -> Option<Vec<String>> {
+        if self.kind(index) == TokenKind::String
+            && self.is(index + 1, ".")
+            && self.is(index + 2, "split")
+            && self.is(index + 3, "(")
+            && index + 4 < self.len()
+            && self.kind(index + 4) == TokenKind::String
+            && self.is(index + 5, ")")
+        {
+            let value = unquote(self.text(index))?;
+            let separator = unquote(self.text(index + 4))?;
+            return Some(if separator.is_empty() {
+                value.chars().map(String::from).collect()
+            } else {
+                value.split(separator.as_str()).map(String::from).collect()
+            });
+        }
+
+        if self.is(index, "[") {
+            let close = self.matching_close(index)?;
+            let mut values = Vec::new();
+            for (position, item) in (index + 1..close).enumerate() {
+                match (position % 2, self.kind(item)) {
+                    (0, TokenKind::String) => values.push(unquote(self.text(item))?),
+                    (1, TokenKind::Punct) if self.text(item) == "," => {}
+                    _ => return None,
+                }
+            }
+            return Some(values);
+        }
+
+        None
+    }
+
+    /// `NAME[index]` lookups between tokens `first` and `last`, as
+    /// (token index of the name, name, index)
+    pub fn lookups(&self, first: usize, last: usize) -> Vec<(usize, &'a str, usize)> {
+        (first..=last.saturating_sub(3))
+            .filter(|&index| {
+                self.kind(index) == TokenKind::Ident
+                    && (index == 0 || !self.is(index - 1, "."))
+                    && self.is(index + 1, "[")
+                    && self.kind(index + 2) == TokenKind::Number
+                    && self.is(index + 3, "]")
+            })
+            .filter_map(|index| {
+                let position = self.text(index + 2).parse().ok()?;
+                Some((index, self.text(index), position))
+            })
+            .collect()
+    }
+
+    /// Find the n function by its structure
+    ///
+    /// The n function takes one argument, wraps its work in `try`/`catch`, and
+    /// refers to the `enhanced_except_` marker either directly or through a
+    /// lookup table. The innermost function with all three traits is returned.
+    pub fn find_n_function(&self, tables: &HashMap<String, Vec<String>>) -> Option<FunctionSpan> {
+        self.functions()
+            .into_iter()
+            .filter(|function| function.params.len() == 1)
+            .filter(|function| {
+                let body = function.first..=function.last;
+                body.clone().any(|index| self.text(index) == "try")
+                    && body.clone().any(|index| self.text(index) == "catch")
+                    && self.refers_to_marker(function, tables)
+            })
+            .min_by_key(|function| function.last - function.first)
+    }
+
+    fn refers_to_marker(
+        &self,
+        function: &FunctionSpan,
+        tables: &HashMap<String, Vec<String>>,
+    ) -> bool {
+        let direct = (function.first..=function.last).any(|index| {
+            self.kind(index) == TokenKind::String && self.text(index).contains(N_FUNCTION_MARKER)
+        });
+        direct
+            || self.lookups(function.first, function.last).into_iter().any(
+                |(_, table, position)| {
+                    tables
+                        .get(table)
+                        .and_then(|values| values.get(position))
+                        .is_some_and(|value| value.contains(N_FUNCTION_MARKER))
+                },
+            )
+    }
+
+    /// Source of tokens `first` through `last` with the given table lookups
+    /// replaced by the string literals they resolve to
+    pub fn inline_lookups(
+        &self,
+        first: usize,
+        last: usize,
+        tables: &HashMap<String, Vec<String>>,
+        names: &[String],
+    ) -> String {
+        let mut output = String::new();
+        let mut copied_to = self.tokens[first].start;
+        for (index, table, position) in self.lookups(first, last) {
+            if !names.iter().any(|name| name == table) {
+                continue;
+            }
+            let Some(value) = tables.get(table).and_then(|values| values.get(position)) else {
+                continue;
+            };
+            output.push_str(&self.source[copied_to..self.tokens[index].start]);
+            output.push_str(&serde_json::Value::from(value.as_str()).to_string());
+            copied_to = self.tokens[index + 3].end;
+        }
+        output.push_str(&self.source[copied_to..self.tokens[last].end]);
+        output
+    }
+}
+
+/// Value of a JavaScript string literal, including its quotes
+pub(crate) fn unquote(literal: &str) -> Option<String> {
+    let inner = literal.get(1..literal.len().checked_sub(1)?)?;
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            'b' => value.push('\u{8}'),
+            'f' => value.push('\u{c}'),
+            'v' => value.push('\u{b}'),
+            '0' => value.push('\0'),
+            'x' => {
+                let code: String = chars.by_ref().take(2).collect();
+                value.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            'u' => {
+                let code: String = chars.by_ref().take(4).collect();
+                value.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            '\n' => {}
+            other => value.push(other),
+        }
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(source: &str) -> Vec<&str> {
+        tokenize(source)
+            .into_iter()
+            .map(|token| &source[token.start..token.end])
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_skips_literals_and_comments() {
+        let source = r#"a=/[/{]/g.test(b)?"}\"{":`x${c+"}"}`;// { not code
+        d=e/2/f;/* } */"#;
+        assert_eq!(
+            texts(source),
+            vec![
+                "a",
+                "=",
+                "/[/{]/g",
+                ".",
+                "test",
+                "(",
+                "b",
+                ")",
+                "?",
+                r#""}\"{""#,
+                ":",
+                r#"`x${c+"}"}`"#,
+                ";",
+                "d",
+                "=",
+                "e",
+                "/",
+                "2",
+                "/",
+                "f",
+                ";"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_finds_innermost_n_function_and_inlines_tables() {
+        let source = r#"(function(g){var T="split;enhanced_except_;join".split(";");
+            g.f=function(a){var b=a[T[0]](""),c=[function(d){d.reverse()}];
+            try{c[0](b)}catch(e){return T[1]+a}return b[T[2]]("")};})(_yt_player);"#;
+        let scanner = Scanner::new(source);
+        let tables = scanner.lookup_tables();
+        assert_eq!(tables["T"], vec!["split", "enhanced_except_", "join"]);
+
+        let function = scanner.find_n_function(&tables).unwrap();
+        assert_eq!(function.params, vec!["a"]);
+        assert!(scanner
+            .slice(function.first, function.last)
+            .starts_with("function(a){var b=a[T[0]]"));
+
+        let inlined =
+            scanner.inline_lookups(function.first, function.last, &tables, &["T".to_string()]);
+        assert!(inlined.starts_with(r#"function(a){var b=a["split"]("")"#));
+        assert!(inlined.ends_with(r#"return "enhanced_except_"+a}return b["join"]("")}"#));
+    }
+
+    #[test]
+    fn test_unquote() {
+        assert_eq!(unquote(r#""a\"b\x41B""#).as_deref(), Some("a\"bAB"));
+        assert_eq!(unquote("'it\\'s'").as_deref(), Some("it's"));
+    }
+}
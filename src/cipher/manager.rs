@@ -1,20 +1,79 @@
-use super::{AdvancedSignatureCipher, ExtractedCipher, ScriptParser, SignatureCipher};
-use crate::{Result, StreamFormat};
+use super::cache::{player_hash, CachedCipher, CipherCache, MemoryCipherCache};
+use super::memo::DecipherMemo;
+use super::{
+    AdvancedSignatureCipher, CipherError, CipherSolver, ExtractedCipher, JavaScriptEngine,
+    RemoteCipherSolver, ScriptParser, SignatureCipher, SolveRequest,
+};
+use crate::http::{RequestContext, YoutubeHttpClient};
+use crate::{Result, StreamFormat, YoutubeError};
+use regex::Regex;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{OwnedMutexGuard, RwLock};
 use url::Url;
 
+/// How long the tracked player script is used before checking for a new one
+pub const DEFAULT_PLAYER_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const YOUTUBE_BASE_URL: &str = "https://www.youtube.com";
+
+/// Key player script and page fetches are rate limited and backed off under,
+/// so that a 429 on them leaves the clients alone
+const PLAYER_SCRIPT_CLIENT: &str = "PLAYER_SCRIPT";
+
 #[derive(Debug, Clone)]
 pub struct SignatureCipherManager {
-    #[allow(clippy::arc_with_non_send_sync)]
     cached_scripts: Arc<RwLock<HashMap<String, CachedPlayerScript>>>,
-    http_client: reqwest::Client,
+    /// Player scripts being loaded, so that concurrent loads share one fetch
+    in_flight: InFlight,
+    /// Backend that keeps extracted ciphers across restarts
+    cipher_cache: Arc<dyn CipherCache>,
+    /// Context pool shared by every advanced cipher this manager creates
+    js_engine: JavaScriptEngine,
+    /// Deciphered signatures and n parameters shared by every advanced cipher
+    memo: Arc<DecipherMemo>,
+    /// Solver used instead of the embedded JavaScript engine
+    solver: Option<Arc<dyn CipherSolver>>,
+    /// Player script YouTube currently serves, refreshed lazily
+    current_player: Arc<RwLock<Option<PlayerScriptInfo>>>,
+    /// Held while looking up the current player, for the same reason
+    player_lookup: Arc<tokio::sync::Mutex<()>>,
+    player_refresh_interval: Duration,
+    /// Origin of the iframe API, embed page and player scripts
+    base_url: String,
+    /// Transport of player script and page fetches, so that they share the
+    /// proxies, route planner and rate limits of every other request
+    http_client: YoutubeHttpClient,
+}
+
+/// The player script YouTube currently serves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerScriptInfo {
+    pub url: Url,
+    /// Player version, the hash in `/s/player/<version>/`
+    pub version: String,
+    /// `signatureTimestamp` (`sts`) that player requests must send so that
+    /// signatures in the response match this script
+    pub signature_timestamp: u32,
+    pub fetched_at: SystemTime,
+}
+
+impl PlayerScriptInfo {
+    /// Add the signature timestamp to the `contentPlaybackContext` of an
+    /// Innertube player request
+    pub fn apply_to_player_payload(&self, payload: &mut serde_json::Value) {
+        payload["playbackContext"]["contentPlaybackContext"]["signatureTimestamp"] =
+            self.signature_timestamp.into();
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct CachedPlayerScript {
+    /// Empty when the cipher was restored from the cipher cache
     pub script_content: String,
-    pub cipher: SignatureCipher,
+    /// Basic cipher, `None` when the script's sig actions could not be parsed
+    pub cipher: Option<SignatureCipher>,
     pub advanced_cipher: Option<AdvancedSignatureCipher>,
     pub extracted_cipher: Option<ExtractedCipher>,
     pub cached_at: std::time::SystemTime,
@@ -29,21 +88,124 @@ impl Default for SignatureCipherManager {
 impl SignatureCipherManager {
     pub fn new() -> Self {
         Self {
-            #[allow(clippy::arc_with_non_send_sync)]
             cached_scripts: Arc::new(RwLock::new(HashMap::new())),
-            http_client: reqwest::Client::new(),
+            in_flight: InFlight::default(),
+            cipher_cache: Arc::new(MemoryCipherCache::new()),
+            js_engine: JavaScriptEngine::default(),
+            memo: Arc::new(DecipherMemo::default()),
+            solver: None,
+            current_player: Arc::new(RwLock::new(None)),
+            player_lookup: Arc::new(tokio::sync::Mutex::new(())),
+            player_refresh_interval: DEFAULT_PLAYER_REFRESH_INTERVAL,
+            base_url: YOUTUBE_BASE_URL.to_string(),
+            http_client: YoutubeHttpClient::default(),
         }
     }
 
+    /// Fetch player scripts and pages through `http_client`
+    ///
+    /// Only the transport changes: copies of a shared manager keep its
+    /// caches and solver.
+    pub fn with_http_client(mut self, http_client: YoutubeHttpClient) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Use `cipher_cache` to store extracted ciphers, e.g. a
+    /// [`FileCipherCache`](super::FileCipherCache) to survive restarts
+    ///
+    /// Its TTL also bounds how long parsed scripts stay in memory.
+    pub fn with_cipher_cache(mut self, cipher_cache: Arc<dyn CipherCache>) -> Self {
+        self.cipher_cache = cipher_cache;
+        self
+    }
+
+    /// Run advanced ciphers in `js_engine`, e.g. one with tighter limits
+    pub fn with_js_engine(mut self, js_engine: JavaScriptEngine) -> Self {
+        self.js_engine = js_engine;
+        self
+    }
+
+    /// Keep at most `capacity` deciphered results in the memo; 0 disables it
+    pub fn with_memo_capacity(mut self, capacity: usize) -> Self {
+        self.memo = Arc::new(DecipherMemo::new(capacity));
+        self
+    }
+
+    /// Solve signatures and n parameters with `solver`, e.g. a
+    /// [`RemoteCipherSolver`], instead of the embedded JavaScript engine
+    pub fn with_solver(mut self, solver: Arc<dyn CipherSolver>) -> Self {
+        self.solver = Some(solver);
+        self
+    }
+
+    /// Select the solver from plugin configuration: the remote service when
+    /// one is configured, the embedded JavaScript engine otherwise
+    pub fn with_config(self, config: &crate::YoutubeConfig) -> Result<Self> {
+        let Some(remote) = &config.remote_cipher else {
+            return Ok(self);
+        };
+        let solver = RemoteCipherSolver::from_config(remote, self.http_client.client().clone())?;
+        log::info!(
+            "Solving ciphers with the remote service at {}",
+            solver.endpoint()
+        );
+        Ok(self.with_solver(Arc::new(solver)))
+    }
+
+    /// The solver used instead of the embedded JavaScript engine, if any
+    pub fn solver(&self) -> Option<&Arc<dyn CipherSolver>> {
+        self.solver.as_ref()
+    }
+
+    /// Look up the current player at `base_url` instead of YouTube
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_player_refresh_interval(mut self, interval: Duration) -> Self {
+        self.player_refresh_interval = interval;
+        self
+    }
+
+    pub fn cipher_cache(&self) -> &Arc<dyn CipherCache> {
+        &self.cipher_cache
+    }
+
+    fn is_fresh(&self, cached: &CachedPlayerScript) -> bool {
+        cached.cached_at.elapsed().unwrap_or_default() < self.cipher_cache.ttl()
+    }
+
+    /// Resolve the playable URL of a format
+    ///
+    /// When the n parameter transformation fails, the player script is
+    /// refreshed and the format deciphered once more; if that fails too the
+    /// [`CipherError::NTransformFailed`] is returned, so callers can move on
+    /// to a client that needs no player script.
     pub async fn resolve_format_url(
         &self,
         player_script_url: &Url,
         format: &StreamFormat,
     ) -> Result<Url> {
+        if let Some(solver) = &self.solver {
+            let mut urls = self
+                .solve_format_urls(
+                    solver.as_ref(),
+                    player_script_url,
+                    std::slice::from_ref(format),
+                )
+                .await?;
+            return Ok(urls.remove(0));
+        }
+
         // Try advanced cipher first, fallback to basic cipher
         if let Ok(advanced_cipher) = self.get_advanced_cipher(player_script_url).await {
             log::debug!("Using advanced JavaScript-based cipher for URL resolution");
-            advanced_cipher.decipher_url(format)
+            self.retry_after_refresh(player_script_url, advanced_cipher, |cipher| {
+                cipher.decipher_url(format)
+            })
+            .await
         } else {
             log::warn!("Advanced cipher failed, falling back to basic cipher operations");
             let cipher = self.get_cipher(player_script_url).await?;
@@ -51,37 +213,244 @@ impl SignatureCipherManager {
         }
     }
 
-    pub async fn get_cipher(&self, player_script_url: &Url) -> Result<SignatureCipher> {
-        let url_string = player_script_url.to_string();
+    /// Resolve the URLs of many formats of one video, deciphering them all in
+    /// a single JavaScript context
+    ///
+    /// Failed n parameter transformations are handled like in
+    /// [`resolve_format_url`](Self::resolve_format_url).
+    pub async fn resolve_format_urls(
+        &self,
+        player_script_url: &Url,
+        formats: &[StreamFormat],
+    ) -> Result<Vec<Url>> {
+        if let Some(solver) = &self.solver {
+            return self
+                .solve_format_urls(solver.as_ref(), player_script_url, formats)
+                .await;
+        }
+
+        if let Ok(advanced_cipher) = self.get_advanced_cipher(player_script_url).await {
+            self.retry_after_refresh(player_script_url, advanced_cipher, |cipher| {
+                cipher.decipher_urls(formats)
+            })
+            .await
+        } else {
+            log::warn!("Advanced cipher failed, falling back to basic cipher operations");
+            let cipher = self.get_cipher(player_script_url).await?;
+            formats
+                .iter()
+                .map(|format| cipher.decipher_url(format))
+                .collect()
+        }
+    }
+
+    /// Resolve format URLs with one request to `solver`
+    ///
+    /// Formats of a video share their n value, so each distinct value is only
+    /// sent once.
+    async fn solve_format_urls(
+        &self,
+        solver: &dyn CipherSolver,
+        player_script_url: &Url,
+        formats: &[StreamFormat],
+    ) -> Result<Vec<Url>> {
+        let mut request = SolveRequest {
+            player_url: player_script_url.to_string(),
+            signatures: Vec::new(),
+            n: Vec::new(),
+        };
+        for format in formats {
+            if let Some(signature) = &format.signature {
+                request.signatures.push(signature.clone());
+            }
+            if let Some(n_param) = format
+                .n_parameter
+                .as_ref()
+                .filter(|n| !request.n.contains(n))
+            {
+                request.n.push(n_param.clone());
+            }
+        }
 
-        // Check cache first
+        // Any solver can be plugged in, so its answer is checked here rather
+        // than trusted to match the request
+        let solved = solver.solve(&request).await?;
+        if solved.signatures.len() != request.signatures.len() || solved.n.len() != request.n.len()
         {
-            let scripts = self.cached_scripts.read().unwrap();
-            if let Some(cached) = scripts.get(&url_string) {
-                // Check if cache is still valid (e.g., less than 1 hour old)
-                if cached.cached_at.elapsed().unwrap_or_default().as_secs() < 3600 {
-                    return Ok(cached.cipher.clone());
+            return Err(YoutubeError::Cipher(format!(
+                "Cipher solver answered {} signatures and {} n values for {} and {}",
+                solved.signatures.len(),
+                solved.n.len(),
+                request.signatures.len(),
+                request.n.len()
+            )));
+        }
+        let n_values: HashMap<&str, String> = request
+            .n
+            .iter()
+            .zip(solved.n)
+            .map(|(n_param, result)| {
+                AdvancedSignatureCipher::check_n_result(n_param, result)
+                    .map(|result| (n_param.as_str(), result))
+            })
+            .collect::<Result<_>>()?;
+
+        let mut signatures = solved.signatures.into_iter();
+        formats
+            .iter()
+            .map(|format| {
+                let mut url = format.url.clone();
+                if format.signature.is_some() {
+                    let signature = signatures.next().ok_or_else(|| {
+                        YoutubeError::Cipher(format!(
+                            "Cipher solver gave no signature for itag {}",
+                            format.itag
+                        ))
+                    })?;
+                    url = AdvancedSignatureCipher::build_url_with_signature(format, &signature)?;
+                }
+                if let Some(n_param) = &format.n_parameter {
+                    let n_value = n_values.get(n_param.as_str()).ok_or_else(|| {
+                        YoutubeError::Cipher(format!("Cipher solver gave no n value for {n_param}"))
+                    })?;
+                    url = AdvancedSignatureCipher::add_n_parameter_to_url(url, n_value)?;
                 }
+                Ok(url)
+            })
+            .collect()
+    }
+
+    /// Run `decipher`, and once more with a refreshed player script if the n
+    /// parameter transformation failed
+    async fn retry_after_refresh<T>(
+        &self,
+        player_script_url: &Url,
+        cipher: AdvancedSignatureCipher,
+        decipher: impl Fn(&AdvancedSignatureCipher) -> Result<T>,
+    ) -> Result<T> {
+        let error = match decipher(&cipher) {
+            Err(YoutubeError::CipherFailed(e @ CipherError::NTransformFailed { .. })) => e,
+            result => return result,
+        };
+
+        log::warn!("{error}, refreshing player script {player_script_url}");
+        let cipher = match self.refresh_failed_cipher(player_script_url, &cipher).await {
+            Ok(cipher) => cipher,
+            Err(e) => {
+                log::warn!("Failed to refresh player script {player_script_url}: {e}");
+                return Err(error.into());
             }
+        };
+        decipher(&cipher)
+    }
+
+    /// Replace `failed` with a freshly fetched cipher
+    ///
+    /// Goes through the same single-flight lock as the first load, so when
+    /// many tasks see the same cipher fail, the first one refetches the script
+    /// and the others pick up its result.
+    async fn refresh_failed_cipher(
+        &self,
+        player_script_url: &Url,
+        failed: &AdvancedSignatureCipher,
+    ) -> Result<AdvancedSignatureCipher> {
+        let url_string = player_script_url.to_string();
+        let _flight = self.in_flight.enter(&url_string).await;
+        if let Some(cipher) = self
+            .cached_advanced_cipher(&url_string)
+            .await
+            .filter(|cipher| !cipher.same_load(failed))
+        {
+            log::debug!("Using player script refreshed concurrently for {url_string}");
+            return Ok(cipher);
+        }
+
+        self.evict_script(player_script_url).await?;
+        let cipher = self.load_advanced_cipher(player_script_url).await?;
+        log::info!("Refreshed player script cache for {url_string}");
+        Ok(cipher)
+    }
+
+    async fn cached_cipher(&self, url_string: &str) -> Option<SignatureCipher> {
+        let scripts = self.cached_scripts.read().await;
+        let cached = scripts
+            .get(url_string)
+            .filter(|cached| self.is_fresh(cached))?;
+        cached.cipher.clone()
+    }
+
+    /// Body of a player script fetched earlier, e.g. by a load whose cipher
+    /// extraction failed
+    async fn cached_script_content(&self, url_string: &str) -> Option<String> {
+        let scripts = self.cached_scripts.read().await;
+        scripts
+            .get(url_string)
+            .filter(|cached| self.is_fresh(cached) && !cached.script_content.is_empty())
+            .map(|cached| cached.script_content.clone())
+    }
+
+    /// Keep a fetched player script body, so that falling back to the basic
+    /// cipher or reading the signature timestamp needs no second fetch
+    async fn keep_script_content(&self, url_string: &str, script_content: String) {
+        let mut scripts = self.cached_scripts.write().await;
+        match scripts.get_mut(url_string) {
+            Some(cached) => cached.script_content = script_content,
+            None => {
+                scripts.insert(
+                    url_string.to_string(),
+                    CachedPlayerScript {
+                        script_content,
+                        cipher: None,
+                        advanced_cipher: None,
+                        extracted_cipher: None,
+                        cached_at: std::time::SystemTime::now(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// The body of a player script, from the cache or fetched and then cached
+    async fn script_content(&self, player_script_url: &Url) -> Result<String> {
+        let url_string = player_script_url.to_string();
+        if let Some(script_content) = self.cached_script_content(&url_string).await {
+            return Ok(script_content);
         }
 
-        // Fetch and parse new script
         let script_content = self.fetch_player_script(player_script_url).await?;
-        let cipher = self.parse_cipher_from_script(&script_content)?;
+        self.keep_script_content(&url_string, script_content.clone())
+            .await;
+        Ok(script_content)
+    }
+
+    async fn cached_advanced_cipher(&self, url_string: &str) -> Option<AdvancedSignatureCipher> {
+        let scripts = self.cached_scripts.read().await;
+        let cached = scripts
+            .get(url_string)
+            .filter(|cached| self.is_fresh(cached))?;
+        cached.advanced_cipher.clone()
+    }
+
+    pub async fn get_cipher(&self, player_script_url: &Url) -> Result<SignatureCipher> {
+        let url_string = player_script_url.to_string();
+
+        // Check cache first, then again once any load already in flight is done
+        if let Some(cipher) = self.cached_cipher(&url_string).await {
+            return Ok(cipher);
+        }
+        let _flight = self.in_flight.enter(&url_string).await;
+        if let Some(cipher) = self.cached_cipher(&url_string).await {
+            return Ok(cipher);
+        }
+
+        // Parse the script, fetching it unless an earlier load already did
+        let script_content = self.script_content(player_script_url).await?;
+        let cipher =
+            SignatureCipher::new(ScriptParser::extract_signature_operations(&script_content)?);
 
         // Cache the result
-        {
-            let mut scripts = self.cached_scripts.write().unwrap();
-            scripts.insert(
-                url_string,
-                CachedPlayerScript {
-                    script_content,
-                    cipher: cipher.clone(),
-                    advanced_cipher: None,
-                    extracted_cipher: None,
-                    cached_at: std::time::SystemTime::now(),
-                },
-            );
+        if let Some(cached) = self.cached_scripts.write().await.get_mut(&url_string) {
+            cached.cipher = Some(cipher.clone());
         }
 
         Ok(cipher)
@@ -93,32 +462,68 @@ impl SignatureCipherManager {
     ) -> Result<AdvancedSignatureCipher> {
         let url_string = player_script_url.to_string();
 
-        // Check cache first
-        {
-            let scripts = self.cached_scripts.read().unwrap();
-            if let Some(cached) = scripts.get(&url_string) {
-                // Check if cache is still valid (e.g., less than 1 hour old)
-                if cached.cached_at.elapsed().unwrap_or_default().as_secs() < 3600 {
-                    if let Some(ref advanced_cipher) = cached.advanced_cipher {
-                        log::debug!("Using cached advanced cipher for {url_string}");
-                        return Ok(advanced_cipher.clone());
+        // Check cache first, then again once any load already in flight is done
+        if let Some(advanced_cipher) = self.cached_advanced_cipher(&url_string).await {
+            log::debug!("Using cached advanced cipher for {url_string}");
+            return Ok(advanced_cipher);
+        }
+        let _flight = self.in_flight.enter(&url_string).await;
+        if let Some(advanced_cipher) = self.cached_advanced_cipher(&url_string).await {
+            log::debug!("Using advanced cipher loaded concurrently for {url_string}");
+            return Ok(advanced_cipher);
+        }
+
+        self.load_advanced_cipher(player_script_url).await
+    }
+
+    /// Restore or fetch the cipher of a player script and cache it
+    ///
+    /// Callers hold the script's single-flight lock.
+    async fn load_advanced_cipher(
+        &self,
+        player_script_url: &Url,
+    ) -> Result<AdvancedSignatureCipher> {
+        let url_string = player_script_url.to_string();
+
+        // Restore from the cipher cache, or fetch and parse the script
+        let hash = player_hash(player_script_url);
+        let restored = self.cipher_cache.get(&hash).await;
+        let (script_content, extracted_cipher) = match &restored {
+            Some(cached) => {
+                log::debug!("Restored cipher for player {hash} from the cipher cache");
+                (String::new(), cached.to_extracted())
+            }
+            None => {
+                let script_content = match self.cached_script_content(&url_string).await {
+                    Some(script_content) => script_content,
+                    None => self.fetch_player_script(player_script_url).await?,
+                };
+                match ScriptParser::extract_cipher_from_script(&script_content) {
+                    Ok(extracted_cipher) => (script_content, extracted_cipher),
+                    Err(e) => {
+                        self.keep_script_content(&url_string, script_content).await;
+                        return Err(e);
                     }
                 }
             }
-        }
-
-        // Fetch and parse new script
-        let script_content = self.fetch_player_script(player_script_url).await?;
-        let extracted_cipher = ScriptParser::extract_cipher_from_script(&script_content)?;
+        };
         let advanced_cipher =
-            AdvancedSignatureCipher::from_extracted_cipher(extracted_cipher.clone())?;
+            AdvancedSignatureCipher::with_engine(extracted_cipher.clone(), self.js_engine.clone())?;
 
-        // Test the cipher to ensure it's working
+        // Test the cipher to ensure it's working, before its results are memoized
         advanced_cipher.test_cipher()?;
+        let advanced_cipher = advanced_cipher.with_memo(self.memo.clone());
+
+        if restored.is_none() {
+            let entry = CachedCipher::from_extracted(hash, &extracted_cipher);
+            if let Err(e) = self.cipher_cache.put(entry).await {
+                log::warn!("Failed to store cipher for {url_string} in the cipher cache: {e}");
+            }
+        }
 
         // Cache the result
         {
-            let mut scripts = self.cached_scripts.write().unwrap();
+            let mut scripts = self.cached_scripts.write().await;
 
             // Update existing cache entry or create new one
             if let Some(cached) = scripts.get_mut(&url_string) {
@@ -127,7 +532,13 @@ impl SignatureCipherManager {
                 cached.cached_at = std::time::SystemTime::now();
             } else {
                 // Create basic cipher as fallback
-                let basic_cipher = self.parse_cipher_from_script(&script_content)?;
+                let basic_cipher = ScriptParser::parse_signature_operations(
+                    &extracted_cipher.sig_actions,
+                    &extracted_cipher.sig_function,
+                )
+                .map(SignatureCipher::new)
+                .inspect_err(|e| log::debug!("No basic cipher for {url_string}: {e}"))
+                .ok();
                 scripts.insert(
                     url_string.clone(),
                     CachedPlayerScript {
@@ -145,31 +556,165 @@ impl SignatureCipherManager {
         Ok(advanced_cipher)
     }
 
-    async fn fetch_player_script(&self, url: &Url) -> Result<String> {
-        let response = self.http_client.get(url.as_str()).send().await?;
-        let content = response.text().await?;
-        Ok(content)
+    /// The player script YouTube currently serves
+    ///
+    /// Looked up from the iframe API, falling back to the embed page, and
+    /// looked up again once older than the refresh interval. A failed refresh
+    /// keeps using the previous player.
+    ///
+    /// Concurrent callers share a single lookup.
+    pub async fn current_player_script(&self) -> Result<PlayerScriptInfo> {
+        if let Some(info) = self.fresh_current_player().await {
+            return Ok(info);
+        }
+        let _lookup = self.player_lookup.lock().await;
+        if let Some(info) = self.fresh_current_player().await {
+            return Ok(info);
+        }
+
+        let cached = self.current_player.read().await.clone();
+
+        match self.fetch_current_player_script().await {
+            Ok(info) => {
+                if cached
+                    .as_ref()
+                    .is_none_or(|old| old.version != info.version)
+                {
+                    log::info!(
+                        "Current player is {} (sts {})",
+                        info.version,
+                        info.signature_timestamp
+                    );
+                }
+                *self.current_player.write().await = Some(info.clone());
+                Ok(info)
+            }
+            Err(e) => match cached {
+                Some(info) => {
+                    log::warn!(
+                        "Failed to refresh player script, keeping {}: {e}",
+                        info.version
+                    );
+                    Ok(info)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    async fn fresh_current_player(&self) -> Option<PlayerScriptInfo> {
+        let current = self.current_player.read().await;
+        current
+            .as_ref()
+            .filter(|info| {
+                info.fetched_at.elapsed().unwrap_or_default() < self.player_refresh_interval
+            })
+            .cloned()
+    }
+
+    async fn fetch_current_player_script(&self) -> Result<PlayerScriptInfo> {
+        let url = match self.player_script_url_from_iframe_api().await {
+            Ok(url) => url,
+            Err(e) => {
+                log::debug!("No player version in the iframe API, trying the embed page: {e}");
+                self.player_script_url_from_embed_page().await?
+            }
+        };
+
+        let signature_timestamp = self.signature_timestamp(&url).await?;
+        Ok(PlayerScriptInfo {
+            version: player_hash(&url),
+            url,
+            signature_timestamp,
+            fetched_at: SystemTime::now(),
+        })
+    }
+
+    async fn player_script_url_from_iframe_api(&self) -> Result<Url> {
+        let body = self.fetch_page("/iframe_api").await?;
+        let version = parse_iframe_api_version(&body).ok_or_else(|| {
+            YoutubeError::Cipher("Player version not found in the iframe API".to_string())
+        })?;
+        Ok(Url::parse(&format!(
+            "{}/s/player/{version}/player_ias.vflset/en_US/base.js",
+            self.base_url
+        ))?)
+    }
+
+    async fn player_script_url_from_embed_page(&self) -> Result<Url> {
+        let body = self.fetch_page("/embed/").await?;
+        let path = parse_embed_player_path(&body).ok_or_else(|| {
+            YoutubeError::Cipher("Player script URL not found in the embed page".to_string())
+        })?;
+        Ok(Url::parse(&format!("{}{path}", self.base_url))?)
     }
 
-    fn parse_cipher_from_script(&self, _script: &str) -> Result<SignatureCipher> {
-        // For now, implement a basic placeholder cipher that performs common operations
-        // In a full implementation, this would parse the JavaScript to extract the actual operations
-        use super::operations::CipherOperation;
+    async fn fetch_page(&self, path: &str) -> Result<String> {
+        let url = format!("{}{path}", self.base_url);
+        let response = self.fetch(&url).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(YoutubeError::HttpError(format!("{url} returned {status}")));
+        }
+        Ok(response.text().await?)
+    }
+
+    /// Send a GET as the web player would, through the shared HTTP client
+    async fn fetch(&self, url: &str) -> Result<reqwest::Response> {
+        let request = self.http_client.client().get(url).build()?;
+        let context = RequestContext {
+            client_name: Some("WEB".to_string()),
+            client_identifier: Some(PLAYER_SCRIPT_CLIENT.to_string()),
+            ..Default::default()
+        };
+        self.http_client
+            .execute_with_context(request, context)
+            .await
+    }
+
+    /// The `signatureTimestamp` of a player script
+    ///
+    /// Taken from the script's cipher, which is extracted and cached along the
+    /// way, or read from the script alone when its cipher cannot be extracted.
+    /// The script is fetched at most once either way.
+    pub async fn signature_timestamp(&self, player_script_url: &Url) -> Result<u32> {
+        // With a solver configured, no cipher is extracted in process
+        let cipher = match self.solver {
+            Some(_) => Err(YoutubeError::Cipher(
+                "Ciphers are solved remotely".to_string(),
+            )),
+            None => self.get_advanced_cipher(player_script_url).await,
+        };
+        let timestamp = match cipher {
+            Ok(cipher) => cipher.get_timestamp().to_string(),
+            Err(e) => {
+                log::debug!("No cipher for {player_script_url}, reading sts alone: {e}");
+                let script = self.script_content(player_script_url).await?;
+                ScriptParser::extract_timestamp(&script)?
+            }
+        };
 
-        // Create a basic cipher with common operations found in YouTube player scripts
-        // This is a simplified version - real implementation would parse the JavaScript
-        let operations = vec![
-            CipherOperation::Reverse,
-            CipherOperation::Swap(1),
-            CipherOperation::Slice(2),
-        ];
+        timestamp
+            .parse()
+            .map_err(|_| YoutubeError::Cipher(format!("Invalid signature timestamp: {timestamp}")))
+    }
 
-        Ok(SignatureCipher::new(operations))
+    /// Fetch a player script, rejecting anything but a 2xx response so that
+    /// error pages are never parsed or cached as a player
+    async fn fetch_player_script(&self, url: &Url) -> Result<String> {
+        let response = self.fetch(url.as_str()).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(YoutubeError::HttpError(format!(
+                "Player script {url} returned {status}"
+            )));
+        }
+        Ok(response.text().await?)
     }
 
     /// Get cache statistics for monitoring
     pub async fn get_cache_stats(&self) -> CacheStats {
-        let scripts = self.cached_scripts.read().unwrap();
+        let scripts = self.cached_scripts.read().await;
         let total_entries = scripts.len();
         let advanced_cipher_entries = scripts
             .values()
@@ -177,7 +722,7 @@ impl SignatureCipherManager {
             .count();
         let expired_entries = scripts
             .values()
-            .filter(|cached| cached.cached_at.elapsed().unwrap_or_default().as_secs() >= 3600)
+            .filter(|cached| !self.is_fresh(cached))
             .count();
 
         CacheStats {
@@ -185,15 +730,18 @@ impl SignatureCipherManager {
             advanced_cipher_entries,
             basic_cipher_entries: total_entries - advanced_cipher_entries,
             expired_entries,
+            memo_entries: self.memo.len(),
+            memo_hits: self.memo.hits(),
+            memo_misses: self.memo.misses(),
         }
     }
 
     /// Clear expired cache entries
     pub async fn cleanup_cache(&self) {
-        let mut scripts = self.cached_scripts.write().unwrap();
+        let mut scripts = self.cached_scripts.write().await;
         let before_count = scripts.len();
 
-        scripts.retain(|_, cached| cached.cached_at.elapsed().unwrap_or_default().as_secs() < 3600);
+        scripts.retain(|_, cached| self.is_fresh(cached));
 
         let after_count = scripts.len();
         if before_count > after_count {
@@ -207,19 +755,93 @@ impl SignatureCipherManager {
     /// Force refresh a specific player script
     pub async fn refresh_script(&self, player_script_url: &Url) -> Result<()> {
         let url_string = player_script_url.to_string();
+        let _flight = self.in_flight.enter(&url_string).await;
 
-        // Remove from cache
-        {
-            let mut scripts = self.cached_scripts.write().unwrap();
-            scripts.remove(&url_string);
-        }
-
-        // Force re-fetch
-        let _ = self.get_advanced_cipher(player_script_url).await?;
+        self.evict_script(player_script_url).await?;
+        self.load_advanced_cipher(player_script_url).await?;
         log::info!("Refreshed player script cache for {url_string}");
 
         Ok(())
     }
+
+    /// Remove a player script from both caches, and forget what its cipher
+    /// produced
+    async fn evict_script(&self, player_script_url: &Url) -> Result<()> {
+        let removed = self
+            .cached_scripts
+            .write()
+            .await
+            .remove(player_script_url.as_str());
+        if let Some(cipher) = removed.and_then(|cached| cached.advanced_cipher) {
+            self.memo.remove_player(cipher.get_timestamp());
+        }
+        self.cipher_cache
+            .remove(&player_hash(player_script_url))
+            .await
+    }
+}
+
+/// Player version from the iframe API, which embeds it as an escaped
+/// `player\/<version>\/` path
+fn parse_iframe_api_version(body: &str) -> Option<String> {
+    static VERSION_REGEX: OnceLock<Regex> = OnceLock::new();
+    let regex =
+        VERSION_REGEX.get_or_init(|| Regex::new(r"player\\?/([A-Za-z0-9_-]+)\\?/").unwrap());
+    regex.captures(body).map(|caps| caps[1].to_string())
+}
+
+/// Player script path from the `jsUrl` of the embed page
+fn parse_embed_player_path(body: &str) -> Option<String> {
+    static JS_URL_REGEX: OnceLock<Regex> = OnceLock::new();
+    let regex = JS_URL_REGEX
+        .get_or_init(|| Regex::new(r#""jsUrl"\s*:\s*"(/s/player/[^"]+/base\.js)""#).unwrap());
+    regex.captures(body).map(|caps| caps[1].to_string())
+}
+
+/// Per-key locks held while a player script is loaded
+///
+/// The first task to load a player holds its lock; the others wait for it and
+/// then find the result in the cache instead of fetching the script again.
+#[derive(Debug, Clone, Default)]
+struct InFlight {
+    locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl InFlight {
+    async fn enter(&self, key: &str) -> FlightGuard {
+        let lock = self
+            .locks
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+        FlightGuard {
+            guard: Some(lock.lock_owned().await),
+            key: key.to_string(),
+            locks: self.locks.clone(),
+        }
+    }
+}
+
+struct FlightGuard {
+    guard: Option<OwnedMutexGuard<()>>,
+    key: String,
+    locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl Drop for FlightGuard {
+    fn drop(&mut self) {
+        self.guard.take();
+        // Forget the lock unless another task is waiting on it
+        let mut locks = self.locks.lock().unwrap();
+        if locks
+            .get(&self.key)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(&self.key);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -228,4 +850,188 @@ pub struct CacheStats {
     pub advanced_cipher_entries: usize,
     pub basic_cipher_entries: usize,
     pub expired_entries: usize,
+    /// Deciphered signatures and n parameters in the memo
+    pub memo_entries: usize,
+    pub memo_hits: u64,
+    pub memo_misses: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::cache::CipherCacheKey;
+
+    #[test]
+    fn test_manager_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SignatureCipherManager>();
+    }
+
+    #[tokio::test]
+    async fn test_restores_cipher_without_fetching() {
+        let cache = Arc::new(MemoryCipherCache::new());
+        cache
+            .put(CachedCipher {
+                key: CipherCacheKey::new("abc123", "19834"),
+                global_vars: r#"var a = "abc".split("");"#.to_string(),
+                sig_actions: "var b = {};".to_string(),
+                sig_function: "var sig = function(c) { return c.split('').reverse().join(''); }"
+                    .to_string(),
+                n_function: "var n = function(c) { return 'yt_' + c; }".to_string(),
+                cached_at: std::time::SystemTime::now(),
+            })
+            .await
+            .unwrap();
+
+        // Nothing listens on the discard port, so a fetch would fail
+        let manager = SignatureCipherManager::new().with_cipher_cache(cache);
+        let url = Url::parse("http://127.0.0.1:9/s/player/abc123/base.js").unwrap();
+
+        let cipher = manager.get_advanced_cipher(&url).await.unwrap();
+        assert_eq!(cipher.get_timestamp(), "19834");
+        assert_eq!(cipher.decipher_signature("xyz").unwrap(), "zyx");
+        assert_eq!(manager.get_cache_stats().await.advanced_cipher_entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_memoizes_results_shared_by_formats() {
+        let cache = Arc::new(MemoryCipherCache::new());
+        cache
+            .put(CachedCipher {
+                key: CipherCacheKey::new("abc123", "19834"),
+                global_vars: String::new(),
+                sig_actions: String::new(),
+                sig_function: "var sig = function(c) { return c.split('').reverse().join(''); }"
+                    .to_string(),
+                n_function: "var n = function(c) { return 'yt_' + c; }".to_string(),
+                cached_at: std::time::SystemTime::now(),
+            })
+            .await
+            .unwrap();
+        let manager = SignatureCipherManager::new().with_cipher_cache(cache);
+        let url = Url::parse("http://127.0.0.1:9/s/player/abc123/base.js").unwrap();
+
+        let formats: Vec<_> = [140, 251]
+            .into_iter()
+            .map(|itag| StreamFormat {
+                info: None,
+                content_type: "audio/webm".to_string(),
+                itag,
+                bitrate: 128000,
+                content_length: 0,
+                audio_channels: 2,
+                url: Url::parse(&format!("https://example.com/videoplayback?itag={itag}")).unwrap(),
+                n_parameter: Some("abc".to_string()),
+                signature: Some("xyz".to_string()),
+                signature_key: Some("sig".to_string()),
+                is_default_audio_track: true,
+                is_drc: false,
+                language: None,
+            })
+            .collect();
+
+        let urls = manager.resolve_format_urls(&url, &formats).await.unwrap();
+        assert!(urls[1].as_str().ends_with("sig=zyx&n=yt_abc"));
+
+        let stats = manager.get_cache_stats().await;
+        assert_eq!(stats.memo_entries, 2);
+        assert_eq!((stats.memo_hits, stats.memo_misses), (2, 2));
+    }
+
+    /// Answers every request with one n value and no signatures
+    #[derive(Debug)]
+    struct ShortSolver;
+
+    #[async_trait::async_trait]
+    impl CipherSolver for ShortSolver {
+        async fn solve(&self, _request: &SolveRequest) -> Result<crate::cipher::SolvedValues> {
+            Ok(crate::cipher::SolvedValues {
+                signatures: Vec::new(),
+                n: vec!["yt_abc".to_string()],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_short_solver_answer_is_an_error() {
+        let manager = SignatureCipherManager::new().with_solver(Arc::new(ShortSolver));
+        let url = Url::parse("http://127.0.0.1:9/s/player/abc123/base.js").unwrap();
+        let formats: Vec<_> = [("abc", None), ("def", Some("xyz"))]
+            .into_iter()
+            .map(|(n_param, signature)| StreamFormat {
+                info: None,
+                content_type: "audio/webm".to_string(),
+                itag: 251,
+                bitrate: 128000,
+                content_length: 0,
+                audio_channels: 2,
+                url: Url::parse("https://example.com/videoplayback?itag=251").unwrap(),
+                n_parameter: Some(n_param.to_string()),
+                signature: signature.map(str::to_string),
+                signature_key: Some("sig".to_string()),
+                is_default_audio_track: true,
+                is_drc: false,
+                language: None,
+            })
+            .collect();
+
+        let Err(YoutubeError::Cipher(message)) = manager.resolve_format_urls(&url, &formats).await
+        else {
+            panic!("expected the short answer to be rejected");
+        };
+        assert_eq!(
+            message,
+            "Cipher solver answered 0 signatures and 1 n values for 1 and 2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_after_failure_reuses_concurrent_refresh() {
+        let entry = CachedCipher {
+            key: CipherCacheKey::new("abc123", "19834"),
+            global_vars: String::new(),
+            sig_actions: String::new(),
+            sig_function: "var sig = function(c) { return c; }".to_string(),
+            n_function: "var n = function(c) { return 'yt_' + c; }".to_string(),
+            cached_at: std::time::SystemTime::now(),
+        };
+        let cache = Arc::new(MemoryCipherCache::new());
+        cache.put(entry.clone()).await.unwrap();
+
+        // Nothing listens on the discard port, so a refetch would fail
+        let manager = SignatureCipherManager::new().with_cipher_cache(cache.clone());
+        let url = Url::parse("http://127.0.0.1:9/s/player/abc123/base.js").unwrap();
+        let failed = manager.get_advanced_cipher(&url).await.unwrap();
+
+        // Another task refreshed the script after `failed` was handed out
+        manager.evict_script(&url).await.unwrap();
+        cache.put(entry).await.unwrap();
+        let refreshed = manager.get_advanced_cipher(&url).await.unwrap();
+        assert!(!refreshed.same_load(&failed));
+
+        let cipher = manager.refresh_failed_cipher(&url, &failed).await.unwrap();
+        assert!(cipher.same_load(&refreshed));
+
+        // The current cipher failing does go back to the network
+        assert!(manager
+            .refresh_failed_cipher(&url, &refreshed)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_parses_player_version_and_path() {
+        let iframe_api = r#"var scriptUrl = 'https:\/\/www.youtube.com\/s\/player\/6c5cb4f4\/www-widgetapi.vflset\/www-widgetapi.js';"#;
+        assert_eq!(
+            parse_iframe_api_version(iframe_api).as_deref(),
+            Some("6c5cb4f4")
+        );
+
+        let embed = r#"ytcfg.set({"jsUrl":"/s/player/6c5cb4f4/player_ias.vflset/en_US/base.js","cssUrl":"x"});"#;
+        assert_eq!(
+            parse_embed_player_path(embed).as_deref(),
+            Some("/s/player/6c5cb4f4/player_ias.vflset/en_US/base.js")
+        );
+        assert!(parse_iframe_api_version("no player here").is_none());
+    }
 }
@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Memoized results kept by default
+pub const DEFAULT_MEMO_CAPACITY: usize = 4096;
+
+/// Which player function produced a memoized result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoKind {
+    Signature,
+    NParameter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MemoKey {
+    kind: MemoKind,
+    timestamp: String,
+    input: String,
+}
+
+#[derive(Debug, Default)]
+struct MemoEntries {
+    values: HashMap<MemoKey, (String, u64)>,
+    /// Keys by last use, oldest first
+    recency: BTreeMap<u64, MemoKey>,
+    next_tick: u64,
+}
+
+/// Bounded LRU memo of deciphered signatures and transformed n parameters
+///
+/// Results are keyed by the player's signature timestamp and the input, so
+/// the same `n` value shared by every format of a video, or a video played
+/// again, is only run through JavaScript once per player.
+#[derive(Debug)]
+pub struct DecipherMemo {
+    capacity: usize,
+    entries: Mutex<MemoEntries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for DecipherMemo {
+    fn default() -> Self {
+        Self::new(DEFAULT_MEMO_CAPACITY)
+    }
+}
+
+impl DecipherMemo {
+    /// Create a memo holding at most `capacity` results; 0 disables it
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(MemoEntries::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn key(kind: MemoKind, timestamp: &str, input: &str) -> MemoKey {
+        MemoKey {
+            kind,
+            timestamp: timestamp.to_string(),
+            input: input.to_string(),
+        }
+    }
+
+    /// Look up a result, counting a hit or a miss
+    pub fn get(&self, kind: MemoKind, timestamp: &str, input: &str) -> Option<String> {
+        let key = Self::key(kind, timestamp, input);
+        let mut entries = self.entries.lock().unwrap();
+        let tick = entries.next_tick;
+
+        let Some((value, last_used)) = entries.values.get_mut(&key) else {
+            drop(entries);
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        let previous = std::mem::replace(last_used, tick);
+        let value = value.clone();
+        entries.recency.remove(&previous);
+        entries.recency.insert(tick, key);
+        entries.next_tick += 1;
+        drop(entries);
+
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(value)
+    }
+
+    /// Store a result, evicting the least recently used one when full
+    pub fn insert(&self, kind: MemoKind, timestamp: &str, input: &str, value: String) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let key = Self::key(kind, timestamp, input);
+        let mut entries = self.entries.lock().unwrap();
+        let tick = entries.next_tick;
+        entries.next_tick += 1;
+
+        if let Some((_, previous)) = entries.values.insert(key.clone(), (value, tick)) {
+            entries.recency.remove(&previous);
+        }
+        entries.recency.insert(tick, key);
+
+        while entries.values.len() > self.capacity {
+            let Some((_, oldest)) = entries.recency.pop_first() else {
+                break;
+            };
+            entries.values.remove(&oldest);
+        }
+    }
+
+    /// Forget every result of the player with `timestamp`
+    pub fn remove_player(&self, timestamp: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        let MemoEntries {
+            values, recency, ..
+        } = &mut *entries;
+        values.retain(|key, (_, tick)| {
+            let keep = key.timestamp != timestamp;
+            if !keep {
+                recency.remove(tick);
+            }
+            keep
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let memo = DecipherMemo::new(2);
+        memo.insert(MemoKind::NParameter, "19834", "a", "A".to_string());
+        memo.insert(MemoKind::NParameter, "19834", "b", "B".to_string());
+
+        // Using `a` makes `b` the oldest
+        assert_eq!(
+            memo.get(MemoKind::NParameter, "19834", "a").as_deref(),
+            Some("A")
+        );
+        memo.insert(MemoKind::NParameter, "19834", "c", "C".to_string());
+
+        assert_eq!(memo.len(), 2);
+        assert!(memo.get(MemoKind::NParameter, "19834", "b").is_none());
+        assert!(memo.get(MemoKind::NParameter, "19834", "c").is_some());
+        assert_eq!((memo.hits(), memo.misses()), (2, 1));
+    }
+
+    #[test]
+    fn test_keys_include_player_and_kind() {
+        let memo = DecipherMemo::default();
+        memo.insert(MemoKind::Signature, "19834", "a", "A".to_string());
+        memo.insert(MemoKind::Signature, "20073", "a", "B".to_string());
+
+        assert!(memo.get(MemoKind::NParameter, "19834", "a").is_none());
+        assert_eq!(
+            memo.get(MemoKind::Signature, "20073", "a").as_deref(),
+            Some("B")
+        );
+
+        memo.remove_player("19834");
+        assert!(memo.get(MemoKind::Signature, "19834", "a").is_none());
+        assert_eq!(memo.len(), 1);
+    }
+}
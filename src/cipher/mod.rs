@@ -1,11 +1,22 @@
 pub mod advanced_cipher;
+pub mod cache;
 pub mod js_engine;
+mod js_scan;
 pub mod manager;
+pub mod memo;
 pub mod operations;
 pub mod script_parser;
+pub mod solver;
 
-pub use advanced_cipher::AdvancedSignatureCipher;
-pub use js_engine::{JavaScriptEngine, JavaScriptEngineError};
-pub use manager::{CacheStats, CachedPlayerScript, SignatureCipherManager};
+pub use advanced_cipher::{AdvancedSignatureCipher, CipherError};
+pub use cache::{CachedCipher, CipherCache, CipherCacheKey, FileCipherCache, MemoryCipherCache};
+pub use js_engine::{
+    JavaScriptEngine, JavaScriptEngineConfig, JavaScriptEngineError, JsContextGuard,
+};
+pub use manager::{CacheStats, CachedPlayerScript, PlayerScriptInfo, SignatureCipherManager};
+pub use memo::{DecipherMemo, MemoKind, DEFAULT_MEMO_CAPACITY};
 pub use operations::{CipherOperation, SignatureCipher};
-pub use script_parser::{ExtractedCipher, ScriptParser};
+pub use script_parser::{
+    ExtractedCipher, ExtractionStep, NFunctionMatch, NFunctionStrategy, ScriptParser,
+};
+pub use solver::{CipherSolver, RemoteCipherSolver, SolveRequest, SolvedValues};
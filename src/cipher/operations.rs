@@ -6,10 +6,13 @@ pub struct SignatureCipher {
     pub operations: Vec<CipherOperation>,
 }
 
-#[derive(Debug, Clone)]
+/// One step of a player script's signature function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CipherOperation {
     Reverse,
+    /// Swap the first character with the one at `index % len`
     Swap(usize),
+    /// Drop the first `n` characters
     Slice(usize),
 }
 
@@ -36,7 +39,7 @@ impl SignatureCipher {
         Ok(url)
     }
 
-    fn decipher_signature(&self, signature: &str) -> Result<String> {
+    pub fn decipher_signature(&self, signature: &str) -> Result<String> {
         let mut chars: Vec<char> = signature.chars().collect();
 
         for operation in &self.operations {
@@ -45,14 +48,13 @@ impl SignatureCipher {
                     chars.reverse();
                 }
                 CipherOperation::Swap(index) => {
-                    if *index < chars.len() {
-                        chars.swap(0, *index);
+                    if !chars.is_empty() {
+                        let index = index % chars.len();
+                        chars.swap(0, index);
                     }
                 }
-                CipherOperation::Slice(index) => {
-                    if *index < chars.len() {
-                        chars = chars[*index..].to_vec();
-                    }
+                CipherOperation::Slice(count) => {
+                    chars.drain(..(*count).min(chars.len()));
                 }
             }
         }
@@ -1,6 +1,8 @@
-use super::JavaScriptEngineError;
-use crate::Result;
+use super::js_scan::Scanner;
+use super::{CipherOperation, JavaScriptEngineError};
+use crate::{Result, YoutubeError};
 use regex::Regex;
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
 /// Extracted cipher information from YouTube player script
@@ -14,11 +16,97 @@ pub struct ExtractedCipher {
     pub raw_script: String,
 }
 
+/// A step of [`ScriptParser::extract_cipher_from_script`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtractionStep {
+    Timestamp,
+    GlobalVars,
+    SigActions,
+    SigFunction,
+    NFunction,
+}
+
+impl ExtractionStep {
+    pub const ALL: [ExtractionStep; 5] = [
+        ExtractionStep::Timestamp,
+        ExtractionStep::GlobalVars,
+        ExtractionStep::SigActions,
+        ExtractionStep::SigFunction,
+        ExtractionStep::NFunction,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExtractionStep::Timestamp => "timestamp",
+            ExtractionStep::GlobalVars => "global_vars",
+            ExtractionStep::SigActions => "sig_actions",
+            ExtractionStep::SigFunction => "sig_function",
+            ExtractionStep::NFunction => "n_function",
+        }
+    }
+}
+
+impl std::fmt::Display for ExtractionStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How [`ScriptParser::find_n_function`] located the n function
+///
+/// Strategies are tried in declaration order; later ones only run when the
+/// earlier ones fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NFunctionStrategy {
+    /// Matched by the layout regexes
+    Regex,
+    /// Found by tokenizing the script and matching the function's structure
+    Structural,
+    /// Found by either, with lookup tables other than the extracted global
+    /// variables inlined into the function
+    InlinedGlobals,
+}
+
+impl NFunctionStrategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NFunctionStrategy::Regex => "regex",
+            NFunctionStrategy::Structural => "structural",
+            NFunctionStrategy::InlinedGlobals => "inlined_globals",
+        }
+    }
+}
+
+impl std::fmt::Display for NFunctionStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The n function of a player script and how it was found
+#[derive(Debug, Clone)]
+pub struct NFunctionMatch {
+    pub source: String,
+    pub strategy: NFunctionStrategy,
+}
+
+/// Kinds of helpers found in the sig actions object
+#[derive(Debug, Clone, Copy)]
+enum SigAction {
+    Reverse,
+    Slice,
+    Swap,
+}
+
 /// YouTube player script parser for extracting cipher functions
 pub struct ScriptParser;
 
 impl ScriptParser {
     /// Extract cipher information from YouTube player script
+    ///
+    /// The signature and n functions are bound to the globals `sig` and `n`,
+    /// which is what [`AdvancedSignatureCipher`](super::AdvancedSignatureCipher)
+    /// calls.
     pub fn extract_cipher_from_script(script: &str) -> Result<ExtractedCipher> {
         // Extract timestamp
         let timestamp = Self::extract_timestamp(script)?;
@@ -33,18 +121,135 @@ impl ScriptParser {
         let sig_function = Self::extract_sig_function(script)?;
 
         // Extract N parameter function
-        let n_function = Self::extract_n_function(script)?;
+        let n_function = Self::find_n_function(script)?;
+        log::debug!("Found n function with the {} strategy", n_function.strategy);
 
         Ok(ExtractedCipher {
             timestamp,
             global_vars,
             sig_actions,
-            sig_function,
-            n_function: Self::clean_n_function(&n_function),
+            sig_function: format!("var sig = {sig_function}"),
+            n_function: format!("var n = {}", Self::clean_n_function(&n_function.source)),
             raw_script: script.to_string(),
         })
     }
 
+    /// Run every extraction step on its own and return those that fail
+    ///
+    /// Unlike [`extract_cipher_from_script`](Self::extract_cipher_from_script),
+    /// which stops at the first failure, this names every pattern a new player
+    /// layout breaks.
+    pub fn failed_extractions(script: &str) -> Vec<(ExtractionStep, YoutubeError)> {
+        ExtractionStep::ALL
+            .into_iter()
+            .filter_map(|step| {
+                let result = match step {
+                    ExtractionStep::Timestamp => Self::extract_timestamp(script),
+                    ExtractionStep::GlobalVars => Self::extract_global_vars(script),
+                    ExtractionStep::SigActions => Self::extract_sig_actions(script),
+                    ExtractionStep::SigFunction => Self::extract_sig_function(script),
+                    ExtractionStep::NFunction => {
+                        Self::find_n_function(script).map(|found| found.source)
+                    }
+                };
+                result.err().map(|e| (step, e))
+            })
+            .collect()
+    }
+
+    /// Extract the basic cipher's signature operations from a player script
+    pub fn extract_signature_operations(script: &str) -> Result<Vec<CipherOperation>> {
+        let sig_actions = Self::extract_sig_actions(script)?;
+        let sig_function = Self::extract_sig_function(script)?;
+        Self::parse_signature_operations(&sig_actions, &sig_function)
+    }
+
+    /// Translate the calls made by the signature function into operations
+    ///
+    /// Each helper of the sig actions object is classified by its body as a
+    /// reverse, splice/slice or swap, then the function's `Obj.name(a, N)`
+    /// calls are mapped onto those helpers in order.
+    pub fn parse_signature_operations(
+        sig_actions: &str,
+        sig_function: &str,
+    ) -> Result<Vec<CipherOperation>> {
+        static OBJECT_REGEX: OnceLock<Regex> = OnceLock::new();
+        static ACTION_REGEX: OnceLock<Regex> = OnceLock::new();
+
+        let object_name = OBJECT_REGEX
+            .get_or_init(|| Regex::new(r"var\s+([$A-Za-z0-9_]+)\s*=\s*\{").unwrap())
+            .captures(sig_actions)
+            .and_then(|caps| caps.get(1))
+            .ok_or_else(|| YoutubeError::Cipher("Sig actions object has no name".to_string()))?
+            .as_str();
+
+        let action_regex = ACTION_REGEX.get_or_init(|| {
+            Regex::new(
+                r#"([$A-Za-z0-9_]+|"[^"]*")\s*:\s*function\s*\(([$\w]+)(?:\s*,\s*([$\w]+))?\)\s*\{([^{}]*)\}"#,
+            )
+            .unwrap()
+        });
+
+        let mut actions = std::collections::HashMap::new();
+        for caps in action_regex.captures_iter(sig_actions) {
+            let name = caps[1].trim_matches('"').to_string();
+            let array = &caps[2];
+            let argument = caps.get(3).map_or("", |m| m.as_str());
+            let body: String = caps[4].chars().filter(|c| !c.is_whitespace()).collect();
+
+            let kind = if body.contains(&format!("{array}.reverse()")) {
+                SigAction::Reverse
+            } else if body.contains(&format!("{array}.splice(0,{argument})"))
+                || body.contains(&format!("{array}.slice({argument})"))
+            {
+                SigAction::Slice
+            } else if body.contains(&format!("{array}[0]")) && body.contains("var") {
+                SigAction::Swap
+            } else {
+                return Err(YoutubeError::Cipher(format!(
+                    "Unknown signature action {name}: {body}"
+                )));
+            };
+            actions.insert(name, kind);
+        }
+
+        // Calls after the first return belong to unrelated code
+        let body = sig_function
+            .find("return")
+            .map_or(sig_function, |end| &sig_function[..end]);
+        let call_regex = Regex::new(&format!(
+            r#"{}(?:\.([$\w]+)|\["([$\w]+)"\])\(\s*[$\w]+\s*,\s*(\d+)\s*\)"#,
+            regex::escape(object_name)
+        ))
+        .map_err(|e| YoutubeError::Cipher(e.to_string()))?;
+
+        let mut operations = Vec::new();
+        for caps in call_regex.captures_iter(body) {
+            let name = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+            let argument: usize = caps[3]
+                .parse()
+                .map_err(|_| YoutubeError::Cipher(format!("Invalid argument in {}", &caps[0])))?;
+
+            operations.push(match actions.get(name) {
+                Some(SigAction::Reverse) => CipherOperation::Reverse,
+                Some(SigAction::Slice) => CipherOperation::Slice(argument),
+                Some(SigAction::Swap) => CipherOperation::Swap(argument),
+                None => {
+                    return Err(YoutubeError::Cipher(format!(
+                        "Signature function calls unknown action {object_name}.{name}"
+                    )))
+                }
+            });
+        }
+
+        if operations.is_empty() {
+            return Err(YoutubeError::Cipher(
+                "Signature function makes no calls to the sig actions object".to_string(),
+            ));
+        }
+        Ok(operations)
+    }
+
     /// Extract timestamp from script
     pub fn extract_timestamp(script: &str) -> Result<String> {
         static TIMESTAMP_REGEX: OnceLock<Regex> = OnceLock::new();
@@ -63,6 +268,18 @@ impl ScriptParser {
 
     /// Extract global variables from script
     pub fn extract_global_vars(script: &str) -> Result<String> {
+        Self::global_vars_captures(script)
+            .and_then(|caps| caps.name("code"))
+            .map(|m| m.as_str().to_string())
+            .ok_or_else(|| {
+                JavaScriptEngineError::CompilationError(
+                    "Global variables not found in script".to_string(),
+                )
+                .into()
+            })
+    }
+
+    fn global_vars_captures(script: &str) -> Option<regex::Captures<'_>> {
         static GLOBAL_VARS_REGEX: OnceLock<Regex> = OnceLock::new();
         let regex = GLOBAL_VARS_REGEX.get_or_init(|| {
             Regex::new(
@@ -78,16 +295,7 @@ impl ScriptParser {
             .unwrap()
         });
 
-        regex
-            .captures(script)
-            .and_then(|caps| caps.name("code"))
-            .map(|m| m.as_str().to_string())
-            .ok_or_else(|| {
-                JavaScriptEngineError::CompilationError(
-                    "Global variables not found in script".to_string(),
-                )
-                .into()
-            })
+        regex.captures(script)
     }
 
     /// Extract signature actions from script
@@ -143,6 +351,61 @@ impl ScriptParser {
             })
     }
 
+    /// Find the n function, trying each [`NFunctionStrategy`] in turn
+    ///
+    /// The regexes only match the layouts they were written for. When they
+    /// miss, the script is tokenized and the n function is picked out by its
+    /// structure: a single argument, a `try`/`catch`, and the
+    /// `enhanced_except_` marker, written out or behind a lookup table. If the
+    /// function then reads lookup tables other than the one
+    /// [`extract_global_vars`](Self::extract_global_vars) finds, their values
+    /// are inlined so the function runs on its own.
+    pub fn find_n_function(script: &str) -> Result<NFunctionMatch> {
+        let mut tables = None;
+        let (source, strategy) = match Self::extract_n_function(script) {
+            Ok(source) => (source, NFunctionStrategy::Regex),
+            Err(e) => {
+                let scanner = Scanner::new(script);
+                let found = scanner.lookup_tables();
+                let function = scanner.find_n_function(&found).ok_or(e)?;
+                tables = Some(found);
+                (
+                    scanner.slice(function.first, function.last).to_string(),
+                    NFunctionStrategy::Structural,
+                )
+            }
+        };
+
+        let global = Self::global_vars_captures(script)
+            .and_then(|caps| caps.name("varname"))
+            .map(|m| m.as_str());
+        let function = Scanner::new(&source);
+        let last = function.len().saturating_sub(1);
+        let mut foreign: Vec<String> = function
+            .lookups(0, last)
+            .into_iter()
+            .map(|(_, name, _)| name)
+            .filter(|name| Some(*name) != global)
+            .map(String::from)
+            .collect();
+        if foreign.is_empty() {
+            return Ok(NFunctionMatch { source, strategy });
+        }
+
+        let tables: HashMap<_, _> = tables.unwrap_or_else(|| Scanner::new(script).lookup_tables());
+        foreign.retain(|name| tables.contains_key(name));
+        if foreign.is_empty() {
+            return Ok(NFunctionMatch { source, strategy });
+        }
+        foreign.sort();
+        foreign.dedup();
+
+        Ok(NFunctionMatch {
+            source: function.inline_lookups(0, last, &tables, &foreign),
+            strategy: NFunctionStrategy::InlinedGlobals,
+        })
+    }
+
     /// Extract N parameter function from script
     fn extract_n_function(script: &str) -> Result<String> {
         static N_FUNCTION_REGEX: OnceLock<Regex> = OnceLock::new();
@@ -205,8 +468,8 @@ impl ScriptParser {
     /// Extract parameter name from function signature
     fn extract_parameter_name(function: &str) -> String {
         static PARAM_REGEX: OnceLock<Regex> = OnceLock::new();
-        let regex =
-            PARAM_REGEX.get_or_init(|| Regex::new(r"function\s*\(\s*([^)]+)\s*\)").unwrap());
+        let regex = PARAM_REGEX
+            .get_or_init(|| Regex::new(r"function(?:\s+[\w$]+)?\s*\(\s*([^)]+)\s*\)").unwrap());
 
         regex
             .captures(function)
@@ -287,4 +550,45 @@ mod tests {
         assert!(!cleaned.contains("if (typeof"));
         assert!(cleaned.contains("var c"));
     }
+
+    #[test]
+    fn test_failed_extractions_names_each_step() {
+        let script = r#"var cfg={signatureTimestamp:19834};var L="a;b".split(";");"#;
+        let failed: Vec<_> = ScriptParser::failed_extractions(script)
+            .into_iter()
+            .map(|(step, _)| step)
+            .collect();
+
+        assert_eq!(
+            failed,
+            vec![
+                ExtractionStep::SigActions,
+                ExtractionStep::SigFunction,
+                ExtractionStep::NFunction
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_n_function_falls_back_to_structure() {
+        let script = r#"var T="split;enhanced_except_;join".split(";");
+            var f=function(a){var b=a.split(""),c=[function(d){d.reverse()}];
+            try{c[0](b)}catch(e){return T[1]+a}return b[T[2]]("")};"#;
+        let found = ScriptParser::find_n_function(script).unwrap();
+
+        assert_eq!(found.strategy, NFunctionStrategy::Structural);
+        assert!(found.source.starts_with("function(a){var b=a.split(\"\")"));
+        assert!(found.source.ends_with("return b[T[2]](\"\")}"));
+    }
+
+    #[test]
+    fn test_find_n_function_inlines_other_tables() {
+        let script = r#"var Q="x y".split(" ");var k=0,T=["","split","enhanced_except_","join"];
+            var f=function(a){var b=a[T[1]](T[0]),c=[1];try{b.reverse()}catch(d){return T[2]+a}return b[T[3]](T[0])};"#;
+        let found = ScriptParser::find_n_function(script).unwrap();
+
+        assert_eq!(found.strategy, NFunctionStrategy::InlinedGlobals);
+        assert!(!found.source.contains("T["));
+        assert!(found.source.contains(r#"return "enhanced_except_"+a"#));
+    }
 }
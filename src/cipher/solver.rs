@@ -0,0 +1,153 @@
+use super::AdvancedSignatureCipher;
+use crate::plugin::RemoteCipherConfig;
+use crate::{Result, YoutubeError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+/// Signatures and n values of one player to solve
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolveRequest {
+    pub player_url: String,
+    pub signatures: Vec<String>,
+    pub n: Vec<String>,
+}
+
+/// Solved values, in the order they were requested
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolvedValues {
+    pub signatures: Vec<String>,
+    pub n: Vec<String>,
+}
+
+/// Something that can run a player's signature and n functions
+#[async_trait]
+pub trait CipherSolver: Send + Sync + std::fmt::Debug {
+    async fn solve(&self, request: &SolveRequest) -> Result<SolvedValues>;
+}
+
+/// Solves in process with the embedded JavaScript engine
+///
+/// The cipher already belongs to one player, so the request's player URL is
+/// not used.
+#[async_trait]
+impl CipherSolver for AdvancedSignatureCipher {
+    async fn solve(&self, request: &SolveRequest) -> Result<SolvedValues> {
+        self.solve_all(&request.signatures, &request.n)
+    }
+}
+
+/// Posts requests to a cipher solving service, so no JavaScript runs in
+/// this process
+///
+/// The service receives a [`SolveRequest`] as JSON and answers with
+/// [`SolvedValues`].
+#[derive(Debug, Clone)]
+pub struct RemoteCipherSolver {
+    endpoint: Url,
+    token: Option<String>,
+    timeout: Duration,
+    http_client: reqwest::Client,
+}
+
+impl RemoteCipherSolver {
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            endpoint,
+            token: None,
+            timeout: Duration::from_secs(10),
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Create a solver from plugin configuration
+    pub fn from_config(config: &RemoteCipherConfig, http_client: reqwest::Client) -> Result<Self> {
+        let mut solver = Self::new(Url::parse(&config.url)?)
+            .with_http_client(http_client)
+            .with_timeout(Duration::from_millis(config.timeout_ms));
+        if let Some(token) = &config.token {
+            solver = solver.with_token(token.clone());
+        }
+        Ok(solver)
+    }
+
+    /// Send `token` as a bearer token with every request
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+}
+
+#[async_trait]
+impl CipherSolver for RemoteCipherSolver {
+    async fn solve(&self, request: &SolveRequest) -> Result<SolvedValues> {
+        let mut builder = self
+            .http_client
+            .post(self.endpoint.clone())
+            .timeout(self.timeout)
+            .json(request);
+        if let Some(token) = &self.token {
+            builder = builder.bearer_auth(token);
+        }
+
+        let solved: SolvedValues = builder.send().await?.error_for_status()?.json().await?;
+        if solved.signatures.len() != request.signatures.len() || solved.n.len() != request.n.len()
+        {
+            return Err(YoutubeError::Cipher(format!(
+                "Cipher solver answered {} signatures and {} n values for {} and {}",
+                solved.signatures.len(),
+                solved.n.len(),
+                request.signatures.len(),
+                request.n.len()
+            )));
+        }
+        Ok(solved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::ExtractedCipher;
+
+    #[tokio::test]
+    async fn test_advanced_cipher_solves_in_order() {
+        let cipher = AdvancedSignatureCipher::from_extracted_cipher(ExtractedCipher {
+            timestamp: "19834".to_string(),
+            global_vars: String::new(),
+            sig_actions: String::new(),
+            sig_function: "var sig = function(c) { return c.split('').reverse().join(''); }"
+                .to_string(),
+            n_function: "var n = function(c) { return c.toUpperCase(); }".to_string(),
+            raw_script: String::new(),
+        })
+        .unwrap();
+
+        let solved = cipher
+            .solve(&SolveRequest {
+                player_url: "https://www.youtube.com/s/player/abc123/base.js".to_string(),
+                signatures: vec!["abc".to_string(), "xyz".to_string()],
+                n: vec!["nnn".to_string()],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(solved.signatures, vec!["cba", "zyx"]);
+        assert_eq!(solved.n, vec!["NNN"]);
+    }
+}
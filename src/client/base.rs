@@ -1,12 +1,15 @@
+use crate::cipher::PlayerScriptInfo;
 use crate::client::traits::Client;
 use crate::config::ClientOptions;
 use crate::error::{Result, YoutubeError};
 use crate::http::{RequestContext, YoutubeHttpClient};
 use crate::playlist::YoutubePlaylist;
+use crate::search::SearchKind;
 use crate::track::{AudioTrackInfo, TrackFormats, YoutubeAudioTrack};
 use crate::{AudioItem, YoutubeAudioSourceManager};
 use async_trait::async_trait;
 use serde_json::Value;
+use std::sync::Arc;
 
 /// Playability status from YouTube API responses
 #[derive(Debug, Clone, PartialEq)]
@@ -74,12 +77,13 @@ pub trait NonMusicClient: Client {
 ///
 /// This struct provides common functionality that can be shared across
 /// different client implementations (Web, Android, iOS, etc.)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NonMusicClientBase {
     http_client: YoutubeHttpClient,
     client_config: crate::client::config::ClientConfig,
     client_name: String,
     options: ClientOptions,
+    requires_player_script: bool,
 }
 
 impl NonMusicClientBase {
@@ -93,6 +97,7 @@ impl NonMusicClientBase {
             client_config,
             client_name,
             options: ClientOptions::default(),
+            requires_player_script: true,
         }
     }
 
@@ -107,6 +112,24 @@ impl NonMusicClientBase {
             client_config,
             client_name,
             options,
+            requires_player_script: true,
+        }
+    }
+
+    /// Mark the client as one whose player responses carry plain URLs, so
+    /// player requests need no signature timestamp
+    pub fn without_player_script(mut self) -> Self {
+        self.requires_player_script = false;
+        self
+    }
+
+    /// Copy of this client that sends requests through the manager's shared
+    /// HTTP client, so connection pool, rate limit backoff and visitor data
+    /// are shared with every other client
+    pub fn for_source(&self, source: &YoutubeAudioSourceManager) -> Self {
+        Self {
+            http_client: source.http_client.for_client(&self.client_name),
+            ..self.clone()
         }
     }
 
@@ -122,6 +145,16 @@ impl NonMusicClientBase {
     pub async fn load_track_info_from_innertube_impl(
         &self,
         video_id: &str,
+    ) -> Result<AudioTrackInfo> {
+        self.load_track_info_with_player_script(video_id, None)
+            .await
+    }
+
+    /// Load track info, sending the signature timestamp of `player_script`
+    async fn load_track_info_with_player_script(
+        &self,
+        video_id: &str,
+        player_script: Option<&PlayerScriptInfo>,
     ) -> Result<AudioTrackInfo> {
         // Create request context
         let context = RequestContext {
@@ -131,7 +164,7 @@ impl NonMusicClientBase {
         };
 
         // Build request payload
-        let payload = self.build_player_request_payload(video_id)?;
+        let payload = self.build_player_request_payload(video_id, player_script)?;
 
         // Make API request
         let response = self
@@ -143,7 +176,11 @@ impl NonMusicClientBase {
     }
 
     /// Build the request payload for the player API
-    fn build_player_request_payload(&self, video_id: &str) -> Result<Value> {
+    fn build_player_request_payload(
+        &self,
+        video_id: &str,
+        player_script: Option<&PlayerScriptInfo>,
+    ) -> Result<Value> {
         let mut payload = serde_json::json!({
             "context": self.client_config.to_context_json(),
             "videoId": video_id
@@ -154,6 +191,10 @@ impl NonMusicClientBase {
             payload["playbackContext"] = playback_context;
         }
 
+        if let Some(player_script) = player_script {
+            player_script.apply_to_player_payload(&mut payload);
+        }
+
         Ok(payload)
     }
 
@@ -209,12 +250,7 @@ impl NonMusicClientBase {
         response: &Value,
     ) -> Result<AudioTrackInfo> {
         // Check playability status
-        let playability_status = self.extract_playability_status(response)?;
-        if playability_status != PlayabilityStatus::Ok {
-            return Err(YoutubeError::VideoUnavailable(format!(
-                "Video {video_id} is not playable: {playability_status:?}"
-            )));
-        }
+        Self::check_playability(video_id, response)?;
 
         // Extract video details
         let video_details = response.get("videoDetails").ok_or_else(|| {
@@ -268,7 +304,7 @@ impl NonMusicClientBase {
     }
 
     /// Extract playability status from API response
-    fn extract_playability_status(&self, response: &Value) -> Result<PlayabilityStatus> {
+    pub(crate) fn extract_playability_status(response: &Value) -> Result<PlayabilityStatus> {
         let playability_status = response.get("playabilityStatus").ok_or_else(|| {
             YoutubeError::ParseError("Missing playabilityStatus in response".to_string())
         })?;
@@ -283,15 +319,35 @@ impl NonMusicClientBase {
         Ok(PlayabilityStatus::from(status_str))
     }
 
+    /// Fail with [`YoutubeError::Unplayable`] unless the response is playable
+    pub(crate) fn check_playability(video_id: &str, response: &Value) -> Result<()> {
+        let status = Self::extract_playability_status(response)?;
+        if status == PlayabilityStatus::Ok {
+            return Ok(());
+        }
+
+        let reason = response
+            .get("playabilityStatus")
+            .and_then(|p| p.get("reason"))
+            .and_then(|r| r.as_str())
+            .map(str::to_string);
+
+        Err(YoutubeError::Unplayable {
+            video_id: video_id.to_string(),
+            status,
+            reason,
+        })
+    }
+
     /// Parse track formats from player API response
-    async fn parse_track_formats(&self, response: &Value) -> Result<TrackFormats> {
+    pub(crate) async fn parse_track_formats(response: &Value) -> Result<TrackFormats> {
         // Check playability status first
-        let playability_status = self.extract_playability_status(response)?;
-        if playability_status != PlayabilityStatus::Ok {
-            return Err(YoutubeError::VideoUnavailable(format!(
-                "Video is not playable: {playability_status:?}"
-            )));
-        }
+        let video_id = response
+            .get("videoDetails")
+            .and_then(|d| d.get("videoId"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        Self::check_playability(video_id, response)?;
 
         // Extract streaming data
         let streaming_data = response.get("streamingData").ok_or_else(|| {
@@ -304,7 +360,7 @@ impl NonMusicClientBase {
         if let Some(adaptive_formats) = streaming_data.get("adaptiveFormats") {
             if let Some(adaptive_array) = adaptive_formats.as_array() {
                 for format_data in adaptive_array {
-                    if let Ok(format) = self.parse_stream_format(format_data) {
+                    if let Ok(format) = Self::parse_stream_format(format_data) {
                         // Only include audio formats
                         if format.content_type.starts_with("audio/") {
                             formats.push(format);
@@ -318,7 +374,7 @@ impl NonMusicClientBase {
         if let Some(regular_formats) = streaming_data.get("formats") {
             if let Some(regular_array) = regular_formats.as_array() {
                 for format_data in regular_array {
-                    if let Ok(format) = self.parse_stream_format(format_data) {
+                    if let Ok(format) = Self::parse_stream_format(format_data) {
                         formats.push(format);
                     }
                 }
@@ -332,13 +388,13 @@ impl NonMusicClientBase {
         }
 
         // Extract player script URL for signature decryption
-        let player_script_url = self.extract_player_script_url(response)?;
+        let player_script_url = Self::extract_player_script_url(response)?;
 
         Ok(TrackFormats::new(formats, player_script_url))
     }
 
     /// Parse individual stream format from JSON
-    fn parse_stream_format(&self, format_data: &Value) -> Result<crate::track::StreamFormat> {
+    fn parse_stream_format(format_data: &Value) -> Result<crate::track::StreamFormat> {
         let itag = format_data
             .get("itag")
             .and_then(|i| i.as_u64())
@@ -368,15 +424,21 @@ impl NonMusicClientBase {
             .unwrap_or(2);
 
         // Extract URL and handle encrypted signatures
-        let (url, signature, signature_key) = self.extract_format_url_and_signature(format_data)?;
+        let (url, signature, signature_key) = Self::extract_format_url_and_signature(format_data)?;
 
+        // The throttling parameter normally only appears in the URL query
         let n_parameter = format_data
             .get("n")
             .and_then(|n| n.as_str())
-            .map(|n| n.to_string());
+            .map(|n| n.to_string())
+            .or_else(|| {
+                url.query_pairs()
+                    .find(|(key, _)| key == "n")
+                    .map(|(_, n)| n.into_owned())
+            });
 
         // Determine format info from content type
-        let info = self.determine_format_info(&content_type);
+        let info = Self::determine_format_info(&content_type);
 
         // Check if this is the default audio track
         let is_default_audio_track = format_data
@@ -390,6 +452,15 @@ impl NonMusicClientBase {
             .and_then(|drc| drc.as_bool())
             .unwrap_or(false);
 
+        // Track IDs look like "en-US.4"; the part before the dot is the language
+        let language = format_data
+            .get("audioTrack")
+            .and_then(|track| track.get("id"))
+            .and_then(|id| id.as_str())
+            .and_then(|id| id.split('.').next())
+            .filter(|language| !language.is_empty())
+            .map(|language| language.to_string());
+
         Ok(crate::track::StreamFormat {
             info,
             content_type,
@@ -403,12 +474,12 @@ impl NonMusicClientBase {
             signature_key,
             is_default_audio_track,
             is_drc,
+            language,
         })
     }
 
     /// Extract URL and signature information from format data
     fn extract_format_url_and_signature(
-        &self,
         format_data: &Value,
     ) -> Result<(url::Url, Option<String>, Option<String>)> {
         // Check for direct URL first
@@ -434,7 +505,7 @@ impl NonMusicClientBase {
 
         // Check for signatureCipher (encrypted format)
         if let Some(cipher_str) = format_data.get("signatureCipher").and_then(|c| c.as_str()) {
-            return self.parse_signature_cipher(cipher_str);
+            return Self::parse_signature_cipher(cipher_str);
         }
 
         // Fallback: check for individual signature components
@@ -461,7 +532,6 @@ impl NonMusicClientBase {
 
     /// Parse signatureCipher parameter into URL and signature components
     fn parse_signature_cipher(
-        &self,
         cipher_str: &str,
     ) -> Result<(url::Url, Option<String>, Option<String>)> {
         let mut url_str = None;
@@ -497,7 +567,7 @@ impl NonMusicClientBase {
     }
 
     /// Determine format info from content type
-    fn determine_format_info(&self, content_type: &str) -> Option<crate::track::FormatInfo> {
+    fn determine_format_info(content_type: &str) -> Option<crate::track::FormatInfo> {
         use crate::track::FormatInfo;
 
         if content_type.contains("webm") {
@@ -513,7 +583,8 @@ impl NonMusicClientBase {
                 None
             }
         } else if content_type.contains("mp4") {
-            if content_type.contains("aac") {
+            // YouTube labels AAC-LC by its RFC 6381 codec string
+            if content_type.contains("mp4a.40.2") {
                 if content_type.starts_with("video/") {
                     Some(FormatInfo::Mp4VideoAacLc)
                 } else {
@@ -527,8 +598,23 @@ impl NonMusicClientBase {
         }
     }
 
+    /// Use the tracked player script for formats whose response does not
+    /// name one
+    pub(crate) fn with_player_script(
+        mut formats: TrackFormats,
+        response: &Value,
+        player_script: Option<PlayerScriptInfo>,
+    ) -> TrackFormats {
+        if let Some(player_script) = player_script {
+            if response.pointer("/playerConfig/assets/js").is_none() {
+                formats.player_script_url = player_script.url;
+            }
+        }
+        formats
+    }
+
     /// Extract player script URL for signature decryption
-    fn extract_player_script_url(&self, response: &Value) -> Result<url::Url> {
+    fn extract_player_script_url(response: &Value) -> Result<url::Url> {
         // Try to find player script URL in various locations
         if let Some(player_config) = response.get("playerConfig") {
             if let Some(assets) = player_config.get("assets") {
@@ -556,13 +642,22 @@ impl NonMusicClientBase {
 
     /// Load search results from YouTube API
     async fn load_search_results(&self, query: &str) -> Result<Vec<crate::search::SearchResult>> {
+        self.load_search_results_with_params(query, None).await
+    }
+
+    /// Load search results, optionally restricted by an Innertube `params` filter
+    async fn load_search_results_with_params(
+        &self,
+        query: &str,
+        params: Option<&str>,
+    ) -> Result<Vec<crate::search::SearchResult>> {
         let context = RequestContext {
             client_name: Some(self.client_name.clone()),
             is_search_request: true,
             ..Default::default()
         };
 
-        let payload = self.build_search_request_payload(query)?;
+        let payload = self.build_search_request_payload(query, params)?;
         let response = self
             .make_innertube_request("search", &payload, context)
             .await?;
@@ -572,13 +667,87 @@ impl NonMusicClientBase {
     }
 
     /// Build search request payload
-    fn build_search_request_payload(&self, query: &str) -> Result<serde_json::Value> {
+    fn build_search_request_payload(
+        &self,
+        query: &str,
+        params: Option<&str>,
+    ) -> Result<serde_json::Value> {
         let context = self.client_config.to_context_json();
 
-        Ok(serde_json::json!({
+        let mut payload = serde_json::json!({
             "context": context,
             "query": query
-        }))
+        });
+
+        if let Some(params) = params {
+            payload["params"] = Value::String(params.to_string());
+        }
+
+        Ok(payload)
+    }
+
+    /// Convert parsed search results into a search result item
+    fn build_search_result(
+        source: &YoutubeAudioSourceManager,
+        query: &str,
+        results: Vec<crate::search::SearchResult>,
+    ) -> Option<AudioItem> {
+        if results.is_empty() {
+            return None;
+        }
+
+        let mut youtube_search_result = crate::search::YoutubeSearchResult::new(query.to_string());
+
+        for result in results {
+            match result {
+                crate::search::SearchResult::Video {
+                    video_id,
+                    title,
+                    author,
+                    duration,
+                    uri,
+                } => {
+                    // Create AudioTrackInfo from search result
+                    let track_info = AudioTrackInfo {
+                        title,
+                        author,
+                        duration,
+                        video_id,
+                        uri: uri
+                            .parse()
+                            .unwrap_or_else(|_| "https://www.youtube.com/".parse().unwrap()),
+                        is_stream: false,
+                        thumbnail: None,
+                        artwork_url: None,
+                    };
+
+                    // Create YoutubeAudioTrack
+                    let track = YoutubeAudioTrack {
+                        info: track_info,
+                        source_manager: std::sync::Arc::new(source.clone()),
+                    };
+
+                    youtube_search_result.add_track(track);
+                }
+                crate::search::SearchResult::Playlist { title, uri, .. } => {
+                    // Create basic playlist info, tracks are loaded through the URL
+                    let playlist = YoutubePlaylist {
+                        name: title,
+                        selected_track: None,
+                        tracks: Vec::new(),
+                        is_search_result: true,
+                        url: Some(uri),
+                    };
+
+                    youtube_search_result.add_playlist(playlist);
+                }
+                channel @ crate::search::SearchResult::Channel { .. } => {
+                    youtube_search_result.add_channel(channel);
+                }
+            }
+        }
+
+        Some(AudioItem::SearchResult(youtube_search_result))
     }
 
     /// Build the request payload for playlist browse API
@@ -669,6 +838,7 @@ impl NonMusicClientBase {
         &self,
         _playlist_id: &str,
         response: &Value,
+        source_manager: &Arc<YoutubeAudioSourceManager>,
     ) -> Result<YoutubePlaylist> {
         // Extract playlist metadata
         let playlist_name = self.extract_playlist_name(response)?;
@@ -678,7 +848,7 @@ impl NonMusicClientBase {
 
         // Extract tracks from video list
         let mut tracks = Vec::new();
-        self.extract_playlist_tracks(&video_list, &mut tracks)
+        self.extract_playlist_tracks(&video_list, &mut tracks, source_manager)
             .await?;
 
         // Check for continuation token
@@ -696,8 +866,12 @@ impl NonMusicClientBase {
 
             match self.load_playlist_continuation(&token).await {
                 Ok((continuation_video_list, next_token)) => {
-                    self.extract_playlist_tracks(&continuation_video_list, &mut tracks)
-                        .await?;
+                    self.extract_playlist_tracks(
+                        &continuation_video_list,
+                        &mut tracks,
+                        source_manager,
+                    )
+                    .await?;
                     continuation_token = next_token;
                 }
                 Err(e) => {
@@ -843,12 +1017,13 @@ impl NonMusicClientBase {
         &self,
         video_list: &Value,
         tracks: &mut Vec<crate::track::YoutubeAudioTrack>,
+        source_manager: &Arc<YoutubeAudioSourceManager>,
     ) -> Result<()> {
         let contents = video_list.get("contents").unwrap_or(video_list);
 
         if let Some(videos) = contents.as_array() {
             for video in videos {
-                if let Some(track) = self.extract_playlist_track(video).await? {
+                if let Some(track) = self.extract_playlist_track(video, source_manager).await? {
                     tracks.push(track);
                 }
             }
@@ -861,6 +1036,7 @@ impl NonMusicClientBase {
     async fn extract_playlist_track(
         &self,
         video: &Value,
+        source_manager: &Arc<YoutubeAudioSourceManager>,
     ) -> Result<Option<crate::track::YoutubeAudioTrack>> {
         let renderer = video.get("playlistVideoRenderer");
         if renderer.is_none() {
@@ -906,7 +1082,7 @@ impl NonMusicClientBase {
             .and_then(|t| t.as_str())
             .unwrap_or("0:00");
 
-        let duration = self.parse_duration_text(duration_text);
+        let duration = Self::parse_duration_text(duration_text);
 
         // Create track info
         let track_info = AudioTrackInfo {
@@ -925,7 +1101,7 @@ impl NonMusicClientBase {
         // Create track with source manager reference
         let track = crate::track::YoutubeAudioTrack {
             info: track_info,
-            source_manager: std::sync::Arc::new(crate::YoutubeAudioSourceManager::new()),
+            source_manager: source_manager.clone(),
         };
 
         Ok(Some(track))
@@ -1003,7 +1179,7 @@ impl NonMusicClientBase {
     }
 
     /// Parse duration text (e.g., "3:45" or "1:23:45") to Duration
-    fn parse_duration_text(&self, duration_text: &str) -> std::time::Duration {
+    pub(crate) fn parse_duration_text(duration_text: &str) -> std::time::Duration {
         let parts: Vec<&str> = duration_text.split(':').collect();
         let mut total_seconds = 0u64;
 
@@ -1041,7 +1217,12 @@ impl NonMusicClientBase {
     }
 
     /// Parse mix response from next API
-    async fn parse_mix_response(&self, _mix_id: &str, response: &Value) -> Result<YoutubePlaylist> {
+    async fn parse_mix_response(
+        &self,
+        _mix_id: &str,
+        response: &Value,
+        source_manager: &Arc<YoutubeAudioSourceManager>,
+    ) -> Result<YoutubePlaylist> {
         // Extract mix playlist data
         let playlist_data = self.extract_mix_playlist_data(response)?;
 
@@ -1056,7 +1237,7 @@ impl NonMusicClientBase {
         let mut tracks = Vec::new();
         if let Some(contents) = playlist_data.get("contents").and_then(|c| c.as_array()) {
             for item in contents {
-                if let Some(track) = self.extract_mix_track(item).await? {
+                if let Some(track) = self.extract_mix_track(item, source_manager).await? {
                     tracks.push(track);
                 }
             }
@@ -1105,6 +1286,7 @@ impl NonMusicClientBase {
     async fn extract_mix_track(
         &self,
         item: &Value,
+        source_manager: &Arc<YoutubeAudioSourceManager>,
     ) -> Result<Option<crate::track::YoutubeAudioTrack>> {
         let renderer = item.get("playlistPanelVideoRenderer");
         if renderer.is_none() {
@@ -1158,7 +1340,7 @@ impl NonMusicClientBase {
             .and_then(|t| t.as_str())
             .unwrap_or("0:00");
 
-        let duration = self.parse_duration_text(duration_text);
+        let duration = Self::parse_duration_text(duration_text);
 
         // Create track info
         let track_info = AudioTrackInfo {
@@ -1177,7 +1359,7 @@ impl NonMusicClientBase {
         // Create track with source manager reference
         let track = crate::track::YoutubeAudioTrack {
             info: track_info,
-            source_manager: std::sync::Arc::new(crate::YoutubeAudioSourceManager::new()),
+            source_manager: source_manager.clone(),
         };
 
         Ok(Some(track))
@@ -1245,7 +1427,7 @@ impl NonMusicClientBase {
             .and_then(|text| text.as_str())
             .unwrap_or("0:00");
 
-        let duration = self.parse_duration_text(duration_text);
+        let duration = Self::parse_duration_text(duration_text);
 
         let uri = format!("https://www.youtube.com/watch?v={video_id}");
 
@@ -1355,6 +1537,28 @@ impl NonMusicClient for NonMusicClientBase {
     }
 
     async fn load_playlist(&self, playlist_id: &str) -> Result<YoutubePlaylist> {
+        // Without a caller-provided manager, tracks share a default one
+        let source_manager = Arc::new(YoutubeAudioSourceManager::new());
+        self.load_playlist_with_source(playlist_id, &source_manager)
+            .await
+    }
+
+    fn get_http_client(&self) -> &YoutubeHttpClient {
+        &self.http_client
+    }
+
+    fn get_client_config(&self) -> &crate::client::config::ClientConfig {
+        &self.client_config
+    }
+}
+
+impl NonMusicClientBase {
+    /// Load a playlist whose tracks keep a reference to `source_manager`
+    async fn load_playlist_with_source(
+        &self,
+        playlist_id: &str,
+        source_manager: &Arc<YoutubeAudioSourceManager>,
+    ) -> Result<YoutubePlaylist> {
         // Load playlist using browse API
         let context = RequestContext {
             client_name: Some(self.client_name.clone()),
@@ -1368,15 +1572,8 @@ impl NonMusicClient for NonMusicClientBase {
             .await?;
 
         // Parse playlist from response
-        self.parse_playlist_response(playlist_id, &response).await
-    }
-
-    fn get_http_client(&self) -> &YoutubeHttpClient {
-        &self.http_client
-    }
-
-    fn get_client_config(&self) -> &crate::client::config::ClientConfig {
-        &self.client_config
+        self.parse_playlist_response(playlist_id, &response, source_manager)
+            .await
     }
 }
 
@@ -1397,6 +1594,10 @@ impl Client for NonMusicClientBase {
         true
     }
 
+    fn requires_player_script(&self) -> bool {
+        self.requires_player_script
+    }
+
     fn get_capabilities(&self) -> crate::client::traits::ClientCapabilities {
         // Base implementation supports all capabilities
         // Specific clients should override this
@@ -1406,21 +1607,25 @@ impl Client for NonMusicClientBase {
             playlists: true,
             mixes: true,
             search: true,
+            music_search: false,
             embedded: false,
         }
     }
 
     async fn load_video(
         &self,
-        _source: &YoutubeAudioSourceManager,
+        source: &YoutubeAudioSourceManager,
         video_id: &str,
     ) -> Result<Option<AudioItem>> {
-        let track_info = self.load_track_info_from_innertube(video_id).await?;
+        let player_script = source.player_script_for(self).await;
+        let track_info = self
+            .load_track_info_with_player_script(video_id, player_script.as_ref())
+            .await?;
 
         // Create basic track
         let track = YoutubeAudioTrack {
             info: track_info,
-            source_manager: std::sync::Arc::new(_source.clone()),
+            source_manager: std::sync::Arc::new(source.clone()),
         };
 
         Ok(Some(AudioItem::Track(track)))
@@ -1428,90 +1633,56 @@ impl Client for NonMusicClientBase {
 
     async fn load_playlist(
         &self,
-        _source: &YoutubeAudioSourceManager,
+        source: &YoutubeAudioSourceManager,
         playlist_id: &str,
         _selected_video_id: Option<&str>,
     ) -> Result<Option<AudioItem>> {
-        let playlist = NonMusicClient::load_playlist(self, playlist_id).await?;
+        let source_manager = Arc::new(source.clone());
+        let playlist = self
+            .load_playlist_with_source(playlist_id, &source_manager)
+            .await?;
         Ok(Some(AudioItem::Playlist(playlist)))
     }
 
     async fn search(
         &self,
-        _source: &YoutubeAudioSourceManager,
+        source: &YoutubeAudioSourceManager,
         query: &str,
     ) -> Result<Option<AudioItem>> {
-        let results = self.load_search_results(query).await?;
+        let mut results = self.load_search_results(query).await?;
 
-        if results.is_empty() {
-            Ok(None)
-        } else {
-            // Convert SearchResults to YoutubeSearchResult
-            let mut youtube_search_result =
-                crate::search::YoutubeSearchResult::new(query.to_string());
-
-            for result in results {
-                match result {
-                    crate::search::SearchResult::Video {
-                        video_id,
-                        title,
-                        author,
-                        duration,
-                        uri,
-                    } => {
-                        // Create AudioTrackInfo from search result
-                        let track_info = AudioTrackInfo {
-                            title,
-                            author,
-                            duration,
-                            video_id,
-                            uri: uri
-                                .parse()
-                                .unwrap_or_else(|_| "https://www.youtube.com/".parse().unwrap()),
-                            is_stream: false,
-                            thumbnail: None,
-                            artwork_url: None,
-                        };
+        // Channels don't fit into a regular search result
+        results.retain(|result| !matches!(result, crate::search::SearchResult::Channel { .. }));
 
-                        // Create YoutubeAudioTrack
-                        let track = YoutubeAudioTrack {
-                            info: track_info,
-                            source_manager: std::sync::Arc::new(_source.clone()),
-                        };
-
-                        youtube_search_result.add_track(track);
-                    }
-                    crate::search::SearchResult::Playlist {
-                        playlist_id: _,
-                        title,
-                        author: _,
-                        video_count: _,
-                        uri: _,
-                    } => {
-                        // Create basic playlist info
-                        let playlist = YoutubePlaylist {
-                            name: title,
-                            selected_track: None,
-                            tracks: Vec::new(), // Will be loaded later
-                            is_search_result: true,
-                        };
+        Ok(Self::build_search_result(source, query, results))
+    }
 
-                        youtube_search_result.add_playlist(playlist);
-                    }
-                    crate::search::SearchResult::Channel { .. } => {
-                        // Skip channels for now as they don't fit into the current structure
-                        continue;
-                    }
-                }
+    async fn search_with_kind(
+        &self,
+        source: &YoutubeAudioSourceManager,
+        query: &str,
+        kind: SearchKind,
+    ) -> Result<Option<AudioItem>> {
+        match kind {
+            SearchKind::Videos => self.search(source, query).await,
+            SearchKind::Playlists | SearchKind::Channels => {
+                let mut results = self
+                    .load_search_results_with_params(query, kind.search_params())
+                    .await?;
+                results.retain(|result| kind.matches(result));
+
+                Ok(Self::build_search_result(source, query, results))
             }
-
-            Ok(Some(AudioItem::SearchResult(youtube_search_result)))
+            SearchKind::Music => Err(YoutubeError::UnsupportedOperation(format!(
+                "{} does not support music search",
+                self.client_name
+            ))),
         }
     }
 
     async fn get_track_formats(
         &self,
-        _source: &YoutubeAudioSourceManager,
+        source: &YoutubeAudioSourceManager,
         video_id: &str,
     ) -> Result<TrackFormats> {
         // Load player response to get streaming data
@@ -1521,18 +1692,20 @@ impl Client for NonMusicClientBase {
             ..Default::default()
         };
 
-        let payload = self.build_player_request_payload(video_id)?;
+        let player_script = source.player_script_for(self).await;
+        let payload = self.build_player_request_payload(video_id, player_script.as_ref())?;
         let response = self
             .make_innertube_request("player", &payload, context)
             .await?;
 
         // Parse streaming data from response
-        self.parse_track_formats(&response).await
+        let formats = Self::parse_track_formats(&response).await?;
+        Ok(Self::with_player_script(formats, &response, player_script))
     }
 
     async fn load_mix(
         &self,
-        _source: &YoutubeAudioSourceManager,
+        source: &YoutubeAudioSourceManager,
         mix_id: &str,
         selected_video_id: Option<&str>,
     ) -> Result<Option<AudioItem>> {
@@ -1549,7 +1722,11 @@ impl Client for NonMusicClientBase {
             .await?;
 
         // Parse mix from response
-        match self.parse_mix_response(mix_id, &response).await {
+        let source_manager = Arc::new(source.clone());
+        match self
+            .parse_mix_response(mix_id, &response, &source_manager)
+            .await
+        {
             Ok(playlist) => Ok(Some(AudioItem::Playlist(playlist))),
             Err(e) => {
                 eprintln!("Failed to parse mix response: {e}");
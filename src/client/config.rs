@@ -252,8 +252,7 @@ impl ClientConfig {
             "contentPlaybackContext": {
                 "html5Preference": "HTML5_PREF_WANTS",
                 "lactThreshold": -1,
-                "referer": "https://www.youtube.com/",
-                "signatureTimestamp": 19834
+                "referer": "https://www.youtube.com/"
             }
         }))
     }
@@ -1,7 +1,14 @@
 use crate::client::traits::ClientCapabilities;
-use crate::{AudioItem, Client, ClientOptions, Result, TrackFormats, YoutubeAudioSourceManager};
+use crate::{
+    AudioItem, Client, ClientOptions, Result, TrackFormats, YoutubeAudioSourceManager, YoutubeError,
+};
 use async_trait::async_trait;
 
+/// Client for the embedded web player
+///
+/// Not implemented yet: it claims no capabilities, so the manager never
+/// routes to it, and every load fails with
+/// [`YoutubeError::UnsupportedOperation`].
 #[derive(Debug, Clone)]
 pub struct WebEmbeddedClient {
     options: ClientOptions,
@@ -40,6 +47,13 @@ impl WebEmbeddedClient {
         self.po_token = po_token;
         self.visitor_data = visitor_data;
     }
+
+    fn unsupported(&self, operation: &str) -> YoutubeError {
+        YoutubeError::UnsupportedOperation(format!(
+            "{} does not support {operation} yet",
+            self.get_identifier()
+        ))
+    }
 }
 
 #[async_trait]
@@ -54,18 +68,23 @@ impl Client for WebEmbeddedClient {
 
     fn can_handle_request(&self, _identifier: &str) -> bool {
         // TODO: Implement URL pattern matching for embedded
-        true
+        false
+    }
+
+    fn supports_format_loading(&self) -> bool {
+        false
     }
 
     fn get_capabilities(&self) -> ClientCapabilities {
-        // Web embedded clients support most features including embedded
+        // TODO: Claim embedded loading once it is implemented
         ClientCapabilities {
-            oauth: true,
-            videos: true,
-            playlists: false, // Embedded clients typically don't support playlists
-            mixes: true,
-            search: true,
-            embedded: true,
+            oauth: false,
+            videos: false,
+            playlists: false,
+            mixes: false,
+            search: false,
+            music_search: false,
+            embedded: false,
         }
     }
 
@@ -75,7 +94,7 @@ impl Client for WebEmbeddedClient {
         _video_id: &str,
     ) -> Result<Option<AudioItem>> {
         // TODO: Implement embedded video loading
-        todo!("WebEmbeddedClient::load_video not implemented yet")
+        Err(self.unsupported("video loading"))
     }
 
     async fn load_playlist(
@@ -85,7 +104,7 @@ impl Client for WebEmbeddedClient {
         _selected_video_id: Option<&str>,
     ) -> Result<Option<AudioItem>> {
         // TODO: Implement embedded playlist loading
-        todo!("WebEmbeddedClient::load_playlist not implemented yet")
+        Err(self.unsupported("playlist loading"))
     }
 
     async fn search(
@@ -94,7 +113,7 @@ impl Client for WebEmbeddedClient {
         _query: &str,
     ) -> Result<Option<AudioItem>> {
         // TODO: Implement embedded search
-        todo!("WebEmbeddedClient::search not implemented yet")
+        Err(self.unsupported("search"))
     }
 
     async fn get_track_formats(
@@ -103,7 +122,7 @@ impl Client for WebEmbeddedClient {
         _video_id: &str,
     ) -> Result<TrackFormats> {
         // TODO: Implement embedded format loading
-        todo!("WebEmbeddedClient::get_track_formats not implemented yet")
+        Err(self.unsupported("format loading"))
     }
 
     async fn load_mix(
@@ -113,7 +132,7 @@ impl Client for WebEmbeddedClient {
         _selected_video_id: Option<&str>,
     ) -> Result<Option<AudioItem>> {
         // TODO: Implement embedded mix loading
-        todo!("WebEmbeddedClient::load_mix not implemented yet")
+        Err(self.unsupported("mix loading"))
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
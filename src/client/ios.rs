@@ -1,5 +1,6 @@
 use crate::client::config::ClientConfig;
 use crate::client::traits::ClientCapabilities;
+use crate::search::SearchKind;
 use crate::{
     AudioItem, Client, ClientOptions, Result, TrackFormats, YoutubeAudioSourceManager, YoutubeError,
 };
@@ -57,6 +58,11 @@ impl Client for IosClient {
         &self.options
     }
 
+    fn requires_player_script(&self) -> bool {
+        // Stream URLs in the player response are never ciphered
+        false
+    }
+
     fn can_handle_request(&self, identifier: &str) -> bool {
         // iOS client can handle most requests but has some limitations
         // Cannot load playlists except mixes (like other streaming clients)
@@ -108,6 +114,16 @@ impl Client for IosClient {
         base_client.search(source, query).await
     }
 
+    async fn search_with_kind(
+        &self,
+        source: &YoutubeAudioSourceManager,
+        query: &str,
+        kind: SearchKind,
+    ) -> Result<Option<AudioItem>> {
+        let base_client = self.create_base_client(source);
+        base_client.search_with_kind(source, query, kind).await
+    }
+
     async fn load_mix(
         &self,
         source: &YoutubeAudioSourceManager,
@@ -146,16 +162,14 @@ impl IosClient {
     /// Create a base client for making Innertube API requests
     fn create_base_client(
         &self,
-        _source: &YoutubeAudioSourceManager,
+        source: &YoutubeAudioSourceManager,
     ) -> crate::client::base::NonMusicClientBase {
         let config = self.get_client_config();
-        // Note: We need to extract the actual HTTP client from the source
-        // For now, create a new one - this should be improved in the future
-        let http_client = crate::http::YoutubeHttpClient::new().unwrap();
         crate::client::base::NonMusicClientBase::new(
-            http_client,
+            source.http_client.for_client(self.get_identifier()),
             config,
             self.get_identifier().to_string(),
         )
+        .without_player_script()
     }
 }
@@ -1,10 +1,37 @@
-use crate::client::traits::ClientCapabilities;
-use crate::{AudioItem, Client, ClientOptions, Result, TrackFormats, YoutubeAudioSourceManager};
+use crate::cipher::PlayerScriptInfo;
+use crate::client::base::NonMusicClientBase;
+use crate::client::config::ClientConfig;
+use crate::client::traits::{ClientCapabilities, MUSIC_API_BASE_URL, WATCH_URL};
+use crate::http::RequestContext;
+use crate::playlist::YoutubePlaylist;
+use crate::search::{SearchKind, YoutubeSearchResult};
+use crate::track::{AudioTrackInfo, YoutubeAudioTrack};
+use crate::{
+    AudioItem, Client, ClientOptions, Result, TrackFormats, YoutubeAudioSourceManager, YoutubeError,
+};
 use async_trait::async_trait;
+use serde_json::{json, Value};
 
+/// Search params that restrict YouTube Music results to songs
+const MUSIC_SEARCH_PARAMS: &str = "Eg-KAQwIARAAGAAgACgAMABqChADEAQQCRAFEAo=";
+
+/// Maximum number of playlist continuation pages to follow
+const MAX_PLAYLIST_PAGES: usize = 6;
+
+/// YouTube Music client implementation (WEB_REMIX)
+///
+/// Migrated from MusicClient.java and Music.java. All requests go to the
+/// `music.youtube.com` Innertube API and are flagged as music requests so the
+/// HTTP filter applies the YouTube Music referer.
+/// Features:
+/// - Song search through the music shelf renderers
+/// - Video, playlist and mix loading with album artwork
+/// - Track format loading through the WEB_REMIX player endpoint
 #[derive(Debug, Clone)]
 pub struct MusicClient {
     options: ClientOptions,
+    client_config: ClientConfig,
+    api_base_url: String,
 }
 
 impl Default for MusicClient {
@@ -15,13 +42,473 @@ impl Default for MusicClient {
 
 impl MusicClient {
     pub fn new() -> Self {
+        Self::with_options(ClientOptions::default())
+    }
+
+    pub fn with_options(options: ClientOptions) -> Self {
         Self {
-            options: ClientOptions::default(),
+            options,
+            client_config: ClientConfig::music(),
+            api_base_url: MUSIC_API_BASE_URL.to_string(),
         }
     }
 
-    pub fn with_options(options: ClientOptions) -> Self {
-        Self { options }
+    /// Override the Innertube API base URL (defaults to [`MUSIC_API_BASE_URL`])
+    ///
+    /// Mainly useful for pointing the client at a mock server in tests.
+    pub fn with_api_base_url(mut self, api_base_url: impl Into<String>) -> Self {
+        self.api_base_url = api_base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Get the client configuration used for API requests
+    pub fn get_client_config(&self) -> &ClientConfig {
+        &self.client_config
+    }
+
+    /// Build the full URL for an Innertube endpoint
+    ///
+    /// With the default base URL, `endpoint_url("search")` is [`MUSIC_SEARCH_URL`].
+    ///
+    /// [`MUSIC_SEARCH_URL`]: crate::client::traits::MUSIC_SEARCH_URL
+    pub fn endpoint_url(&self, endpoint: &str) -> String {
+        format!("{}/{endpoint}?prettyPrint=false", self.api_base_url)
+    }
+
+    /// Create a request context flagged as a music request
+    fn request_context(&self) -> RequestContext {
+        RequestContext {
+            client_name: Some(self.client_config.client_name.clone()),
+            is_music_request: true,
+            ..Default::default()
+        }
+    }
+
+    /// Make a request to the YouTube Music Innertube API through the
+    /// manager's shared HTTP client
+    async fn make_music_request(
+        &self,
+        source: &YoutubeAudioSourceManager,
+        endpoint: &str,
+        payload: &Value,
+        context: RequestContext,
+    ) -> Result<Value> {
+        let http_client = source.http_client.for_client(self.get_identifier());
+        let request = http_client
+            .client()
+            .post(self.endpoint_url(endpoint))
+            .header("Content-Type", "application/json")
+            .header("Origin", "https://music.youtube.com")
+            .json(payload)
+            .build()
+            .map_err(|e| YoutubeError::HttpError(format!("Failed to build request: {e}")))?;
+
+        let response = http_client.execute_with_context(request, context).await?;
+
+        if !response.status().is_success() {
+            return Err(YoutubeError::ApiError(format!(
+                "API request failed with status: {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| YoutubeError::HttpError(format!("Failed to parse JSON response: {e}")))
+    }
+
+    /// Load the raw player response for a video
+    async fn load_player_response(
+        &self,
+        source: &YoutubeAudioSourceManager,
+        video_id: &str,
+        player_script: Option<&PlayerScriptInfo>,
+    ) -> Result<Value> {
+        let mut payload = json!({
+            "context": self.client_config.to_context_json(),
+            "videoId": video_id,
+            "playbackContext": {
+                "contentPlaybackContext": {
+                    "html5Preference": "HTML5_PREF_WANTS"
+                }
+            }
+        });
+        if let Some(player_script) = player_script {
+            player_script.apply_to_player_payload(&mut payload);
+        }
+
+        let context = RequestContext {
+            is_player_request: true,
+            ..self.request_context()
+        };
+
+        self.make_music_request(source, "player", &payload, context)
+            .await
+    }
+
+    /// Parse track information from a WEB_REMIX player response
+    fn parse_video_details(video_id: &str, response: &Value) -> Result<AudioTrackInfo> {
+        NonMusicClientBase::check_playability(video_id, response)?;
+
+        let video_details = response.get("videoDetails").ok_or_else(|| {
+            YoutubeError::ParseError("Missing videoDetails in response".to_string())
+        })?;
+
+        let title = video_details
+            .get("title")
+            .and_then(|t| t.as_str())
+            .unwrap_or("Unknown Title")
+            .to_string();
+
+        let author = video_details
+            .get("author")
+            .and_then(|a| a.as_str())
+            .unwrap_or("Unknown Artist")
+            .to_string();
+
+        let is_live = video_details
+            .get("isLive")
+            .and_then(|l| l.as_bool())
+            .unwrap_or(false);
+
+        let length_seconds = video_details
+            .get("lengthSeconds")
+            .and_then(|l| l.as_str())
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        let artwork_url = video_details
+            .get("thumbnail")
+            .and_then(|t| t.get("thumbnails"))
+            .and_then(Self::best_thumbnail);
+
+        Ok(AudioTrackInfo {
+            title,
+            author,
+            duration: if is_live {
+                std::time::Duration::from_secs(0)
+            } else {
+                std::time::Duration::from_secs(length_seconds)
+            },
+            video_id: video_id.to_string(),
+            is_stream: is_live,
+            uri: format!("{WATCH_URL}{video_id}").parse()?,
+            thumbnail: artwork_url.clone(),
+            artwork_url,
+        })
+    }
+
+    /// Parse song results from a music search response
+    ///
+    /// Based on Java MusicClient.extractSearchResults(). Results are read from every
+    /// `musicShelfRenderer` in the first tab of the tabbed search results.
+    fn parse_search_results(response: &Value) -> Vec<AudioTrackInfo> {
+        let sections = response
+            .get("contents")
+            .and_then(|c| c.get("tabbedSearchResultsRenderer"))
+            .and_then(|t| t.get("tabs"))
+            .and_then(|tabs| tabs.get(0))
+            .and_then(|tab| tab.get("tabRenderer"))
+            .and_then(|tr| tr.get("content"))
+            .and_then(|content| content.get("sectionListRenderer"))
+            .and_then(|slr| slr.get("contents"))
+            .and_then(|contents| contents.as_array());
+
+        let Some(sections) = sections else {
+            return Vec::new();
+        };
+
+        sections
+            .iter()
+            .filter_map(|section| section.get("musicShelfRenderer"))
+            .filter_map(|shelf| shelf.get("contents").and_then(|c| c.as_array()))
+            .flatten()
+            .filter_map(|item| item.get("musicResponsiveListItemRenderer"))
+            .filter_map(Self::parse_list_item)
+            .collect()
+    }
+
+    /// Parse a single `musicResponsiveListItemRenderer` into track information
+    ///
+    /// Search results keep the duration in the last run of the second flex column,
+    /// while playlist shelves keep it in the first fixed column. Items whose last
+    /// run links somewhere (artists, albums, playlists) are not tracks and are skipped.
+    fn parse_list_item(renderer: &Value) -> Option<AudioTrackInfo> {
+        let columns = renderer.get("flexColumns")?;
+
+        let title_run = columns
+            .get(0)
+            .and_then(|c| c.get("musicResponsiveListItemFlexColumnRenderer"))
+            .and_then(|c| c.get("text"))
+            .and_then(|t| t.get("runs"))
+            .and_then(|r| r.get(0))?;
+
+        let title = title_run.get("text").and_then(|t| t.as_str())?;
+
+        let video_id = title_run
+            .get("navigationEndpoint")
+            .and_then(|n| n.get("watchEndpoint"))
+            .and_then(|w| w.get("videoId"))
+            .or_else(|| {
+                renderer
+                    .get("playlistItemData")
+                    .and_then(|p| p.get("videoId"))
+            })
+            .and_then(|v| v.as_str())?;
+
+        let detail_runs = columns
+            .get(1)
+            .and_then(|c| c.get("musicResponsiveListItemFlexColumnRenderer"))
+            .and_then(|c| c.get("text"))
+            .and_then(|t| t.get("runs"))
+            .and_then(|r| r.as_array());
+
+        let author = detail_runs
+            .and_then(|runs| runs.first())
+            .and_then(|run| run.get("text"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("Unknown Artist");
+
+        let fixed_duration = renderer
+            .get("fixedColumns")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("musicResponsiveListItemFixedColumnRenderer"))
+            .and_then(|c| c.get("text"))
+            .and_then(|t| {
+                t.get("runs")
+                    .and_then(|r| r.get(0))
+                    .and_then(|r| r.get("text"))
+                    .or_else(|| t.get("simpleText"))
+            })
+            .and_then(|t| t.as_str());
+
+        let duration_text = match fixed_duration {
+            Some(text) => text,
+            None => {
+                let last_run = detail_runs.and_then(|runs| runs.last())?;
+                if last_run.get("navigationEndpoint").is_some() {
+                    return None;
+                }
+                last_run.get("text").and_then(|t| t.as_str())?
+            }
+        };
+
+        let artwork_url = renderer
+            .get("thumbnail")
+            .and_then(|t| t.get("musicThumbnailRenderer"))
+            .and_then(|m| m.get("thumbnail"))
+            .and_then(|t| t.get("thumbnails"))
+            .and_then(Self::best_thumbnail);
+
+        Some(AudioTrackInfo {
+            title: title.to_string(),
+            author: author.to_string(),
+            duration: NonMusicClientBase::parse_duration_text(duration_text),
+            video_id: video_id.to_string(),
+            is_stream: false,
+            uri: format!("{WATCH_URL}{video_id}").parse().ok()?,
+            thumbnail: artwork_url.clone(),
+            artwork_url,
+        })
+    }
+
+    /// Parse a `playlistPanelVideoRenderer` from a mix queue
+    fn parse_panel_video(renderer: &Value) -> Option<AudioTrackInfo> {
+        let video_id = renderer.get("videoId").and_then(|v| v.as_str())?;
+
+        let title = renderer
+            .get("title")
+            .and_then(|t| {
+                t.get("runs")
+                    .and_then(|r| r.get(0))
+                    .and_then(|r| r.get("text"))
+                    .or_else(|| t.get("simpleText"))
+            })
+            .and_then(|t| t.as_str())
+            .unwrap_or("Unknown Title");
+
+        let author = renderer
+            .get("longBylineText")
+            .or_else(|| renderer.get("shortBylineText"))
+            .and_then(|b| b.get("runs"))
+            .and_then(|r| r.get(0))
+            .and_then(|r| r.get("text"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("Unknown Artist");
+
+        let duration_text = renderer
+            .get("lengthText")
+            .and_then(|l| {
+                l.get("runs")
+                    .and_then(|r| r.get(0))
+                    .and_then(|r| r.get("text"))
+                    .or_else(|| l.get("simpleText"))
+            })
+            .and_then(|t| t.as_str())
+            .unwrap_or("0:00");
+
+        let artwork_url = renderer
+            .get("thumbnail")
+            .and_then(|t| t.get("thumbnails"))
+            .and_then(Self::best_thumbnail);
+
+        Some(AudioTrackInfo {
+            title: title.to_string(),
+            author: author.to_string(),
+            duration: NonMusicClientBase::parse_duration_text(duration_text),
+            video_id: video_id.to_string(),
+            is_stream: false,
+            uri: format!("{WATCH_URL}{video_id}").parse().ok()?,
+            thumbnail: artwork_url.clone(),
+            artwork_url,
+        })
+    }
+
+    /// Pick the largest thumbnail URL (YouTube lists them in ascending size)
+    fn best_thumbnail(thumbnails: &Value) -> Option<String> {
+        thumbnails
+            .as_array()
+            .and_then(|thumbnails| thumbnails.last())
+            .and_then(|t| t.get("url"))
+            .and_then(|u| u.as_str())
+            .map(|u| u.to_string())
+    }
+
+    /// Extract the playlist title from a WEB_REMIX browse response
+    fn extract_playlist_name(response: &Value) -> String {
+        let header_title = |header: &str| {
+            response
+                .get("header")
+                .and_then(|h| h.get(header))
+                .and_then(|h| h.get("title"))
+                .and_then(|t| t.get("runs"))
+                .and_then(|r| r.get(0))
+                .and_then(|r| r.get("text"))
+                .and_then(|t| t.as_str())
+        };
+
+        header_title("musicDetailHeaderRenderer")
+            .or_else(|| header_title("musicResponsiveHeaderRenderer"))
+            .or_else(|| {
+                // Newer layouts keep the header inside the two column renderer
+                response
+                    .get("contents")
+                    .and_then(|c| c.get("twoColumnBrowseResultsRenderer"))
+                    .and_then(|t| t.get("tabs"))
+                    .and_then(|tabs| tabs.get(0))
+                    .and_then(|tab| tab.get("tabRenderer"))
+                    .and_then(|tr| tr.get("content"))
+                    .and_then(|content| content.get("sectionListRenderer"))
+                    .and_then(|slr| slr.get("contents"))
+                    .and_then(|contents| contents.get(0))
+                    .and_then(|item| item.get("musicResponsiveHeaderRenderer"))
+                    .and_then(|h| h.get("title"))
+                    .and_then(|t| t.get("runs"))
+                    .and_then(|r| r.get(0))
+                    .and_then(|r| r.get("text"))
+                    .and_then(|t| t.as_str())
+            })
+            .or_else(|| {
+                response
+                    .get("microformat")
+                    .and_then(|m| m.get("microformatDataRenderer"))
+                    .and_then(|m| m.get("title"))
+                    .and_then(|t| t.as_str())
+            })
+            .unwrap_or("YouTube Music Playlist")
+            .to_string()
+    }
+
+    /// Extract the playlist shelf from a WEB_REMIX browse response
+    fn extract_playlist_shelf(response: &Value) -> Option<&Value> {
+        let contents = response.get("contents")?;
+
+        // Single column layout
+        contents
+            .get("singleColumnBrowseResultsRenderer")
+            .and_then(|s| s.get("tabs"))
+            .and_then(|tabs| tabs.get(0))
+            .and_then(|tab| tab.get("tabRenderer"))
+            .and_then(|tr| tr.get("content"))
+            .and_then(|content| content.get("sectionListRenderer"))
+            .or_else(|| {
+                // Two column layout keeps the track list in the secondary contents
+                contents
+                    .get("twoColumnBrowseResultsRenderer")
+                    .and_then(|t| t.get("secondaryContents"))
+                    .and_then(|s| s.get("sectionListRenderer"))
+            })
+            .and_then(|slr| slr.get("contents"))
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("musicPlaylistShelfRenderer"))
+    }
+
+    /// Extract tracks and the next continuation token from a list of shelf items
+    fn extract_shelf_tracks(items: &Value, tracks: &mut Vec<AudioTrackInfo>) -> Option<String> {
+        let items = items.as_array()?;
+        let mut continuation = None;
+
+        for item in items {
+            if let Some(renderer) = item.get("musicResponsiveListItemRenderer") {
+                if let Some(track) = Self::parse_list_item(renderer) {
+                    tracks.push(track);
+                }
+            } else if let Some(token) = item
+                .get("continuationItemRenderer")
+                .and_then(|c| c.get("continuationEndpoint"))
+                .and_then(|c| c.get("continuationCommand"))
+                .and_then(|c| c.get("token"))
+                .and_then(|t| t.as_str())
+            {
+                continuation = Some(token.to_string());
+            }
+        }
+
+        continuation
+    }
+
+    /// Extract the mix queue from a WEB_REMIX next response
+    fn extract_mix_panel(response: &Value) -> Option<&Value> {
+        response
+            .get("contents")
+            .and_then(|c| c.get("singleColumnMusicWatchNextResultsRenderer"))
+            .and_then(|s| s.get("tabbedRenderer"))
+            .and_then(|t| t.get("watchNextTabbedResultsRenderer"))
+            .and_then(|w| w.get("tabs"))
+            .and_then(|tabs| tabs.get(0))
+            .and_then(|tab| tab.get("tabRenderer"))
+            .and_then(|tr| tr.get("content"))
+            .and_then(|content| content.get("musicQueueRenderer"))
+            .and_then(|queue| queue.get("content"))
+            .and_then(|content| content.get("playlistPanelRenderer"))
+    }
+
+    /// Wrap track information into playlist tracks and mark the selected one
+    fn build_playlist(
+        source: &YoutubeAudioSourceManager,
+        name: String,
+        infos: Vec<AudioTrackInfo>,
+        selected_video_id: Option<&str>,
+    ) -> YoutubePlaylist {
+        let source_manager = std::sync::Arc::new(source.clone());
+        let selected_index = selected_video_id
+            .and_then(|selected| infos.iter().position(|info| info.video_id == selected));
+
+        let tracks = infos
+            .into_iter()
+            .map(|info| YoutubeAudioTrack {
+                info,
+                source_manager: source_manager.clone(),
+            })
+            .collect();
+
+        let mut playlist = YoutubePlaylist::with_tracks(name, tracks);
+        if let Some(index) = selected_index {
+            playlist.set_selected_track(index);
+        }
+
+        playlist
     }
 }
 
@@ -35,71 +522,323 @@ impl Client for MusicClient {
         &self.options
     }
 
-    fn can_handle_request(&self, _identifier: &str) -> bool {
-        // TODO: Implement URL pattern matching for music
-        true
+    fn can_handle_request(&self, identifier: &str) -> bool {
+        use crate::utils::{self, UrlTools};
+
+        // Can handle music.youtube.com and YouTube URLs, bare IDs and prefixed searches
+        utils::extract_video_id(identifier).is_some()
+            || utils::extract_playlist_id(identifier).is_some()
+            || UrlTools::is_valid_video_id(identifier)
+            || UrlTools::is_valid_playlist_id(identifier)
+            || identifier.contains("youtube.com")
+            || identifier.contains("youtu.be")
+            || SearchKind::parse(identifier).is_some()
     }
 
     fn get_capabilities(&self) -> ClientCapabilities {
-        // Music clients support all features except embedded
+        // Music clients support all features except embedded and plain
+        // searches, as WEB_REMIX only searches songs
         ClientCapabilities {
             oauth: true,
             videos: true,
             playlists: true,
             mixes: true,
-            search: true,
+            search: false,
+            music_search: true,
             embedded: false,
         }
     }
 
     async fn load_video(
         &self,
-        _source: &YoutubeAudioSourceManager,
-        _video_id: &str,
+        source: &YoutubeAudioSourceManager,
+        video_id: &str,
     ) -> Result<Option<AudioItem>> {
-        // TODO: Implement music video loading
-        todo!("MusicClient::load_video not implemented yet")
+        let player_script = source.player_script_for(self).await;
+        let response = self
+            .load_player_response(source, video_id, player_script.as_ref())
+            .await?;
+        let info = Self::parse_video_details(video_id, &response)?;
+
+        Ok(Some(AudioItem::Track(YoutubeAudioTrack {
+            info,
+            source_manager: std::sync::Arc::new(source.clone()),
+        })))
     }
 
     async fn load_playlist(
         &self,
-        _source: &YoutubeAudioSourceManager,
-        _playlist_id: &str,
-        _selected_video_id: Option<&str>,
+        source: &YoutubeAudioSourceManager,
+        playlist_id: &str,
+        selected_video_id: Option<&str>,
     ) -> Result<Option<AudioItem>> {
-        // TODO: Implement music playlist loading
-        todo!("MusicClient::load_playlist not implemented yet")
+        let browse_id = if playlist_id.starts_with("VL") {
+            playlist_id.to_string()
+        } else {
+            format!("VL{playlist_id}")
+        };
+
+        let payload = json!({
+            "context": self.client_config.to_context_json(),
+            "browseId": browse_id
+        });
+
+        let context = RequestContext {
+            is_browse_request: true,
+            ..self.request_context()
+        };
+
+        let response = self
+            .make_music_request(source, "browse", &payload, context.clone())
+            .await?;
+
+        let name = Self::extract_playlist_name(&response);
+        let shelf = Self::extract_playlist_shelf(&response).ok_or_else(|| {
+            YoutubeError::ParseError("Could not find music playlist shelf".to_string())
+        })?;
+
+        let mut infos = Vec::new();
+        let mut continuation = shelf
+            .get("contents")
+            .and_then(|contents| Self::extract_shelf_tracks(contents, &mut infos));
+
+        let mut page_count = 0;
+        while let Some(token) = continuation.take() {
+            if page_count >= MAX_PLAYLIST_PAGES {
+                break;
+            }
+            page_count += 1;
+
+            let payload = json!({
+                "context": self.client_config.to_context_json(),
+                "continuation": token
+            });
+
+            let response = match self
+                .make_music_request(source, "browse", &payload, context.clone())
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    log::warn!("Failed to load music playlist continuation: {e}");
+                    break;
+                }
+            };
+
+            continuation = response
+                .get("onResponseReceivedActions")
+                .and_then(|actions| actions.get(0))
+                .and_then(|action| action.get("appendContinuationItemsAction"))
+                .and_then(|acia| acia.get("continuationItems"))
+                .and_then(|items| Self::extract_shelf_tracks(items, &mut infos));
+        }
+
+        if infos.is_empty() {
+            return Err(YoutubeError::ParseError(
+                "No tracks found in music playlist".to_string(),
+            ));
+        }
+
+        Ok(Some(AudioItem::Playlist(Self::build_playlist(
+            source,
+            name,
+            infos,
+            selected_video_id,
+        ))))
     }
 
     async fn search(
         &self,
-        _source: &YoutubeAudioSourceManager,
-        _query: &str,
+        source: &YoutubeAudioSourceManager,
+        query: &str,
     ) -> Result<Option<AudioItem>> {
-        // TODO: Implement music search
-        todo!("MusicClient::search not implemented yet")
+        let payload = json!({
+            "context": self.client_config.to_context_json(),
+            "query": query,
+            "params": MUSIC_SEARCH_PARAMS
+        });
+
+        let context = RequestContext {
+            is_search_request: true,
+            ..self.request_context()
+        };
+
+        let response = self
+            .make_music_request(source, "search", &payload, context)
+            .await?;
+        let infos = Self::parse_search_results(&response);
+
+        if infos.is_empty() {
+            return Ok(None);
+        }
+
+        let source_manager = std::sync::Arc::new(source.clone());
+        let tracks = infos
+            .into_iter()
+            .map(|info| YoutubeAudioTrack {
+                info,
+                source_manager: source_manager.clone(),
+            })
+            .collect();
+
+        Ok(Some(AudioItem::SearchResult(
+            YoutubeSearchResult::with_tracks(query.to_string(), tracks),
+        )))
+    }
+
+    async fn search_with_kind(
+        &self,
+        source: &YoutubeAudioSourceManager,
+        query: &str,
+        kind: SearchKind,
+    ) -> Result<Option<AudioItem>> {
+        match kind {
+            // WEB_REMIX only has song search, which would answer plain
+            // searches with songs instead of videos
+            SearchKind::Music => self.search(source, query).await,
+            SearchKind::Videos | SearchKind::Playlists | SearchKind::Channels => {
+                Err(YoutubeError::UnsupportedOperation(format!(
+                    "{} does not support {kind:?} search",
+                    self.get_identifier()
+                )))
+            }
+        }
     }
 
     async fn get_track_formats(
         &self,
-        _source: &YoutubeAudioSourceManager,
-        _video_id: &str,
+        source: &YoutubeAudioSourceManager,
+        video_id: &str,
     ) -> Result<TrackFormats> {
-        // TODO: Implement music format loading
-        todo!("MusicClient::get_track_formats not implemented yet")
+        let player_script = source.player_script_for(self).await;
+        let response = self
+            .load_player_response(source, video_id, player_script.as_ref())
+            .await?;
+        let formats = NonMusicClientBase::parse_track_formats(&response).await?;
+        Ok(NonMusicClientBase::with_player_script(
+            formats,
+            &response,
+            player_script,
+        ))
     }
 
     async fn load_mix(
         &self,
-        _source: &YoutubeAudioSourceManager,
-        _mix_id: &str,
-        _selected_video_id: Option<&str>,
+        source: &YoutubeAudioSourceManager,
+        mix_id: &str,
+        selected_video_id: Option<&str>,
     ) -> Result<Option<AudioItem>> {
-        // TODO: Implement music mix loading
-        todo!("MusicClient::load_mix not implemented yet")
+        let mut payload = json!({
+            "context": self.client_config.to_context_json(),
+            "playlistId": mix_id
+        });
+
+        if let Some(video_id) = selected_video_id {
+            payload["videoId"] = Value::String(video_id.to_string());
+        }
+
+        let context = RequestContext {
+            is_next_request: true,
+            ..self.request_context()
+        };
+
+        let response = self
+            .make_music_request(source, "next", &payload, context)
+            .await?;
+
+        let panel = Self::extract_mix_panel(&response).ok_or_else(|| {
+            YoutubeError::ParseError("Could not find music mix queue".to_string())
+        })?;
+
+        let name = panel
+            .get("title")
+            .and_then(|t| t.as_str())
+            .unwrap_or("YouTube Music Mix")
+            .to_string();
+
+        let infos: Vec<AudioTrackInfo> = panel
+            .get("contents")
+            .and_then(|c| c.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.get("playlistPanelVideoRenderer"))
+                    .filter_map(Self::parse_panel_video)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if infos.is_empty() {
+            return Err(YoutubeError::ParseError(
+                "No tracks found in music mix".to_string(),
+            ));
+        }
+
+        Ok(Some(AudioItem::Playlist(Self::build_playlist(
+            source,
+            name,
+            infos,
+            selected_video_id,
+        ))))
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::traits::MUSIC_SEARCH_URL;
+
+    #[test]
+    fn test_endpoint_url() {
+        let client = MusicClient::new();
+        assert_eq!(client.endpoint_url("search"), MUSIC_SEARCH_URL);
+
+        let client = MusicClient::new().with_api_base_url("http://127.0.0.1:1234/youtubei/v1/");
+        assert_eq!(
+            client.endpoint_url("player"),
+            "http://127.0.0.1:1234/youtubei/v1/player?prettyPrint=false"
+        );
+    }
+
+    #[test]
+    fn test_parse_list_item_skips_non_tracks() {
+        let artist = json!({
+            "flexColumns": [
+                {"musicResponsiveListItemFlexColumnRenderer": {"text": {"runs": [
+                    {"text": "Rick Astley", "navigationEndpoint": {"browseEndpoint": {"browseId": "UC123"}}}
+                ]}}},
+                {"musicResponsiveListItemFlexColumnRenderer": {"text": {"runs": [
+                    {"text": "Artist"}, {"text": " • "},
+                    {"text": "4M subscribers", "navigationEndpoint": {"browseEndpoint": {}}}
+                ]}}}
+            ]
+        });
+
+        assert!(MusicClient::parse_list_item(&artist).is_none());
+    }
+
+    #[test]
+    fn test_can_handle_request() {
+        let client = MusicClient::new();
+        assert!(client.can_handle_request("https://music.youtube.com/watch?v=dQw4w9WgXcQ"));
+        assert!(client.can_handle_request(
+            "https://music.youtube.com/playlist?list=OLAK5uy_kZ1Lr3UmEWtaH8zB6X4dXbCb2bXbCb2bX"
+        ));
+        assert!(client.can_handle_request("https://youtu.be/dQw4w9WgXcQ"));
+        assert!(client.can_handle_request("dQw4w9WgXcQ"));
+        assert!(client.can_handle_request("ytmsearch:never gonna give you up"));
+        assert!(!client.can_handle_request("never gonna give you up"));
+        assert!(!client.can_handle_request("https://soundcloud.com/rick-astley"));
+    }
+
+    #[test]
+    fn test_config_is_web_remix() {
+        let client = MusicClient::new();
+        assert_eq!(client.get_client_config().client_name, "WEB_REMIX");
+        assert!(client.request_context().is_music_request);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{AudioItem, ClientOptions, Result, TrackFormats, YoutubeAudioSourceManager};
+use crate::search::SearchKind;
+use crate::{
+    AudioItem, ClientOptions, Result, TrackFormats, YoutubeAudioSourceManager, YoutubeError,
+};
 use async_trait::async_trait;
 
 /// Comprehensive client capabilities structure
@@ -15,6 +18,8 @@ pub struct ClientCapabilities {
     pub mixes: bool,
     /// Can perform search operations
     pub search: bool,
+    /// Can perform YouTube Music searches (`ytmsearch:`)
+    pub music_search: bool,
     /// Can be used in embedded contexts
     pub embedded: bool,
 }
@@ -82,6 +87,11 @@ pub trait Client: Send + Sync {
         self.get_capabilities().search
     }
 
+    /// Check if client supports YouTube Music searches
+    fn supports_music_search(&self) -> bool {
+        self.get_capabilities().music_search
+    }
+
     async fn load_video(
         &self,
         source: &YoutubeAudioSourceManager,
@@ -101,6 +111,25 @@ pub trait Client: Send + Sync {
         query: &str,
     ) -> Result<Option<AudioItem>>;
 
+    /// Run a search selected by an identifier prefix
+    ///
+    /// Regular video searches go through [`Client::search`]; other kinds are
+    /// unsupported unless the client overrides this method.
+    async fn search_with_kind(
+        &self,
+        source: &YoutubeAudioSourceManager,
+        query: &str,
+        kind: SearchKind,
+    ) -> Result<Option<AudioItem>> {
+        match kind {
+            SearchKind::Videos => self.search(source, query).await,
+            _ => Err(YoutubeError::UnsupportedOperation(format!(
+                "{} does not support {kind:?} search",
+                self.get_identifier()
+            ))),
+        }
+    }
+
     async fn get_track_formats(
         &self,
         source: &YoutubeAudioSourceManager,
@@ -131,6 +160,7 @@ impl ClientCapabilities {
             playlists: true,
             mixes: true,
             search: true,
+            music_search: false,
             embedded: false,
         }
     }
@@ -143,6 +173,7 @@ impl ClientCapabilities {
             playlists: false,
             mixes: true,
             search: true,
+            music_search: false,
             embedded: false,
         }
     }
@@ -155,6 +186,7 @@ impl ClientCapabilities {
             playlists: true,
             mixes: true,
             search: true,
+            music_search: false,
             embedded: false,
         }
     }
@@ -167,6 +199,7 @@ impl ClientCapabilities {
             playlists: false,
             mixes: true,
             search: true,
+            music_search: false,
             embedded: false,
         }
     }
@@ -179,6 +212,7 @@ impl ClientCapabilities {
             playlists: false,
             mixes: false,
             search: true,
+            music_search: false,
             embedded: false,
         }
     }
@@ -191,6 +225,7 @@ impl ClientCapabilities {
             playlists: false,
             mixes: true,
             search: true,
+            music_search: false,
             embedded: true,
         }
     }
@@ -210,18 +245,23 @@ pub fn generate_capabilities_summary() -> String {
 
     let mut summary = String::from("Client Capabilities Summary\n");
     summary.push_str("==============================\n");
-    summary.push_str("| Client | OAuth | Videos | Playlists | Mixes | Search | Embedded |\n");
-    summary.push_str("| ------ | ----- | ------ | --------- | ----- | ------ | -------- |\n");
+    summary.push_str(
+        "| Client | OAuth | Videos | Playlists | Mixes | Search | Music Search | Embedded |\n",
+    );
+    summary.push_str(
+        "| ------ | ----- | ------ | --------- | ----- | ------ | ------------ | -------- |\n",
+    );
 
     for (name, caps) in clients {
         summary.push_str(&format!(
-            "| {:<15} | {} | {} | {} | {} | {} | {} |\n",
+            "| {:<15} | {} | {} | {} | {} | {} | {} | {} |\n",
             name,
             if caps.oauth { "✅" } else { "❌" },
             if caps.videos { "✅" } else { "❌" },
             if caps.playlists { "✅" } else { "❌" },
             if caps.mixes { "✅" } else { "❌" },
             if caps.search { "✅" } else { "❌" },
+            if caps.music_search { "✅" } else { "❌" },
             if caps.embedded { "✅" } else { "❌" },
         ));
     }
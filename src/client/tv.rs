@@ -1,5 +1,7 @@
 use crate::client::config::ClientConfig;
 use crate::client::traits::ClientCapabilities;
+use crate::http::RequestContext;
+use crate::search::SearchKind;
 use crate::{
     AudioItem, Client, ClientOptions, Result, TrackFormats, YoutubeAudioSourceManager, YoutubeError,
 };
@@ -179,6 +181,24 @@ impl Client for TvClient {
         base_client.search(source, query).await
     }
 
+    async fn search_with_kind(
+        &self,
+        source: &YoutubeAudioSourceManager,
+        query: &str,
+        kind: SearchKind,
+    ) -> Result<Option<AudioItem>> {
+        // Check capabilities before proceeding
+        if !kind.is_supported_by(&self.get_capabilities()) {
+            return Err(YoutubeError::UnsupportedOperation(format!(
+                "{} does not support {kind:?} search",
+                self.get_identifier()
+            )));
+        }
+
+        let base_client = self.create_base_client(source);
+        base_client.search_with_kind(source, query, kind).await
+    }
+
     async fn load_mix(
         &self,
         source: &YoutubeAudioSourceManager,
@@ -228,7 +248,7 @@ impl TvClient {
         let config = self.get_client_config();
 
         // Create request payload
-        let payload = json!({
+        let mut payload = json!({
             "context": {
                 "client": {
                     "clientName": config.client_name,
@@ -246,18 +266,29 @@ impl TvClient {
                 }
             }
         });
+        if let Some(player_script) = source.player_script_for(self).await {
+            player_script.apply_to_player_payload(&mut payload);
+        }
 
         // Make API request
-        let response = source
-            .http_client
+        let http_client = source.http_client.for_client(self.get_identifier());
+        let request = http_client
+            .client()
             .post("https://youtubei.googleapis.com/youtubei/v1/player")
             .header("Content-Type", "application/json")
             .header("User-Agent", &config.user_agent)
             .json(&payload)
-            .send()
-            .await
+            .build()
             .map_err(|e| YoutubeError::HttpError(format!("Failed to load video: {e}")))?;
 
+        // No client name, so the filter keeps the embedded user agent above;
+        // backoff and rate limits are kept under the client's identifier
+        let context = RequestContext {
+            is_player_request: true,
+            ..Default::default()
+        };
+        let response = http_client.execute_with_context(request, context).await?;
+
         if !response.status().is_success() {
             return Err(YoutubeError::HttpError(format!(
                 "Video load failed with status: {}",
@@ -303,16 +334,24 @@ impl TvClient {
         }
 
         // Make API request
-        let response = source
-            .http_client
+        let http_client = source.http_client.for_client(self.get_identifier());
+        let request = http_client
+            .client()
             .post("https://youtubei.googleapis.com/youtubei/v1/next")
             .header("Content-Type", "application/json")
             .header("User-Agent", &config.user_agent)
             .json(&payload)
-            .send()
-            .await
+            .build()
             .map_err(|e| YoutubeError::HttpError(format!("Failed to load mix: {e}")))?;
 
+        // No client name, so the filter keeps the embedded user agent above;
+        // backoff and rate limits are kept under the client's identifier
+        let context = RequestContext {
+            is_next_request: true,
+            ..Default::default()
+        };
+        let response = http_client.execute_with_context(request, context).await?;
+
         if !response.status().is_success() {
             return Err(YoutubeError::HttpError(format!(
                 "Mix load failed with status: {}",
@@ -333,14 +372,11 @@ impl TvClient {
     /// Create a base client for making Innertube API requests
     fn create_base_client(
         &self,
-        _source: &YoutubeAudioSourceManager,
+        source: &YoutubeAudioSourceManager,
     ) -> crate::client::base::NonMusicClientBase {
         let config = self.get_client_config();
-        // Note: We need to extract the actual HTTP client from the source
-        // For now, create a new one - this should be improved in the future
-        let http_client = crate::http::YoutubeHttpClient::new().unwrap();
         crate::client::base::NonMusicClientBase::new(
-            http_client,
+            source.http_client.for_client(self.get_identifier()),
             config,
             self.get_identifier().to_string(),
         )
@@ -3,6 +3,7 @@ use crate::client::traits::ClientCapabilities;
 use crate::client::{Client, NonMusicClient, NonMusicClientBase};
 use crate::http::YoutubeHttpClient;
 use crate::playlist::YoutubePlaylist;
+use crate::search::SearchKind;
 use crate::track::{AudioTrackInfo, TrackFormats};
 use async_trait::async_trait;
 
@@ -59,6 +60,23 @@ impl WebClient {
         })
     }
 
+    pub fn with_options(options: ClientOptions) -> Result<Self> {
+        let http_client = YoutubeHttpClient::new()?;
+        let client_config = ClientConfig::web();
+        let base = NonMusicClientBase::with_options(
+            http_client,
+            client_config,
+            "WEB".to_string(),
+            options,
+        );
+
+        Ok(Self {
+            base,
+            po_token: None,
+            visitor_data: None,
+        })
+    }
+
     /// Create Mobile Web client variant
     /// Migrated from MWeb.java
     pub fn mobile() -> Result<Self> {
@@ -160,8 +178,11 @@ impl Client for WebClient {
         // Can handle video IDs, YouTube URLs, and search queries
         utils::extract_video_id(identifier).is_some()
             || utils::extract_playlist_id(identifier).is_some()
+            || utils::UrlTools::is_valid_video_id(identifier)
+            || utils::UrlTools::is_valid_playlist_id(identifier)
             || identifier.contains("youtube.com")
             || identifier.contains("youtu.be")
+            || SearchKind::parse(identifier).is_some()
     }
 
     fn get_capabilities(&self) -> ClientCapabilities {
@@ -172,6 +193,7 @@ impl Client for WebClient {
             playlists: true,
             mixes: true,
             search: true,
+            music_search: false,
             embedded: false,
         }
     }
@@ -181,7 +203,10 @@ impl Client for WebClient {
         source: &YoutubeAudioSourceManager,
         video_id: &str,
     ) -> Result<Option<AudioItem>> {
-        self.base.load_video(source, video_id).await
+        self.base
+            .for_source(source)
+            .load_video(source, video_id)
+            .await
     }
 
     async fn load_playlist(
@@ -190,7 +215,8 @@ impl Client for WebClient {
         playlist_id: &str,
         selected_video_id: Option<&str>,
     ) -> Result<Option<AudioItem>> {
-        Client::load_playlist(&self.base, source, playlist_id, selected_video_id).await
+        let base = self.base.for_source(source);
+        Client::load_playlist(&base, source, playlist_id, selected_video_id).await
     }
 
     async fn search(
@@ -198,7 +224,19 @@ impl Client for WebClient {
         source: &YoutubeAudioSourceManager,
         query: &str,
     ) -> Result<Option<AudioItem>> {
-        self.base.search(source, query).await
+        self.base.for_source(source).search(source, query).await
+    }
+
+    async fn search_with_kind(
+        &self,
+        source: &YoutubeAudioSourceManager,
+        query: &str,
+        kind: SearchKind,
+    ) -> Result<Option<AudioItem>> {
+        self.base
+            .for_source(source)
+            .search_with_kind(source, query, kind)
+            .await
     }
 
     async fn get_track_formats(
@@ -206,7 +244,10 @@ impl Client for WebClient {
         source: &YoutubeAudioSourceManager,
         video_id: &str,
     ) -> Result<TrackFormats> {
-        self.base.get_track_formats(source, video_id).await
+        self.base
+            .for_source(source)
+            .get_track_formats(source, video_id)
+            .await
     }
 
     async fn load_mix(
@@ -215,7 +256,10 @@ impl Client for WebClient {
         mix_id: &str,
         selected_video_id: Option<&str>,
     ) -> Result<Option<AudioItem>> {
-        self.base.load_mix(source, mix_id, selected_video_id).await
+        self.base
+            .for_source(source)
+            .load_mix(source, mix_id, selected_video_id)
+            .await
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
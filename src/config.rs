@@ -1,3 +1,4 @@
+use crate::track::FormatSelector;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -5,6 +6,9 @@ pub struct YoutubeSourceOptions {
     pub allow_search: bool,
     pub allow_direct_video_ids: bool,
     pub allow_direct_playlist_ids: bool,
+    /// Format selection for clients without their own selector
+    #[serde(default)]
+    pub format_selector: FormatSelector,
 }
 
 impl Default for YoutubeSourceOptions {
@@ -13,6 +17,7 @@ impl Default for YoutubeSourceOptions {
             allow_search: true,
             allow_direct_video_ids: true,
             allow_direct_playlist_ids: true,
+            format_selector: FormatSelector::default(),
         }
     }
 }
@@ -36,6 +41,11 @@ impl YoutubeSourceOptions {
         self.allow_direct_playlist_ids = allow_direct_playlist_ids;
         self
     }
+
+    pub fn set_format_selector(mut self, format_selector: FormatSelector) -> Self {
+        self.format_selector = format_selector;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -44,6 +54,13 @@ pub struct ClientOptions {
     pub playlist_loading: bool,
     pub video_loading: bool,
     pub searching: bool,
+    /// Overrides the source manager's format selector for this client
+    #[serde(default)]
+    pub format_selector: Option<FormatSelector>,
+    /// Name of the proxy pool this client's requests go through, overriding
+    /// the default pool
+    #[serde(default)]
+    pub proxy_pool: Option<String>,
 }
 
 impl Default for ClientOptions {
@@ -53,6 +70,8 @@ impl Default for ClientOptions {
             playlist_loading: true,
             video_loading: true,
             searching: true,
+            format_selector: None,
+            proxy_pool: None,
         }
     }
 }
@@ -81,4 +100,14 @@ impl ClientOptions {
         self.searching = searching;
         self
     }
+
+    pub fn set_format_selector(mut self, format_selector: FormatSelector) -> Self {
+        self.format_selector = Some(format_selector);
+        self
+    }
+
+    pub fn set_proxy_pool(mut self, proxy_pool: Option<String>) -> Self {
+        self.proxy_pool = proxy_pool;
+        self
+    }
 }
@@ -0,0 +1,300 @@
+use crate::error::{Result, YoutubeError};
+use std::io::{ErrorKind, SeekFrom};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+/// Largest element body read into memory at once
+///
+/// Blocks, track entries and cue indexes are all far below this; anything
+/// larger means the stream is corrupt or not EBML at all.
+pub const MAX_ELEMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Header of an EBML element as read from a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElementHeader {
+    pub id: u32,
+    /// Body size, `None` when the element uses the "unknown size" marker
+    pub size: Option<u64>,
+    /// Stream offset of the first byte of the element ID
+    pub offset: u64,
+    /// Stream offset of the first byte of the body
+    pub data_offset: u64,
+}
+
+impl ElementHeader {
+    /// Stream offset just past the element, if its size is known
+    pub fn end(&self) -> Option<u64> {
+        self.size.map(|size| self.data_offset + size)
+    }
+}
+
+/// Sequential EBML reader over an async byte stream
+#[derive(Debug)]
+pub struct EbmlReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> EbmlReader<R> {
+    /// Wrap a stream positioned at its first byte
+    pub fn new(inner: R) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read the next element header, or `None` at a clean end of stream
+    pub async fn read_element_header(&mut self) -> Result<Option<ElementHeader>> {
+        let offset = self.position;
+
+        let Some(first) = self.read_first_byte().await? else {
+            return Ok(None);
+        };
+        let id_length = vint_length(first, 4)?;
+        let mut id = u32::from(first);
+        for _ in 1..id_length {
+            id = (id << 8) | u32::from(self.read_u8().await?);
+        }
+
+        let first = self.read_u8().await?;
+        let size_length = vint_length(first, 8)?;
+        let mut size = u64::from(first) & (0xFF >> size_length);
+        let mut all_ones = size == (0xFF >> size_length);
+        for _ in 1..size_length {
+            let byte = self.read_u8().await?;
+            all_ones &= byte == 0xFF;
+            size = (size << 8) | u64::from(byte);
+        }
+
+        Ok(Some(ElementHeader {
+            id,
+            size: (!all_ones).then_some(size),
+            offset,
+            data_offset: self.position,
+        }))
+    }
+
+    /// Read the whole body of an element into memory
+    pub async fn read_body(&mut self, header: &ElementHeader) -> Result<Vec<u8>> {
+        let size = known_size(header)?;
+        if size > MAX_ELEMENT_SIZE {
+            return Err(YoutubeError::ParseError(format!(
+                "EBML element {:#X} is too large: {size} bytes",
+                header.id
+            )));
+        }
+
+        let mut body = vec![0; size as usize];
+        self.inner.read_exact(&mut body).await?;
+        self.position += size;
+        Ok(body)
+    }
+
+    /// Skip over the body of an element
+    pub async fn skip_body(&mut self, header: &ElementHeader) -> Result<()> {
+        let size = known_size(header)?;
+        self.seek_to(header.data_offset + size).await
+    }
+
+    pub async fn seek_to(&mut self, position: u64) -> Result<()> {
+        if position != self.position {
+            self.position = self.inner.seek(SeekFrom::Start(position)).await?;
+        }
+        Ok(())
+    }
+
+    async fn read_first_byte(&mut self) -> Result<Option<u8>> {
+        match self.inner.read_u8().await {
+            Ok(byte) => {
+                self.position += 1;
+                Ok(Some(byte))
+            }
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn read_u8(&mut self) -> Result<u8> {
+        let byte = self.inner.read_u8().await?;
+        self.position += 1;
+        Ok(byte)
+    }
+}
+
+fn known_size(header: &ElementHeader) -> Result<u64> {
+    header.size.ok_or_else(|| {
+        YoutubeError::ParseError(format!("EBML element {:#X} has an unknown size", header.id))
+    })
+}
+
+/// Length in bytes of a variable-size integer from its first byte
+fn vint_length(first: u8, max_length: u32) -> Result<u32> {
+    let length = first.leading_zeros() + 1;
+    if length > max_length {
+        return Err(YoutubeError::ParseError(format!(
+            "Invalid EBML variable-size integer starting with {first:#04X}"
+        )));
+    }
+    Ok(length)
+}
+
+/// Decode an unsigned variable-size integer (marker bit removed) from memory
+///
+/// Returns the value and the number of bytes it occupied.
+pub fn parse_vint(data: &[u8]) -> Option<(u64, usize)> {
+    let first = *data.first()?;
+    let length = vint_length(first, 8).ok()? as usize;
+    let bytes = data.get(..length)?;
+
+    let value = bytes[1..]
+        .iter()
+        .fold(u64::from(first) & (0xFF >> length), |value, byte| {
+            (value << 8) | u64::from(*byte)
+        });
+    Some((value, length))
+}
+
+/// Decode a signed variable-size integer as used by EBML lacing
+pub fn parse_signed_vint(data: &[u8]) -> Option<(i64, usize)> {
+    let (value, length) = parse_vint(data)?;
+    let bias = (1i64 << (7 * length - 1)) - 1;
+    Some((value as i64 - bias, length))
+}
+
+/// Decode a big-endian unsigned integer element body
+pub fn parse_uint(data: &[u8]) -> Result<u64> {
+    if data.len() > 8 {
+        return Err(YoutubeError::ParseError(format!(
+            "EBML unsigned integer is {} bytes long",
+            data.len()
+        )));
+    }
+    Ok(data
+        .iter()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+}
+
+/// Decode a 4 or 8 byte float element body
+pub fn parse_float(data: &[u8]) -> Result<f64> {
+    match data.len() {
+        0 => Ok(0.0),
+        4 => Ok(f64::from(f32::from_be_bytes(data.try_into().unwrap()))),
+        8 => Ok(f64::from_be_bytes(data.try_into().unwrap())),
+        length => Err(YoutubeError::ParseError(format!(
+            "EBML float is {length} bytes long"
+        ))),
+    }
+}
+
+/// Decode a string element body, dropping trailing NUL padding
+pub fn parse_string(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// Iterator over the child elements of an in-memory master element body
+pub struct Children<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Children<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = Result<(u32, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let parsed = (|| {
+            let first = self.data[0];
+            let id_length = vint_length(first, 4).ok()? as usize;
+            let id = self
+                .data
+                .get(..id_length)?
+                .iter()
+                .fold(0u32, |id, byte| (id << 8) | u32::from(*byte));
+
+            let (size, size_length) = parse_vint(&self.data[id_length..])?;
+            let start = id_length + size_length;
+            let end = start.checked_add(usize::try_from(size).ok()?)?;
+            let body = self.data.get(start..end)?;
+            Some((id, body, end))
+        })();
+
+        match parsed {
+            Some((id, body, end)) => {
+                self.data = &self.data[end..];
+                Some(Ok((id, body)))
+            }
+            None => {
+                self.data = &[];
+                Some(Err(YoutubeError::ParseError(
+                    "Truncated EBML child element".to_string(),
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vint() {
+        assert_eq!(parse_vint(&[0x81]), Some((1, 1)));
+        assert_eq!(parse_vint(&[0x40, 0x02]), Some((2, 2)));
+        assert_eq!(parse_vint(&[0x10, 0x00, 0x01, 0x00]), Some((256, 4)));
+        assert_eq!(parse_vint(&[0x40]), None);
+        assert_eq!(parse_vint(&[0x00]), None);
+    }
+
+    #[test]
+    fn test_parse_signed_vint() {
+        assert_eq!(parse_signed_vint(&[0xBF]), Some((0, 1)));
+        assert_eq!(parse_signed_vint(&[0x80]), Some((-63, 1)));
+        assert_eq!(parse_signed_vint(&[0x5F, 0xFF]), Some((0, 2)));
+    }
+
+    #[tokio::test]
+    async fn test_read_element_header() {
+        // Segment with an unknown size, then a one byte Timecode element
+        let data = vec![
+            0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xE7, 0x81,
+            0x2A,
+        ];
+        let mut reader = EbmlReader::new(std::io::Cursor::new(data));
+
+        let segment = reader.read_element_header().await.unwrap().unwrap();
+        assert_eq!(segment.id, 0x18538067);
+        assert_eq!(segment.size, None);
+        assert_eq!(segment.data_offset, 12);
+
+        let timecode = reader.read_element_header().await.unwrap().unwrap();
+        assert_eq!(timecode.id, 0xE7);
+        assert_eq!(timecode.size, Some(1));
+        assert_eq!(reader.read_body(&timecode).await.unwrap(), vec![0x2A]);
+
+        assert!(reader.read_element_header().await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_children_reports_truncation() {
+        let children: Vec<_> = Children::new(&[0xD7, 0x81, 0x01, 0x83, 0x82, 0x02]).collect();
+        assert_eq!(children.len(), 2);
+        assert!(matches!(children[0], Ok((0xD7, [0x01]))));
+        assert!(children[1].is_err());
+    }
+}
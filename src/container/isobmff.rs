@@ -0,0 +1,297 @@
+use crate::error::{Result, YoutubeError};
+use std::io::{ErrorKind, SeekFrom};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+/// Largest box body read into memory at once
+///
+/// `moov`, `moof` and `sidx` boxes are far below this; `mdat` bodies are never
+/// read whole.
+pub const MAX_BOX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Header of an ISO-BMFF box as read from a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxHeader {
+    pub kind: [u8; 4],
+    /// Body size, `None` when the box extends to the end of the stream
+    pub size: Option<u64>,
+    /// Stream offset of the first byte of the box
+    pub offset: u64,
+    /// Stream offset of the first byte of the body
+    pub data_offset: u64,
+}
+
+impl BoxHeader {
+    /// Stream offset just past the box, if its size is known
+    pub fn end(&self) -> Option<u64> {
+        self.size.map(|size| self.data_offset + size)
+    }
+}
+
+/// Sequential ISO-BMFF box reader over an async byte stream
+#[derive(Debug)]
+pub struct BoxReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> BoxReader<R> {
+    /// Wrap a stream positioned at its first byte
+    pub fn new(inner: R) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read the next box header, or `None` at a clean end of stream
+    pub async fn read_box_header(&mut self) -> Result<Option<BoxHeader>> {
+        let offset = self.position;
+
+        let mut size_bytes = [0u8; 4];
+        match self.inner.read_exact(&mut size_bytes).await {
+            Ok(_) => self.position += 4,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut kind = [0u8; 4];
+        self.read_exact(&mut kind).await?;
+
+        let size = match u32::from_be_bytes(size_bytes) {
+            0 => None,
+            1 => {
+                let mut large_size = [0u8; 8];
+                self.read_exact(&mut large_size).await?;
+                Some(u64::from_be_bytes(large_size))
+            }
+            size => Some(u64::from(size)),
+        };
+
+        let data_offset = self.position;
+        let size = match size {
+            Some(size) => Some(size.checked_sub(data_offset - offset).ok_or_else(|| {
+                YoutubeError::ParseError(format!(
+                    "MP4 box {} is smaller than its header",
+                    kind_name(&kind)
+                ))
+            })?),
+            None => None,
+        };
+
+        Ok(Some(BoxHeader {
+            kind,
+            size,
+            offset,
+            data_offset,
+        }))
+    }
+
+    /// Read the whole body of a box into memory
+    pub async fn read_body(&mut self, header: &BoxHeader) -> Result<Vec<u8>> {
+        let size = header.size.ok_or_else(|| {
+            YoutubeError::ParseError(format!(
+                "MP4 box {} has no size to read",
+                kind_name(&header.kind)
+            ))
+        })?;
+        if size > MAX_BOX_SIZE {
+            return Err(YoutubeError::ParseError(format!(
+                "MP4 box {} is too large: {size} bytes",
+                kind_name(&header.kind)
+            )));
+        }
+
+        let mut body = vec![0; size as usize];
+        self.read_exact(&mut body).await?;
+        Ok(body)
+    }
+
+    /// Read `buf.len()` bytes from the current position
+    pub async fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.inner.read_exact(buf).await?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+
+    pub async fn seek_to(&mut self, position: u64) -> Result<()> {
+        if position != self.position {
+            self.position = self.inner.seek(SeekFrom::Start(position)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Printable form of a box type for error messages
+pub fn kind_name(kind: &[u8; 4]) -> String {
+    String::from_utf8_lossy(kind).into_owned()
+}
+
+/// Big-endian cursor over an in-memory box body
+#[derive(Debug, Clone)]
+pub struct ByteCursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ByteCursor<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        if length > self.data.len() {
+            return Err(YoutubeError::ParseError(
+                "Truncated MP4 box body".to_string(),
+            ));
+        }
+        let (taken, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    pub fn skip(&mut self, length: usize) -> Result<()> {
+        self.take(length).map(|_| ())
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Read a 32 bit value for version 0 boxes and a 64 bit one otherwise
+    pub fn versioned(&mut self, version: u8) -> Result<u64> {
+        if version == 0 {
+            self.u32().map(u64::from)
+        } else {
+            self.u64()
+        }
+    }
+
+    /// Read the version and flags of a full box
+    pub fn full_box_header(&mut self) -> Result<(u8, u32)> {
+        let value = self.u32()?;
+        Ok(((value >> 24) as u8, value & 0x00FF_FFFF))
+    }
+}
+
+/// Iterator over the child boxes of an in-memory container box body
+pub struct Boxes<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Boxes<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for Boxes<'a> {
+    type Item = Result<([u8; 4], &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let parsed = (|| {
+            let size = u32::from_be_bytes(self.data.get(..4)?.try_into().ok()?);
+            let kind: [u8; 4] = self.data.get(4..8)?.try_into().ok()?;
+            let (header_length, size) = match size {
+                0 => (8, self.data.len()),
+                1 => (
+                    16,
+                    usize::try_from(u64::from_be_bytes(self.data.get(8..16)?.try_into().ok()?))
+                        .ok()?,
+                ),
+                size => (8, size as usize),
+            };
+            let body = self.data.get(header_length..size)?;
+            Some((kind, body, size))
+        })();
+
+        match parsed {
+            Some((kind, body, size)) => {
+                self.data = &self.data[size..];
+                Some(Ok((kind, body)))
+            }
+            None => {
+                self.data = &[];
+                Some(Err(YoutubeError::ParseError(
+                    "Truncated MP4 child box".to_string(),
+                )))
+            }
+        }
+    }
+}
+
+/// Find the first child box of the given type
+pub fn find_box<'a>(data: &'a [u8], kind: &[u8; 4]) -> Result<Option<&'a [u8]>> {
+    for child in Boxes::new(data) {
+        let (child_kind, body) = child?;
+        if &child_kind == kind {
+            return Ok(Some(body));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_box_header() {
+        // A 64 bit sized box, then one extending to the end of the stream
+        let mut data = vec![0, 0, 0, 1];
+        data.extend_from_slice(b"mdat");
+        data.extend_from_slice(&18u64.to_be_bytes());
+        data.extend_from_slice(&[0xAA, 0xBB]);
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        data.extend_from_slice(b"free");
+        data.push(0xCC);
+        let mut reader = BoxReader::new(std::io::Cursor::new(data));
+
+        let mdat = reader.read_box_header().await.unwrap().unwrap();
+        assert_eq!(&mdat.kind, b"mdat");
+        assert_eq!(mdat.size, Some(2));
+        assert_eq!(mdat.data_offset, 16);
+        assert_eq!(reader.read_body(&mdat).await.unwrap(), vec![0xAA, 0xBB]);
+
+        let free = reader.read_box_header().await.unwrap().unwrap();
+        assert_eq!(&free.kind, b"free");
+        assert_eq!(free.size, None);
+    }
+
+    #[test]
+    fn test_boxes_reports_truncation() {
+        let mut data = vec![0, 0, 0, 9];
+        data.extend_from_slice(b"mfhd");
+        data.push(1);
+        data.extend_from_slice(&[0, 0, 0, 20]);
+        data.extend_from_slice(b"traf");
+
+        let children: Vec<_> = Boxes::new(&data).collect();
+        assert_eq!(children.len(), 2);
+        assert!(matches!(children[0], Ok((kind, [1])) if &kind == b"mfhd"));
+        assert!(children[1].is_err());
+    }
+}
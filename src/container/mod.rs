@@ -0,0 +1,17 @@
+pub mod ebml;
+pub mod isobmff;
+pub mod mp4;
+pub mod webm;
+
+pub use mp4::{Mp4Demuxer, Mp4Track, SegmentReference};
+pub use webm::{CuePoint, WebmDemuxer, WebmTrack};
+
+use std::time::Duration;
+
+/// A single compressed audio frame taken out of a container
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioPacket {
+    /// Presentation time of the frame from the start of the stream
+    pub timestamp: Duration,
+    pub data: Vec<u8>,
+}
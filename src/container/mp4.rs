@@ -0,0 +1,596 @@
+use super::isobmff::{find_box, kind_name, BoxHeader, BoxReader, Boxes, ByteCursor};
+use super::AudioPacket;
+use crate::error::{Result, YoutubeError};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncSeek};
+
+/// Object type indication of MPEG-4 audio in an `esds` descriptor
+const OBJECT_TYPE_MPEG4_AUDIO: u8 = 0x40;
+
+// tfhd flags
+const TFHD_BASE_DATA_OFFSET: u32 = 0x000001;
+const TFHD_SAMPLE_DESCRIPTION_INDEX: u32 = 0x000002;
+const TFHD_DEFAULT_SAMPLE_DURATION: u32 = 0x000008;
+const TFHD_DEFAULT_SAMPLE_SIZE: u32 = 0x000010;
+const TFHD_DEFAULT_SAMPLE_FLAGS: u32 = 0x000020;
+
+// trun flags
+const TRUN_DATA_OFFSET: u32 = 0x000001;
+const TRUN_FIRST_SAMPLE_FLAGS: u32 = 0x000004;
+const TRUN_SAMPLE_DURATION: u32 = 0x000100;
+const TRUN_SAMPLE_SIZE: u32 = 0x000200;
+const TRUN_SAMPLE_FLAGS: u32 = 0x000400;
+const TRUN_SAMPLE_COMPOSITION_OFFSET: u32 = 0x000800;
+
+/// An AAC track from the `moov` box
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mp4Track {
+    pub track_id: u32,
+    /// Media timescale from `mdhd`, in ticks per second
+    pub timescale: u32,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// MPEG-4 AudioSpecificConfig from the `esds` box
+    pub decoder_config: Vec<u8>,
+    default_sample_duration: u32,
+    default_sample_size: u32,
+}
+
+impl Mp4Track {
+    /// MPEG-4 audio object type, 2 for AAC-LC
+    pub fn audio_object_type(&self) -> Option<u8> {
+        self.decoder_config.first().map(|byte| byte >> 3)
+    }
+}
+
+/// An entry of the `sidx` segment index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentReference {
+    pub timestamp: Duration,
+    pub duration: Duration,
+    /// Stream offset of the segment's first `moof`
+    pub offset: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SampleRef {
+    offset: u64,
+    size: u32,
+    decode_time: u64,
+}
+
+/// Fragmented MP4 demuxer producing timestamped AAC access units
+///
+/// Mirrors lavaplayer's MP4 fragmented reader:
+/// - `moov` parsing for AAC tracks and their `esds` decoder configuration
+/// - The `sidx` segment index used for time-based seeking
+/// - `moof`/`mdat` fragments with `tfhd` defaults, `tfdt` and `trun` sample tables
+///
+/// Packets are only produced for the selected track, which defaults to the
+/// first AAC track. Other tracks of muxed streams are skipped without being read.
+#[derive(Debug)]
+pub struct Mp4Demuxer<R> {
+    reader: BoxReader<R>,
+    tracks: Vec<Mp4Track>,
+    segments: Vec<SegmentReference>,
+    track_id: u32,
+    next_decode_time: u64,
+    samples: VecDeque<SampleRef>,
+    /// Body range of the `mdat` currently being read
+    mdat: Option<(u64, Option<u64>)>,
+    skip_until: Option<Duration>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> Mp4Demuxer<R> {
+    /// Parse the headers of a fragmented MP4 stream and position it at the first fragment
+    pub async fn open(reader: R) -> Result<Self> {
+        let mut reader = BoxReader::new(reader);
+        let mut tracks = None;
+        let mut segments = Vec::new();
+        let mut first_fragment_offset = None;
+
+        while let Some(header) = reader.read_box_header().await? {
+            match &header.kind {
+                b"moov" => tracks = Some(parse_moov(&reader.read_body(&header).await?)?),
+                b"sidx" if segments.is_empty() => {
+                    let end = header.end().ok_or_else(|| invalid("unsized sidx box"))?;
+                    segments = parse_sidx(&reader.read_body(&header).await?, end)?;
+                }
+                b"moof" => {
+                    first_fragment_offset = Some(header.offset);
+                    break;
+                }
+                b"mdat" => {
+                    return Err(YoutubeError::UnsupportedOperation(
+                        "Only fragmented MP4 streams are supported".to_string(),
+                    ))
+                }
+                _ => skip(&mut reader, &header).await?,
+            }
+        }
+
+        let tracks = tracks.ok_or_else(|| invalid("missing moov box"))?;
+        let first_fragment_offset =
+            first_fragment_offset.ok_or_else(|| invalid("no fragments found"))?;
+        reader.seek_to(first_fragment_offset).await?;
+
+        let track_id = tracks
+            .first()
+            .map(|track| track.track_id)
+            .ok_or_else(|| invalid("no AAC track found"))?;
+
+        Ok(Self {
+            reader,
+            tracks,
+            segments,
+            track_id,
+            next_decode_time: 0,
+            samples: VecDeque::new(),
+            mdat: None,
+            skip_until: None,
+        })
+    }
+
+    /// All AAC tracks of the stream
+    pub fn tracks(&self) -> &[Mp4Track] {
+        &self.tracks
+    }
+
+    /// The track packets are produced for
+    pub fn track(&self) -> &Mp4Track {
+        self.tracks
+            .iter()
+            .find(|track| track.track_id == self.track_id)
+            .expect("selected track exists")
+    }
+
+    /// Produce packets for a different AAC track from now on
+    pub fn select_track(&mut self, track_id: u32) -> Result<()> {
+        if !self.tracks.iter().any(|track| track.track_id == track_id) {
+            return Err(invalid(&format!("no AAC track with ID {track_id}")));
+        }
+        self.track_id = track_id;
+        self.samples.clear();
+        self.mdat = None;
+        Ok(())
+    }
+
+    pub fn segments(&self) -> &[SegmentReference] {
+        &self.segments
+    }
+
+    /// Total duration covered by the segment index
+    pub fn duration(&self) -> Option<Duration> {
+        self.segments
+            .last()
+            .map(|segment| segment.timestamp + segment.duration)
+    }
+
+    /// Read the next access unit of the selected track, or `None` at the end
+    pub async fn next_packet(&mut self) -> Result<Option<AudioPacket>> {
+        loop {
+            if let Some((mdat_start, mdat_end)) = self.mdat {
+                let in_mdat = self.samples.front().is_some_and(|sample| {
+                    sample.offset >= mdat_start
+                        && mdat_end.is_none_or(|end| sample.offset + u64::from(sample.size) <= end)
+                });
+
+                if !in_mdat {
+                    // Done with this mdat; unread samples may live in a later one
+                    self.mdat = None;
+                    match mdat_end {
+                        Some(end) => self.reader.seek_to(end).await?,
+                        None => return Ok(None),
+                    }
+                    continue;
+                }
+
+                let sample = self.samples.pop_front().expect("sample is queued");
+                let timestamp = ticks_to_duration(sample.decode_time, self.track().timescale);
+                if self.skip_until.is_some_and(|target| timestamp < target) {
+                    continue;
+                }
+                self.skip_until = None;
+
+                self.reader.seek_to(sample.offset).await?;
+                let mut data = vec![0; sample.size as usize];
+                self.reader.read_exact(&mut data).await?;
+                return Ok(Some(AudioPacket { timestamp, data }));
+            }
+
+            let Some(header) = self.reader.read_box_header().await? else {
+                return Ok(None);
+            };
+
+            match &header.kind {
+                b"moof" => {
+                    let body = self.reader.read_body(&header).await?;
+                    self.parse_moof(&body, header.offset)?;
+                }
+                b"mdat" => {
+                    self.samples
+                        .retain(|sample| sample.offset >= header.data_offset);
+                    self.mdat = Some((header.data_offset, header.end()));
+                }
+                _ => skip(&mut self.reader, &header).await?,
+            }
+        }
+    }
+
+    /// Seek so that the next packet is the first one at or after `position`
+    ///
+    /// Jumps to the `sidx` segment containing `position` and returns the
+    /// segment's start time.
+    pub async fn seek(&mut self, position: Duration) -> Result<Duration> {
+        let segment = self
+            .segments
+            .iter()
+            .take_while(|segment| segment.timestamp <= position)
+            .last()
+            .or_else(|| self.segments.first())
+            .copied()
+            .ok_or_else(|| {
+                YoutubeError::UnsupportedOperation(
+                    "MP4 stream has no segment index to seek with".to_string(),
+                )
+            })?;
+
+        self.reader.seek_to(segment.offset).await?;
+        self.samples.clear();
+        self.mdat = None;
+        self.next_decode_time = duration_to_ticks(segment.timestamp, self.track().timescale);
+        self.skip_until = (position > segment.timestamp).then_some(position);
+
+        Ok(segment.timestamp)
+    }
+
+    fn parse_moof(&mut self, body: &[u8], moof_offset: u64) -> Result<()> {
+        self.samples.clear();
+        self.mdat = None;
+
+        for child in Boxes::new(body) {
+            let (kind, traf) = child?;
+            if &kind != b"traf" {
+                continue;
+            }
+            let tfhd = find_box(traf, b"tfhd")?.ok_or_else(|| invalid("traf without tfhd"))?;
+            let mut cursor = ByteCursor::new(tfhd);
+            let (_, flags) = cursor.full_box_header()?;
+            if cursor.u32()? != self.track_id {
+                continue;
+            }
+
+            let track = self.track();
+            let mut base_data_offset = moof_offset;
+            let mut default_duration = track.default_sample_duration;
+            let mut default_size = track.default_sample_size;
+            if flags & TFHD_BASE_DATA_OFFSET != 0 {
+                base_data_offset = cursor.u64()?;
+            }
+            if flags & TFHD_SAMPLE_DESCRIPTION_INDEX != 0 {
+                cursor.skip(4)?;
+            }
+            if flags & TFHD_DEFAULT_SAMPLE_DURATION != 0 {
+                default_duration = cursor.u32()?;
+            }
+            if flags & TFHD_DEFAULT_SAMPLE_SIZE != 0 {
+                default_size = cursor.u32()?;
+            }
+            if flags & TFHD_DEFAULT_SAMPLE_FLAGS != 0 {
+                cursor.skip(4)?;
+            }
+
+            if let Some(tfdt) = find_box(traf, b"tfdt")? {
+                let mut cursor = ByteCursor::new(tfdt);
+                let (version, _) = cursor.full_box_header()?;
+                self.next_decode_time = cursor.versioned(version)?;
+            }
+
+            let mut data_end = base_data_offset;
+            for child in Boxes::new(traf) {
+                let (kind, trun) = child?;
+                if &kind != b"trun" {
+                    continue;
+                }
+
+                let mut cursor = ByteCursor::new(trun);
+                let (_, flags) = cursor.full_box_header()?;
+                let sample_count = cursor.u32()?;
+                // Runs without an explicit offset continue where the previous one ended
+                let mut offset = if flags & TRUN_DATA_OFFSET != 0 {
+                    base_data_offset
+                        .checked_add_signed(i64::from(cursor.u32()? as i32))
+                        .ok_or_else(|| invalid("trun data offset out of range"))?
+                } else {
+                    data_end
+                };
+                if flags & TRUN_FIRST_SAMPLE_FLAGS != 0 {
+                    cursor.skip(4)?;
+                }
+
+                for _ in 0..sample_count {
+                    let duration = if flags & TRUN_SAMPLE_DURATION != 0 {
+                        cursor.u32()?
+                    } else {
+                        default_duration
+                    };
+                    let size = if flags & TRUN_SAMPLE_SIZE != 0 {
+                        cursor.u32()?
+                    } else {
+                        default_size
+                    };
+                    if flags & TRUN_SAMPLE_FLAGS != 0 {
+                        cursor.skip(4)?;
+                    }
+                    if flags & TRUN_SAMPLE_COMPOSITION_OFFSET != 0 {
+                        cursor.skip(4)?;
+                    }
+
+                    self.samples.push_back(SampleRef {
+                        offset,
+                        size,
+                        decode_time: self.next_decode_time,
+                    });
+                    offset += u64::from(size);
+                    self.next_decode_time += u64::from(duration);
+                }
+                data_end = offset;
+            }
+        }
+
+        self.samples
+            .make_contiguous()
+            .sort_by_key(|sample| sample.offset);
+        Ok(())
+    }
+}
+
+async fn skip<R: AsyncRead + AsyncSeek + Unpin>(
+    reader: &mut BoxReader<R>,
+    header: &BoxHeader,
+) -> Result<()> {
+    let end = header.end().ok_or_else(|| {
+        invalid(&format!(
+            "cannot skip unsized {} box",
+            kind_name(&header.kind)
+        ))
+    })?;
+    reader.seek_to(end).await
+}
+
+fn invalid(message: &str) -> YoutubeError {
+    YoutubeError::ParseError(format!("Invalid MP4 stream: {message}"))
+}
+
+fn ticks_to_duration(ticks: u64, timescale: u32) -> Duration {
+    let timescale = u64::from(timescale.max(1));
+    Duration::from_secs(ticks / timescale)
+        + Duration::from_nanos((ticks % timescale) * 1_000_000_000 / timescale)
+}
+
+fn duration_to_ticks(duration: Duration, timescale: u32) -> u64 {
+    (duration.as_nanos() * u128::from(timescale) / 1_000_000_000) as u64
+}
+
+/// Parse the AAC tracks out of a `moov` body
+fn parse_moov(body: &[u8]) -> Result<Vec<Mp4Track>> {
+    let mut tracks = Vec::new();
+    // (track_id, default_sample_duration, default_sample_size) from mvex/trex
+    let mut trex = Vec::new();
+
+    if let Some(mvex) = find_box(body, b"mvex")? {
+        for child in Boxes::new(mvex) {
+            let (kind, body) = child?;
+            if &kind == b"trex" {
+                let mut cursor = ByteCursor::new(body);
+                cursor.full_box_header()?;
+                let track_id = cursor.u32()?;
+                cursor.skip(4)?;
+                trex.push((track_id, cursor.u32()?, cursor.u32()?));
+            }
+        }
+    }
+
+    for child in Boxes::new(body) {
+        let (kind, trak) = child?;
+        if &kind != b"trak" {
+            continue;
+        }
+        if let Some(mut track) = parse_trak(trak)? {
+            if let Some((_, duration, size)) = trex.iter().find(|(id, ..)| *id == track.track_id) {
+                track.default_sample_duration = *duration;
+                track.default_sample_size = *size;
+            }
+            tracks.push(track);
+        }
+    }
+
+    Ok(tracks)
+}
+
+/// Parse a `trak` body, returning `None` unless it is an AAC audio track
+fn parse_trak(trak: &[u8]) -> Result<Option<Mp4Track>> {
+    let tkhd = find_box(trak, b"tkhd")?.ok_or_else(|| invalid("trak without tkhd"))?;
+    let mut cursor = ByteCursor::new(tkhd);
+    let (version, _) = cursor.full_box_header()?;
+    cursor.versioned(version)?;
+    cursor.versioned(version)?;
+    let track_id = cursor.u32()?;
+
+    let mdia = find_box(trak, b"mdia")?.ok_or_else(|| invalid("trak without mdia"))?;
+
+    let hdlr = find_box(mdia, b"hdlr")?.ok_or_else(|| invalid("mdia without hdlr"))?;
+    let mut cursor = ByteCursor::new(hdlr);
+    cursor.full_box_header()?;
+    cursor.skip(4)?;
+    if cursor.take(4)? != b"soun" {
+        return Ok(None);
+    }
+
+    let mdhd = find_box(mdia, b"mdhd")?.ok_or_else(|| invalid("mdia without mdhd"))?;
+    let mut cursor = ByteCursor::new(mdhd);
+    let (version, _) = cursor.full_box_header()?;
+    cursor.versioned(version)?;
+    cursor.versioned(version)?;
+    let timescale = cursor.u32()?;
+
+    let stsd = find_box(mdia, b"minf")?
+        .map(|minf| find_box(minf, b"stbl"))
+        .transpose()?
+        .flatten()
+        .map(|stbl| find_box(stbl, b"stsd"))
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| invalid("sound track without stsd"))?;
+
+    let mut cursor = ByteCursor::new(stsd);
+    cursor.full_box_header()?;
+    cursor.u32()?;
+    let Some(mp4a) = find_box(cursor.remaining(), b"mp4a")? else {
+        return Ok(None);
+    };
+
+    let mut cursor = ByteCursor::new(mp4a);
+    cursor.skip(16)?;
+    let channels = cursor.u16()?;
+    cursor.skip(6)?;
+    let sample_rate = cursor.u32()? >> 16;
+
+    let Some(esds) = find_box(cursor.remaining(), b"esds")? else {
+        return Ok(None);
+    };
+    let Some(decoder_config) = parse_esds(esds)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(Mp4Track {
+        track_id,
+        timescale,
+        sample_rate,
+        channels,
+        decoder_config,
+        default_sample_duration: 0,
+        default_sample_size: 0,
+    }))
+}
+
+/// Extract the AudioSpecificConfig of an MPEG-4 audio `esds` box
+fn parse_esds(esds: &[u8]) -> Result<Option<Vec<u8>>> {
+    let mut cursor = ByteCursor::new(esds);
+    cursor.full_box_header()?;
+
+    let (tag, es) = read_descriptor(&mut cursor)?;
+    if tag != 0x03 {
+        return Err(invalid("esds without an ES descriptor"));
+    }
+    let mut cursor = ByteCursor::new(es);
+    cursor.skip(2)?;
+    let flags = cursor.u8()?;
+    if flags & 0x80 != 0 {
+        cursor.skip(2)?;
+    }
+    if flags & 0x40 != 0 {
+        let url_length = cursor.u8()?;
+        cursor.skip(usize::from(url_length))?;
+    }
+    if flags & 0x20 != 0 {
+        cursor.skip(2)?;
+    }
+
+    let (tag, decoder_config) = read_descriptor(&mut cursor)?;
+    if tag != 0x04 {
+        return Err(invalid("esds without a decoder config descriptor"));
+    }
+    let mut cursor = ByteCursor::new(decoder_config);
+    if cursor.u8()? != OBJECT_TYPE_MPEG4_AUDIO {
+        return Ok(None);
+    }
+    cursor.skip(12)?;
+
+    let (tag, specific_info) = read_descriptor(&mut cursor)?;
+    if tag != 0x05 {
+        return Err(invalid("esds without decoder specific info"));
+    }
+    Ok(Some(specific_info.to_vec()))
+}
+
+fn read_descriptor<'a>(cursor: &mut ByteCursor<'a>) -> Result<(u8, &'a [u8])> {
+    let tag = cursor.u8()?;
+    let mut length = 0usize;
+    for _ in 0..4 {
+        let byte = cursor.u8()?;
+        length = (length << 7) | usize::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Ok((tag, cursor.take(length)?))
+}
+
+/// Parse a `sidx` body into segments; `end` is the stream offset just past the box
+fn parse_sidx(body: &[u8], end: u64) -> Result<Vec<SegmentReference>> {
+    let mut cursor = ByteCursor::new(body);
+    let (version, _) = cursor.full_box_header()?;
+    cursor.u32()?;
+    let timescale = cursor.u32()?;
+    let mut time = cursor.versioned(version)?;
+    let mut offset = end + cursor.versioned(version)?;
+    cursor.skip(2)?;
+    let count = cursor.u16()?;
+
+    let mut segments = Vec::with_capacity(usize::from(count));
+    for _ in 0..count {
+        let reference = cursor.u32()?;
+        let duration = cursor.u32()?;
+        cursor.skip(4)?;
+
+        if reference & 0x8000_0000 != 0 {
+            return Err(YoutubeError::UnsupportedOperation(
+                "Hierarchical MP4 segment indexes are not supported".to_string(),
+            ));
+        }
+        let size = u64::from(reference & 0x7FFF_FFFF);
+
+        // Derive the duration from both ends so consecutive segments line up exactly
+        let timestamp = ticks_to_duration(time, timescale);
+        segments.push(SegmentReference {
+            timestamp,
+            duration: ticks_to_duration(time + u64::from(duration), timescale) - timestamp,
+            offset,
+            size,
+        });
+        time += u64::from(duration);
+        offset += size;
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_to_duration() {
+        assert_eq!(ticks_to_duration(44100, 44100), Duration::from_secs(1));
+        assert_eq!(ticks_to_duration(1024, 44100).as_micros(), 23219);
+        assert_eq!(duration_to_ticks(Duration::from_secs(2), 44100), 88200);
+    }
+
+    #[test]
+    fn test_parse_esds() {
+        let mut esds = vec![0, 0, 0, 0];
+        // ES descriptor with a multi-byte length
+        esds.extend_from_slice(&[0x03, 0x80, 0x80, 0x80, 22, 0, 1, 0]);
+        esds.extend_from_slice(&[0x04, 17, 0x40, 0x15]);
+        esds.extend_from_slice(&[0; 11]);
+        esds.extend_from_slice(&[0x05, 2, 0x12, 0x10]);
+
+        assert_eq!(parse_esds(&esds).unwrap(), Some(vec![0x12, 0x10]));
+
+        esds[14] = 0x6B; // MP3
+        assert_eq!(parse_esds(&esds).unwrap(), None);
+    }
+}
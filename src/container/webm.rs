@@ -0,0 +1,809 @@
+use super::ebml::{
+    parse_float, parse_signed_vint, parse_string, parse_uint, parse_vint, Children, EbmlReader,
+};
+use super::AudioPacket;
+use crate::error::{Result, YoutubeError};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncSeek};
+
+/// Matroska element IDs used by the demuxer
+mod ids {
+    pub const EBML: u32 = 0x1A45DFA3;
+    pub const DOC_TYPE: u32 = 0x4282;
+    pub const SEGMENT: u32 = 0x18538067;
+    pub const SEEK_HEAD: u32 = 0x114D9B74;
+    pub const SEEK: u32 = 0x4DBB;
+    pub const SEEK_ID: u32 = 0x53AB;
+    pub const SEEK_POSITION: u32 = 0x53AC;
+    pub const INFO: u32 = 0x1549A966;
+    pub const TIMECODE_SCALE: u32 = 0x2AD7B1;
+    pub const DURATION: u32 = 0x4489;
+    pub const TRACKS: u32 = 0x1654AE6B;
+    pub const TRACK_ENTRY: u32 = 0xAE;
+    pub const TRACK_NUMBER: u32 = 0xD7;
+    pub const TRACK_TYPE: u32 = 0x83;
+    pub const CODEC_ID: u32 = 0x86;
+    pub const CODEC_PRIVATE: u32 = 0x63A2;
+    pub const CODEC_DELAY: u32 = 0x56AA;
+    pub const SEEK_PRE_ROLL: u32 = 0x56BB;
+    pub const AUDIO: u32 = 0xE1;
+    pub const SAMPLING_FREQUENCY: u32 = 0xB5;
+    pub const CHANNELS: u32 = 0x9F;
+    pub const CUES: u32 = 0x1C53BB6B;
+    pub const CUE_POINT: u32 = 0xBB;
+    pub const CUE_TIME: u32 = 0xB3;
+    pub const CUE_TRACK_POSITIONS: u32 = 0xB7;
+    pub const CUE_TRACK: u32 = 0xF7;
+    pub const CUE_CLUSTER_POSITION: u32 = 0xF1;
+    pub const CLUSTER: u32 = 0x1F43B675;
+    pub const CLUSTER_TIMECODE: u32 = 0xE7;
+    pub const SIMPLE_BLOCK: u32 = 0xA3;
+    pub const BLOCK_GROUP: u32 = 0xA0;
+    pub const BLOCK: u32 = 0xA1;
+}
+
+const TRACK_TYPE_AUDIO: u64 = 2;
+const DEFAULT_TIMECODE_SCALE: u64 = 1_000_000;
+
+/// Codec ID of Opus audio tracks
+pub const OPUS_CODEC_ID: &str = "A_OPUS";
+
+/// A track entry from the `Tracks` element
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebmTrack {
+    pub number: u64,
+    pub track_type: u64,
+    pub codec_id: String,
+    /// For Opus this is the `OpusHead` identification header
+    pub codec_private: Vec<u8>,
+    pub sample_rate: Option<f64>,
+    pub channels: Option<u64>,
+    pub codec_delay: Option<Duration>,
+    pub seek_pre_roll: Option<Duration>,
+}
+
+impl WebmTrack {
+    pub fn is_audio(&self) -> bool {
+        self.track_type == TRACK_TYPE_AUDIO
+    }
+
+    pub fn is_opus(&self) -> bool {
+        self.is_audio() && self.codec_id == OPUS_CODEC_ID
+    }
+}
+
+/// An entry of the `Cues` seek index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CuePoint {
+    pub timestamp: Duration,
+    pub track: u64,
+    /// Cluster offset relative to the start of the segment body
+    pub cluster_position: u64,
+}
+
+#[derive(Debug, Default)]
+struct RawCuePoint {
+    time: u64,
+    track: u64,
+    cluster_position: u64,
+}
+
+/// WebM (Matroska) demuxer producing timestamped audio packets
+///
+/// Based on lavaplayer's MatroskaStreamingFile, this provides:
+/// - EBML header and `Segment` parsing up to the first `Cluster`
+/// - Track entries with Opus codec private data and delays
+/// - The `Cues` index, located through the `SeekHead` when it trails the clusters
+/// - Packet extraction from `SimpleBlock` and `BlockGroup`, including lacing
+/// - Time-based seeking through the cue index
+///
+/// Packets are only produced for the selected track, which defaults to the
+/// first Opus track (or the first audio track if there is no Opus track).
+#[derive(Debug)]
+pub struct WebmDemuxer<R> {
+    reader: EbmlReader<R>,
+    segment_data_offset: u64,
+    timecode_scale: u64,
+    duration: Option<Duration>,
+    tracks: Vec<WebmTrack>,
+    cues: Vec<CuePoint>,
+    track_number: u64,
+    cluster_timecode: u64,
+    pending: VecDeque<AudioPacket>,
+    skip_until: Option<Duration>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> WebmDemuxer<R> {
+    /// Parse the headers of a WebM stream and position it at the first cluster
+    pub async fn open(reader: R) -> Result<Self> {
+        let mut reader = EbmlReader::new(reader);
+
+        let header = reader
+            .read_element_header()
+            .await?
+            .filter(|header| header.id == ids::EBML)
+            .ok_or_else(|| invalid("missing EBML header"))?;
+        let doc_type = Children::new(&reader.read_body(&header).await?)
+            .filter_map(|child| child.ok())
+            .find(|(id, _)| *id == ids::DOC_TYPE)
+            .map(|(_, body)| parse_string(body));
+        match doc_type.as_deref() {
+            Some("webm") | Some("matroska") => {}
+            other => return Err(invalid(&format!("unsupported document type {other:?}"))),
+        }
+
+        let segment = reader
+            .read_element_header()
+            .await?
+            .filter(|header| header.id == ids::SEGMENT)
+            .ok_or_else(|| invalid("missing Segment"))?;
+        let segment_data_offset = segment.data_offset;
+
+        let mut timecode_scale = DEFAULT_TIMECODE_SCALE;
+        let mut raw_duration = None;
+        let mut tracks = Vec::new();
+        let mut raw_cues = Vec::new();
+        let mut cues_position = None;
+        let mut first_cluster_offset = None;
+
+        while let Some(element) = reader.read_element_header().await? {
+            match element.id {
+                ids::SEEK_HEAD => {
+                    cues_position = parse_seek_head(&reader.read_body(&element).await?)?;
+                }
+                ids::INFO => {
+                    let (scale, duration) = parse_info(&reader.read_body(&element).await?)?;
+                    timecode_scale = scale;
+                    raw_duration = duration;
+                }
+                ids::TRACKS => tracks = parse_tracks(&reader.read_body(&element).await?)?,
+                ids::CUES => raw_cues = parse_cues(&reader.read_body(&element).await?)?,
+                ids::CLUSTER => {
+                    first_cluster_offset = Some(element.offset);
+                    break;
+                }
+                _ => reader.skip_body(&element).await?,
+            }
+        }
+
+        let first_cluster_offset =
+            first_cluster_offset.ok_or_else(|| invalid("no clusters found"))?;
+
+        // YouTube puts the cue index after the clusters, so jump there and back
+        if raw_cues.is_empty() {
+            if let Some(position) = cues_position {
+                reader.seek_to(segment_data_offset + position).await?;
+                if let Some(element) = reader.read_element_header().await? {
+                    if element.id == ids::CUES {
+                        raw_cues = parse_cues(&reader.read_body(&element).await?)?;
+                    }
+                }
+            }
+        }
+        reader.seek_to(first_cluster_offset).await?;
+
+        let track_number = tracks
+            .iter()
+            .find(|track| track.is_opus())
+            .or_else(|| tracks.iter().find(|track| track.is_audio()))
+            .map(|track| track.number)
+            .ok_or_else(|| invalid("no audio track found"))?;
+
+        let to_duration = |ticks: u64| Duration::from_nanos(ticks.saturating_mul(timecode_scale));
+        let cues = raw_cues
+            .into_iter()
+            .map(|cue| CuePoint {
+                timestamp: to_duration(cue.time),
+                track: cue.track,
+                cluster_position: cue.cluster_position,
+            })
+            .collect();
+        let duration = raw_duration
+            .map(|ticks: f64| Duration::from_nanos((ticks * timecode_scale as f64) as u64));
+
+        Ok(Self {
+            reader,
+            segment_data_offset,
+            timecode_scale,
+            duration,
+            tracks,
+            cues,
+            track_number,
+            cluster_timecode: 0,
+            pending: VecDeque::new(),
+            skip_until: None,
+        })
+    }
+
+    pub fn tracks(&self) -> &[WebmTrack] {
+        &self.tracks
+    }
+
+    /// The track packets are produced for
+    pub fn track(&self) -> &WebmTrack {
+        self.tracks
+            .iter()
+            .find(|track| track.number == self.track_number)
+            .expect("selected track exists")
+    }
+
+    /// Produce packets for a different track from now on
+    pub fn select_track(&mut self, track_number: u64) -> Result<()> {
+        if !self.tracks.iter().any(|track| track.number == track_number) {
+            return Err(invalid(&format!("no track with number {track_number}")));
+        }
+        self.track_number = track_number;
+        self.pending.clear();
+        Ok(())
+    }
+
+    pub fn cues(&self) -> &[CuePoint] {
+        &self.cues
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Read the next packet of the selected track, or `None` at the end
+    pub async fn next_packet(&mut self) -> Result<Option<AudioPacket>> {
+        loop {
+            if let Some(packet) = self.pending.pop_front() {
+                if self
+                    .skip_until
+                    .is_some_and(|target| packet.timestamp < target)
+                {
+                    continue;
+                }
+                self.skip_until = None;
+                return Ok(Some(packet));
+            }
+
+            let Some(element) = self.reader.read_element_header().await? else {
+                return Ok(None);
+            };
+
+            match element.id {
+                // Descend into clusters; their children are read one by one
+                ids::CLUSTER => {}
+                ids::CLUSTER_TIMECODE => {
+                    self.cluster_timecode = parse_uint(&self.reader.read_body(&element).await?)?;
+                }
+                ids::SIMPLE_BLOCK => {
+                    let body = self.reader.read_body(&element).await?;
+                    self.queue_block(&body)?;
+                }
+                ids::BLOCK_GROUP => {
+                    let body = self.reader.read_body(&element).await?;
+                    for child in Children::new(&body) {
+                        let (id, block) = child?;
+                        if id == ids::BLOCK {
+                            self.queue_block(block)?;
+                        }
+                    }
+                }
+                _ => self.reader.skip_body(&element).await?,
+            }
+        }
+    }
+
+    /// Seek so that the next packet is the first one at or after `position`
+    ///
+    /// Jumps to the cluster of the last cue point at or before `position`
+    /// and returns that cue point's timestamp.
+    pub async fn seek(&mut self, position: Duration) -> Result<Duration> {
+        let cue = self
+            .cues
+            .iter()
+            .filter(|cue| cue.track == self.track_number)
+            .take_while(|cue| cue.timestamp <= position)
+            .last()
+            .or_else(|| self.cues.first())
+            .copied()
+            .ok_or_else(|| {
+                YoutubeError::UnsupportedOperation(
+                    "WebM stream has no cue index to seek with".to_string(),
+                )
+            })?;
+
+        self.reader
+            .seek_to(self.segment_data_offset + cue.cluster_position)
+            .await?;
+        self.pending.clear();
+        self.cluster_timecode = 0;
+        self.skip_until = (position > cue.timestamp).then_some(position);
+
+        Ok(cue.timestamp)
+    }
+
+    fn queue_block(&mut self, block: &[u8]) -> Result<()> {
+        let (track, track_length) =
+            parse_vint(block).ok_or_else(|| invalid("block without a track number"))?;
+        if track != self.track_number {
+            return Ok(());
+        }
+
+        let header = block
+            .get(track_length..track_length + 3)
+            .ok_or_else(|| invalid("truncated block header"))?;
+        let relative_timecode = i16::from_be_bytes([header[0], header[1]]);
+        let flags = header[2];
+
+        let ticks = (self.cluster_timecode as i64 + i64::from(relative_timecode)).max(0) as u64;
+        let timestamp = Duration::from_nanos(ticks.saturating_mul(self.timecode_scale));
+
+        for frame in split_laced_frames(flags, &block[track_length + 3..])? {
+            self.pending.push_back(AudioPacket {
+                timestamp,
+                data: frame.to_vec(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid(message: &str) -> YoutubeError {
+    YoutubeError::ParseError(format!("Invalid WebM stream: {message}"))
+}
+
+/// Split a block payload into frames according to its lacing flags
+///
+/// All frames of a laced block share the block's timestamp.
+pub fn split_laced_frames(flags: u8, payload: &[u8]) -> Result<Vec<&[u8]>> {
+    const NO_LACING: u8 = 0x00;
+    const XIPH_LACING: u8 = 0x02;
+    const FIXED_LACING: u8 = 0x04;
+    const EBML_LACING: u8 = 0x06;
+
+    let lacing = flags & 0x06;
+    if lacing == NO_LACING {
+        return Ok(vec![payload]);
+    }
+
+    let (&count, mut rest) = payload
+        .split_first()
+        .ok_or_else(|| invalid("laced block without a frame count"))?;
+    let count = usize::from(count) + 1;
+
+    let mut sizes = Vec::with_capacity(count);
+    match lacing {
+        XIPH_LACING => {
+            for _ in 1..count {
+                let mut size = 0usize;
+                loop {
+                    let (&byte, remaining) = rest
+                        .split_first()
+                        .ok_or_else(|| invalid("truncated Xiph lacing"))?;
+                    rest = remaining;
+                    size += usize::from(byte);
+                    if byte != 0xFF {
+                        break;
+                    }
+                }
+                sizes.push(size);
+            }
+        }
+        FIXED_LACING => {
+            if rest.len() % count != 0 {
+                return Err(invalid("fixed lacing does not divide the block evenly"));
+            }
+            sizes.resize(count - 1, rest.len() / count);
+        }
+        EBML_LACING => {
+            let (first, length) =
+                parse_vint(rest).ok_or_else(|| invalid("truncated EBML lacing"))?;
+            rest = &rest[length..];
+            let mut size = first as i64;
+            sizes.push(first as usize);
+
+            for _ in 2..count {
+                let (delta, length) =
+                    parse_signed_vint(rest).ok_or_else(|| invalid("truncated EBML lacing"))?;
+                rest = &rest[length..];
+                size += delta;
+                if size < 0 {
+                    return Err(invalid("negative EBML laced frame size"));
+                }
+                sizes.push(size as usize);
+            }
+        }
+        _ => unreachable!("lacing is a two bit field"),
+    }
+
+    let mut frames = Vec::with_capacity(count);
+    for size in sizes {
+        if size > rest.len() {
+            return Err(invalid("laced frame exceeds the block"));
+        }
+        let (frame, remaining) = rest.split_at(size);
+        frames.push(frame);
+        rest = remaining;
+    }
+    frames.push(rest);
+
+    Ok(frames)
+}
+
+/// Find the segment-relative position of the `Cues` element
+fn parse_seek_head(body: &[u8]) -> Result<Option<u64>> {
+    for child in Children::new(body) {
+        let (id, seek) = child?;
+        if id != ids::SEEK {
+            continue;
+        }
+
+        let mut seek_id = None;
+        let mut position = None;
+        for field in Children::new(seek) {
+            match field? {
+                (ids::SEEK_ID, value) => seek_id = Some(parse_uint(value)?),
+                (ids::SEEK_POSITION, value) => position = Some(parse_uint(value)?),
+                _ => {}
+            }
+        }
+
+        if seek_id == Some(u64::from(ids::CUES)) {
+            return Ok(position);
+        }
+    }
+    Ok(None)
+}
+
+fn parse_info(body: &[u8]) -> Result<(u64, Option<f64>)> {
+    let mut timecode_scale = DEFAULT_TIMECODE_SCALE;
+    let mut duration = None;
+
+    for child in Children::new(body) {
+        match child? {
+            (ids::TIMECODE_SCALE, value) => timecode_scale = parse_uint(value)?,
+            (ids::DURATION, value) => duration = Some(parse_float(value)?),
+            _ => {}
+        }
+    }
+
+    if timecode_scale == 0 {
+        return Err(invalid("timecode scale of zero"));
+    }
+    Ok((timecode_scale, duration))
+}
+
+fn parse_tracks(body: &[u8]) -> Result<Vec<WebmTrack>> {
+    let mut tracks = Vec::new();
+
+    for child in Children::new(body) {
+        let (id, entry) = child?;
+        if id != ids::TRACK_ENTRY {
+            continue;
+        }
+
+        let mut track = WebmTrack {
+            number: 0,
+            track_type: 0,
+            codec_id: String::new(),
+            codec_private: Vec::new(),
+            sample_rate: None,
+            channels: None,
+            codec_delay: None,
+            seek_pre_roll: None,
+        };
+
+        for field in Children::new(entry) {
+            match field? {
+                (ids::TRACK_NUMBER, value) => track.number = parse_uint(value)?,
+                (ids::TRACK_TYPE, value) => track.track_type = parse_uint(value)?,
+                (ids::CODEC_ID, value) => track.codec_id = parse_string(value),
+                (ids::CODEC_PRIVATE, value) => track.codec_private = value.to_vec(),
+                (ids::CODEC_DELAY, value) => {
+                    track.codec_delay = Some(Duration::from_nanos(parse_uint(value)?))
+                }
+                (ids::SEEK_PRE_ROLL, value) => {
+                    track.seek_pre_roll = Some(Duration::from_nanos(parse_uint(value)?))
+                }
+                (ids::AUDIO, audio) => {
+                    for setting in Children::new(audio) {
+                        match setting? {
+                            (ids::SAMPLING_FREQUENCY, value) => {
+                                track.sample_rate = Some(parse_float(value)?)
+                            }
+                            (ids::CHANNELS, value) => track.channels = Some(parse_uint(value)?),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        tracks.push(track);
+    }
+
+    Ok(tracks)
+}
+
+fn parse_cues(body: &[u8]) -> Result<Vec<RawCuePoint>> {
+    let mut cues = Vec::new();
+
+    for child in Children::new(body) {
+        let (id, point) = child?;
+        if id != ids::CUE_POINT {
+            continue;
+        }
+
+        let mut time = 0;
+        for field in Children::new(point) {
+            match field? {
+                (ids::CUE_TIME, value) => time = parse_uint(value)?,
+                (ids::CUE_TRACK_POSITIONS, positions) => {
+                    let mut cue = RawCuePoint {
+                        time,
+                        ..Default::default()
+                    };
+                    for position in Children::new(positions) {
+                        match position? {
+                            (ids::CUE_TRACK, value) => cue.track = parse_uint(value)?,
+                            (ids::CUE_CLUSTER_POSITION, value) => {
+                                cue.cluster_position = parse_uint(value)?
+                            }
+                            _ => {}
+                        }
+                    }
+                    cues.push(cue);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    cues.sort_by_key(|cue| cue.time);
+    Ok(cues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Encode an element with an 8 byte size so offsets are easy to predict
+    fn element(id: u32, body: &[u8]) -> Vec<u8> {
+        let mut data: Vec<u8> = id
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        data.push(0x01);
+        data.extend_from_slice(&(body.len() as u64).to_be_bytes()[1..]);
+        data.extend_from_slice(body);
+        data
+    }
+
+    fn uint(id: u32, value: u64) -> Vec<u8> {
+        element(id, &value.to_be_bytes())
+    }
+
+    fn simple_block(track: u8, relative_timecode: i16, payload: &[u8]) -> Vec<u8> {
+        let mut body = vec![0x80 | track];
+        body.extend_from_slice(&relative_timecode.to_be_bytes());
+        body.push(0x80); // keyframe, no lacing
+        body.extend_from_slice(payload);
+        element(ids::SIMPLE_BLOCK, &body)
+    }
+
+    fn cluster(timecode: u64, blocks: &[(u8, i16, &[u8])]) -> Vec<u8> {
+        let mut body = uint(ids::CLUSTER_TIMECODE, timecode);
+        for (track, relative, payload) in blocks {
+            body.extend(simple_block(*track, *relative, payload));
+        }
+        element(ids::CLUSTER, &body)
+    }
+
+    /// A WebM file shaped like YouTube's: Opus audio, cues after the clusters
+    fn sample_webm() -> Vec<u8> {
+        let header = element(ids::EBML, &element(ids::DOC_TYPE, b"webm"));
+
+        let info = element(
+            ids::INFO,
+            &[
+                uint(ids::TIMECODE_SCALE, 1_000_000),
+                element(ids::DURATION, &3000f64.to_be_bytes()),
+            ]
+            .concat(),
+        );
+
+        let opus = element(
+            ids::TRACK_ENTRY,
+            &[
+                uint(ids::TRACK_NUMBER, 1),
+                uint(ids::TRACK_TYPE, 2),
+                element(ids::CODEC_ID, b"A_OPUS"),
+                element(ids::CODEC_PRIVATE, b"OpusHead"),
+                uint(ids::CODEC_DELAY, 6_500_000),
+                element(
+                    ids::AUDIO,
+                    &[
+                        element(ids::SAMPLING_FREQUENCY, &48000f64.to_be_bytes()),
+                        uint(ids::CHANNELS, 2),
+                    ]
+                    .concat(),
+                ),
+            ]
+            .concat(),
+        );
+        let video = element(
+            ids::TRACK_ENTRY,
+            &[
+                uint(ids::TRACK_NUMBER, 2),
+                uint(ids::TRACK_TYPE, 1),
+                element(ids::CODEC_ID, b"V_VP9"),
+            ]
+            .concat(),
+        );
+        let tracks = element(ids::TRACKS, &[opus, video].concat());
+
+        let clusters = [
+            cluster(0, &[(1, 0, b"a0"), (2, 0, b"v0"), (1, 500, b"a1")]),
+            cluster(1000, &[(1, 0, b"b0"), (1, 500, b"b1")]),
+            cluster(2000, &[(1, 0, b"c0"), (1, 500, b"c1")]),
+        ];
+
+        // The seek head has a fixed size, so positions can be computed up front
+        let seek_head_size = element(
+            ids::SEEK_HEAD,
+            &element(
+                ids::SEEK,
+                &[uint(ids::SEEK_ID, 0), uint(ids::SEEK_POSITION, 0)].concat(),
+            ),
+        )
+        .len();
+        let first_cluster = seek_head_size + info.len() + tracks.len();
+
+        let mut cue_points = Vec::new();
+        let mut position = first_cluster;
+        for (index, cluster) in clusters.iter().enumerate() {
+            cue_points.extend(element(
+                ids::CUE_POINT,
+                &[
+                    uint(ids::CUE_TIME, index as u64 * 1000),
+                    element(
+                        ids::CUE_TRACK_POSITIONS,
+                        &[
+                            uint(ids::CUE_TRACK, 1),
+                            uint(ids::CUE_CLUSTER_POSITION, position as u64),
+                        ]
+                        .concat(),
+                    ),
+                ]
+                .concat(),
+            ));
+            position += cluster.len();
+        }
+
+        let seek_head = element(
+            ids::SEEK_HEAD,
+            &element(
+                ids::SEEK,
+                &[
+                    uint(ids::SEEK_ID, u64::from(ids::CUES)),
+                    uint(ids::SEEK_POSITION, position as u64),
+                ]
+                .concat(),
+            ),
+        );
+
+        let segment_body = [
+            seek_head,
+            info,
+            tracks,
+            clusters.concat(),
+            element(ids::CUES, &cue_points),
+        ]
+        .concat();
+
+        [header, element(ids::SEGMENT, &segment_body)].concat()
+    }
+
+    async fn collect(demuxer: &mut WebmDemuxer<Cursor<Vec<u8>>>) -> Vec<(u64, Vec<u8>)> {
+        let mut packets = Vec::new();
+        while let Some(packet) = demuxer.next_packet().await.unwrap() {
+            packets.push((packet.timestamp.as_millis() as u64, packet.data));
+        }
+        packets
+    }
+
+    #[tokio::test]
+    async fn test_open_parses_headers_and_trailing_cues() {
+        let demuxer = WebmDemuxer::open(Cursor::new(sample_webm())).await.unwrap();
+
+        assert_eq!(demuxer.tracks().len(), 2);
+        let track = demuxer.track();
+        assert!(track.is_opus());
+        assert_eq!(track.number, 1);
+        assert_eq!(track.codec_private, b"OpusHead");
+        assert_eq!(track.sample_rate, Some(48000.0));
+        assert_eq!(track.channels, Some(2));
+        assert_eq!(track.codec_delay, Some(Duration::from_micros(6500)));
+
+        assert_eq!(demuxer.duration(), Some(Duration::from_secs(3)));
+        let cue_times: Vec<_> = demuxer.cues().iter().map(|cue| cue.timestamp).collect();
+        assert_eq!(
+            cue_times,
+            vec![
+                Duration::ZERO,
+                Duration::from_secs(1),
+                Duration::from_secs(2)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_packets_for_selected_track() {
+        let mut demuxer = WebmDemuxer::open(Cursor::new(sample_webm())).await.unwrap();
+
+        assert_eq!(
+            collect(&mut demuxer).await,
+            vec![
+                (0, b"a0".to_vec()),
+                (500, b"a1".to_vec()),
+                (1000, b"b0".to_vec()),
+                (1500, b"b1".to_vec()),
+                (2000, b"c0".to_vec()),
+                (2500, b"c1".to_vec()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_seek_uses_cues() {
+        let mut demuxer = WebmDemuxer::open(Cursor::new(sample_webm())).await.unwrap();
+
+        let cue = demuxer.seek(Duration::from_millis(1700)).await.unwrap();
+        assert_eq!(cue, Duration::from_secs(1));
+        assert_eq!(
+            collect(&mut demuxer).await,
+            vec![(2000, b"c0".to_vec()), (2500, b"c1".to_vec())]
+        );
+
+        // Seeking backwards lands exactly on a cue
+        let cue = demuxer.seek(Duration::from_secs(1)).await.unwrap();
+        assert_eq!(cue, Duration::from_secs(1));
+        let next = demuxer.next_packet().await.unwrap().unwrap();
+        assert_eq!(next.data, b"b0");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_webm() {
+        let data = element(ids::EBML, &element(ids::DOC_TYPE, b"mkv3d"));
+        let err = WebmDemuxer::open(Cursor::new(data)).await.unwrap_err();
+        assert!(err.to_string().contains("unsupported document type"));
+    }
+
+    #[test]
+    fn test_split_laced_frames() {
+        // Xiph: three frames of 2, 256 and 1 bytes
+        let mut xiph = vec![2, 2, 0xFF, 1];
+        xiph.extend_from_slice(&[1; 2]);
+        xiph.extend_from_slice(&[2; 256]);
+        xiph.push(3);
+        let frames = split_laced_frames(0x02, &xiph).unwrap();
+        assert_eq!(
+            frames.iter().map(|frame| frame.len()).collect::<Vec<_>>(),
+            vec![2, 256, 1]
+        );
+
+        // Fixed: two frames of 3 bytes
+        let frames = split_laced_frames(0x04, &[1, 1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(frames, vec![&[1, 2, 3][..], &[4, 5, 6][..]]);
+
+        // EBML: sizes 4, then 4 - 1 = 3, then the remaining 2 bytes
+        let mut ebml = vec![2, 0x84, 0xBE];
+        ebml.extend_from_slice(&[7; 9]);
+        let frames = split_laced_frames(0x06, &ebml).unwrap();
+        assert_eq!(
+            frames.iter().map(|frame| frame.len()).collect::<Vec<_>>(),
+            vec![4, 3, 2]
+        );
+
+        assert!(split_laced_frames(0x04, &[1, 1, 2, 3]).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::client::base::PlayabilityStatus;
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -23,9 +25,22 @@ pub enum YoutubeError {
     #[error("Video unavailable: {0}")]
     VideoUnavailable(String),
 
+    #[error(
+        "Video {video_id} is not playable: {status:?} ({})",
+        .reason.as_deref().unwrap_or("no reason given")
+    )]
+    Unplayable {
+        video_id: String,
+        status: PlayabilityStatus,
+        reason: Option<String>,
+    },
+
     #[error("Cipher error: {0}")]
     Cipher(String),
 
+    #[error("Cipher error: {0}")]
+    CipherFailed(#[from] crate::cipher::CipherError),
+
     #[error("JavaScript engine error: {0}")]
     JavaScriptEngine(#[from] crate::cipher::JavaScriptEngineError),
 
@@ -44,6 +59,9 @@ pub enum YoutubeError {
     #[error("HTTP error: {0}")]
     HttpError(String),
 
+    #[error("Proxy {proxy} failed: {reason}")]
+    ProxyFailed { proxy: String, reason: String },
+
     #[error("URL parse error: {0}")]
     UrlParse(#[from] url::ParseError),
 
@@ -52,6 +70,51 @@ pub enum YoutubeError {
 
     #[error("HTTP request error: {0}")]
     Http(#[from] reqwest::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    LoadFailed(LoadFailure),
+}
+
+impl YoutubeError {
+    /// How a load failing with this error should be reported to the user
+    pub fn severity(&self) -> FailureSeverity {
+        match self {
+            YoutubeError::Unplayable { .. }
+            | YoutubeError::VideoUnavailable(_)
+            | YoutubeError::CannotBeLoaded(_)
+            | YoutubeError::OptionDisabled(_) => FailureSeverity::Common,
+            YoutubeError::RateLimited(_)
+            | YoutubeError::NetworkError(_)
+            | YoutubeError::Io(_)
+            | YoutubeError::HttpError(_)
+            | YoutubeError::ProxyFailed { .. }
+            | YoutubeError::Http(_)
+            | YoutubeError::ApiError(_)
+            | YoutubeError::Auth(_)
+            | YoutubeError::AuthError(_) => FailureSeverity::Suspicious,
+            YoutubeError::LoadFailed(failure) => failure.severity(),
+            _ => FailureSeverity::Fault,
+        }
+    }
+
+    /// The reason YouTube gave for refusing playback, if any
+    pub fn playability_reason(&self) -> Option<&str> {
+        match self {
+            YoutubeError::Unplayable { reason, .. } => reason.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The proxy a request failed through, if a proxy was at fault
+    pub fn failed_proxy(&self) -> Option<&str> {
+        match self {
+            YoutubeError::ProxyFailed { proxy, .. } => Some(proxy),
+            _ => None,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, YoutubeError>;
@@ -63,3 +126,208 @@ pub enum AudioItem {
     SearchResult(crate::YoutubeSearchResult),
     NoMatches,
 }
+
+/// Severity of a load failure, mirroring Lavalink's exception severities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FailureSeverity {
+    /// The content itself cannot be played (removed, private, age restricted)
+    Common,
+    /// The failure is likely temporary or environmental (rate limits, network)
+    Suspicious,
+    /// Something broke on our side (parsing, cipher, configuration)
+    Fault,
+}
+
+impl FailureSeverity {
+    /// Lowercase name as used in Lavalink `LOAD_FAILED` payloads
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureSeverity::Common => "common",
+            FailureSeverity::Suspicious => "suspicious",
+            FailureSeverity::Fault => "fault",
+        }
+    }
+}
+
+/// A single client's failed attempt at loading an identifier
+#[derive(Debug)]
+pub struct LoadAttempt {
+    pub client: String,
+    pub error: YoutubeError,
+    pub playability_reason: Option<String>,
+    /// Proxy the attempt failed through, without credentials
+    pub proxy: Option<String>,
+}
+
+impl LoadAttempt {
+    pub fn new(client: impl Into<String>, error: YoutubeError) -> Self {
+        let playability_reason = error.playability_reason().map(str::to_string);
+        let proxy = error.failed_proxy().map(str::to_string);
+        Self {
+            client: client.into(),
+            error,
+            playability_reason,
+            proxy,
+        }
+    }
+
+    pub fn severity(&self) -> FailureSeverity {
+        self.error.severity()
+    }
+}
+
+/// Every failed attempt made while walking the client fallback chain
+#[derive(Debug)]
+pub struct LoadFailure {
+    pub identifier: String,
+    pub attempts: Vec<LoadAttempt>,
+}
+
+impl LoadFailure {
+    pub fn new(identifier: impl Into<String>) -> Self {
+        Self {
+            identifier: identifier.into(),
+            attempts: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, client: impl Into<String>, error: YoutubeError) {
+        self.attempts.push(LoadAttempt::new(client, error));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attempts.is_empty()
+    }
+
+    /// The least severe failure wins: if any client was told the content is
+    /// unplayable, that is the answer regardless of what the others hit
+    pub fn severity(&self) -> FailureSeverity {
+        self.primary_attempt()
+            .map(LoadAttempt::severity)
+            .unwrap_or(FailureSeverity::Fault)
+    }
+
+    /// User-facing cause, preferring YouTube's own playability reason
+    pub fn cause_message(&self) -> String {
+        match self.primary_attempt() {
+            Some(attempt) => attempt
+                .playability_reason
+                .clone()
+                .unwrap_or_else(|| attempt.error.to_string()),
+            None => format!("No client could load {}", self.identifier),
+        }
+    }
+
+    /// Whether every attempted client was rate limited
+    pub fn is_rate_limited(&self) -> bool {
+        !self.attempts.is_empty()
+            && self
+                .attempts
+                .iter()
+                .all(|attempt| matches!(attempt.error, YoutubeError::RateLimited(_)))
+    }
+
+    /// Proxies that attempts failed through, without duplicates
+    pub fn failed_proxies(&self) -> Vec<&str> {
+        let mut proxies = Vec::new();
+        for proxy in self.attempts.iter().filter_map(|a| a.proxy.as_deref()) {
+            if !proxies.contains(&proxy) {
+                proxies.push(proxy);
+            }
+        }
+        proxies
+    }
+
+    fn primary_attempt(&self) -> Option<&LoadAttempt> {
+        self.attempts
+            .iter()
+            .min_by_key(|attempt| attempt.severity())
+    }
+}
+
+impl fmt::Display for LoadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to load {} with {} client(s)",
+            self.identifier,
+            self.attempts.len()
+        )?;
+        for attempt in &self.attempts {
+            write!(f, "; {}: {}", attempt.client, attempt.error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unplayable(reason: &str) -> YoutubeError {
+        YoutubeError::Unplayable {
+            video_id: "dQw4w9WgXcQ".to_string(),
+            status: PlayabilityStatus::LoginRequired,
+            reason: Some(reason.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_load_failure_prefers_playability_reason() {
+        let mut failure = LoadFailure::new("dQw4w9WgXcQ");
+        failure.record("WEB", YoutubeError::RateLimited("429".to_string()));
+        failure.record("ANDROID", unplayable("Sign in to confirm your age"));
+        failure.record("MUSIC", YoutubeError::ParseError("bad".to_string()));
+
+        assert_eq!(failure.severity(), FailureSeverity::Common);
+        assert_eq!(failure.cause_message(), "Sign in to confirm your age");
+        assert_eq!(
+            failure.attempts[1].playability_reason.as_deref(),
+            Some("Sign in to confirm your age")
+        );
+        assert!(!failure.is_rate_limited());
+    }
+
+    #[test]
+    fn test_load_failure_rate_limited() {
+        let mut failure = LoadFailure::new("dQw4w9WgXcQ");
+        failure.record("WEB", YoutubeError::RateLimited("429".to_string()));
+        failure.record("ANDROID", YoutubeError::RateLimited("429".to_string()));
+
+        assert!(failure.is_rate_limited());
+        assert_eq!(failure.severity(), FailureSeverity::Suspicious);
+        assert_eq!(failure.severity().as_str(), "suspicious");
+
+        let message = YoutubeError::LoadFailed(failure).to_string();
+        assert!(message.starts_with("Failed to load dQw4w9WgXcQ with 2 client(s)"));
+        assert!(message.contains("ANDROID: Rate limited: 429"));
+    }
+
+    #[test]
+    fn test_load_failure_reports_failed_proxies() {
+        let proxy_failed = || YoutubeError::ProxyFailed {
+            proxy: "socks5://10.0.0.2:1080".to_string(),
+            reason: "connection refused".to_string(),
+        };
+        let mut failure = LoadFailure::new("dQw4w9WgXcQ");
+        failure.record("WEB", proxy_failed());
+        failure.record("IOS", proxy_failed());
+        failure.record("MUSIC", YoutubeError::ParseError("bad".to_string()));
+
+        assert_eq!(failure.failed_proxies(), vec!["socks5://10.0.0.2:1080"]);
+        assert_eq!(failure.severity(), FailureSeverity::Suspicious);
+        assert_eq!(
+            failure.cause_message(),
+            "Proxy socks5://10.0.0.2:1080 failed: connection refused"
+        );
+        assert_eq!(failure.attempts[2].proxy, None);
+    }
+
+    #[test]
+    fn test_unplayable_message() {
+        assert_eq!(
+            unplayable("Private video").to_string(),
+            "Video dQw4w9WgXcQ is not playable: LoginRequired (Private video)"
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use crate::error::YoutubeError;
+use cookie_store::{CookieStore, RawCookie};
+use reqwest::header::HeaderValue;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Cookie jar shared by every HTTP client sending YouTube requests
+///
+/// Account cookies, loaded from a Netscape `cookies.txt` or restored from a
+/// previous run, are kept until cleared. Cookies YouTube sets during a
+/// request sequence are kept in that sequence's [`CookieSequence`] and
+/// dropped when it ends, like the Java filter resetting the cookie store of
+/// its context; responses updating an account cookie update it in place.
+#[derive(Debug, Default)]
+pub struct YoutubeCookieJar {
+    account: RwLock<CookieStore>,
+    /// Sequence of requests sent outside a load
+    sequence: Arc<CookieSequence>,
+}
+
+/// Cookies YouTube set during one request sequence, e.g. the requests of a
+/// track load
+///
+/// Each load keeps its own, so concurrent loads neither see nor clear each
+/// other's cookies.
+#[derive(Debug, Default)]
+pub struct CookieSequence {
+    store: RwLock<CookieStore>,
+}
+
+impl CookieSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the cookies set during the sequence
+    pub fn clear(&self) {
+        self.store.write().unwrap().clear();
+    }
+}
+
+impl YoutubeCookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load account cookies from the contents of a Netscape `cookies.txt`,
+    /// returning how many were loaded
+    ///
+    /// Expired cookies are skipped.
+    pub fn load_netscape(&self, content: &str) -> Result<usize, YoutubeError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let invalid = |line: usize, reason: String| {
+            YoutubeError::ParseError(format!("Invalid cookies.txt line {line}: {reason}"))
+        };
+
+        let mut cookies = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            // curl and browser extensions keep HttpOnly cookies behind a
+            // comment-like prefix
+            let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+                Some(line) => (line, true),
+                None => (line, false),
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(cookie) =
+                netscape_cookie(line, http_only, now).map_err(|e| invalid(index + 1, e))?
+            {
+                cookies.push((index + 1, cookie));
+            }
+        }
+
+        let mut account = self.account.write().unwrap();
+        for (line, (set_cookie, url)) in &cookies {
+            account
+                .parse(set_cookie, url)
+                .map_err(|e| invalid(*line, e.to_string()))?;
+        }
+        Ok(cookies.len())
+    }
+
+    /// Load account cookies from a Netscape `cookies.txt` file
+    pub async fn load_netscape_file(&self, path: impl AsRef<Path>) -> Result<usize, YoutubeError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        self.load_netscape(&content)
+    }
+
+    /// Save the account cookies outliving the session to `path`, as JSON
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), YoutubeError> {
+        let path = path.as_ref();
+        let mut content = Vec::new();
+        cookie_store::serde::json::save(&self.account.read().unwrap(), &mut content)
+            .map_err(|e| YoutubeError::ParseError(format!("Failed to serialize cookies: {e}")))?;
+
+        let temp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        tokio::fs::write(&temp_path, content).await?;
+        tokio::fs::rename(&temp_path, path).await?;
+        Ok(())
+    }
+
+    /// Replace the account cookies with those saved by [`Self::save`],
+    /// returning how many were restored
+    pub async fn restore(&self, path: impl AsRef<Path>) -> Result<usize, YoutubeError> {
+        let content = tokio::fs::read(path).await?;
+        let store = cookie_store::serde::json::load(content.as_slice())
+            .map_err(|e| YoutubeError::ParseError(format!("Failed to restore cookies: {e}")))?;
+
+        let restored = store.iter_unexpired().count();
+        *self.account.write().unwrap() = store;
+        Ok(restored)
+    }
+
+    /// Save the account cookies to `path` each `interval`, until the jar is
+    /// dropped
+    pub fn spawn_persistence(self: &Arc<Self>, path: PathBuf, interval: Duration) {
+        let jar = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately, with nothing new to save
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(jar) = jar.upgrade() else {
+                    break;
+                };
+                if let Err(e) = jar.save(&path).await {
+                    log::warn!("Failed to persist cookies to {}: {e}", path.display());
+                }
+            }
+        });
+    }
+
+    /// The sequence of requests sent outside a load
+    pub fn shared_sequence(&self) -> &Arc<CookieSequence> {
+        &self.sequence
+    }
+
+    /// Drop the cookies set during the shared request sequence
+    pub fn clear_sequence(&self) {
+        self.sequence.clear();
+    }
+
+    /// Drop every cookie, including account cookies
+    pub fn clear(&self) {
+        self.account.write().unwrap().clear();
+        self.sequence.clear();
+    }
+
+    /// Names and values of the cookies sent with requests to `url` in the
+    /// shared sequence
+    pub fn cookies_for(&self, url: &Url) -> Vec<(String, String)> {
+        self.sequence_cookies_for(url, &self.sequence)
+    }
+
+    /// Names and values of the cookies sent with requests to `url` in
+    /// `sequence`, whose cookies override account cookies of the same name
+    pub fn sequence_cookies_for(
+        &self,
+        url: &Url,
+        sequence: &CookieSequence,
+    ) -> Vec<(String, String)> {
+        let mut cookies: Vec<(String, String)> = self
+            .account
+            .read()
+            .unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        for (name, value) in sequence.store.read().unwrap().get_request_values(url) {
+            match cookies.iter_mut().find(|(existing, _)| existing == name) {
+                Some(cookie) => cookie.1 = value.to_string(),
+                None => cookies.push((name.to_string(), value.to_string())),
+            }
+        }
+        cookies
+    }
+
+    /// `Cookie` header of requests to `url` in `sequence`
+    pub fn header_for(&self, url: &Url, sequence: &CookieSequence) -> Option<HeaderValue> {
+        cookie_header(self.sequence_cookies_for(url, sequence))
+    }
+
+    /// Keep the cookies a response from `url` set in `sequence`, except those
+    /// updating an account cookie, which the jar updates itself
+    pub fn set_sequence_cookies<'a>(
+        &self,
+        cookie_headers: impl IntoIterator<Item = &'a HeaderValue>,
+        url: &Url,
+        sequence: &CookieSequence,
+    ) {
+        let account = self.account.read().unwrap();
+        let mut store = sequence.store.write().unwrap();
+        for cookie in raw_cookies(cookie_headers) {
+            if updates_account(&account, &cookie, url) {
+                continue;
+            }
+            if let Err(e) = store.insert_raw(&cookie, url) {
+                log::debug!("Ignored cookie {} set by {url}: {e}", cookie.name());
+            }
+        }
+    }
+}
+
+/// Requests sent by the reqwest client itself carry account cookies, and
+/// responses update them; [`YoutubeHttpClient`](super::YoutubeHttpClient)
+/// adds the cookies of the request sequence.
+impl reqwest::cookie::CookieStore for YoutubeCookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let mut account = self.account.write().unwrap();
+        for cookie in raw_cookies(cookie_headers) {
+            if !updates_account(&account, &cookie, url) {
+                continue;
+            }
+            if let Err(e) = account.insert_raw(&cookie, url) {
+                log::debug!("Ignored cookie {} set by {url}: {e}", cookie.name());
+            }
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let account = self.account.read().unwrap();
+        cookie_header(
+            account
+                .get_request_values(url)
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        )
+    }
+}
+
+/// `Cookie` header sending `cookies`, if any
+fn cookie_header(cookies: impl IntoIterator<Item = (String, String)>) -> Option<HeaderValue> {
+    let header = cookies
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    if header.is_empty() {
+        return None;
+    }
+    HeaderValue::from_str(&header).ok()
+}
+
+/// The cookies of `Set-Cookie` values that parse
+fn raw_cookies<'a>(
+    cookie_headers: impl IntoIterator<Item = &'a HeaderValue>,
+) -> Vec<RawCookie<'static>> {
+    cookie_headers
+        .into_iter()
+        .filter_map(|header| {
+            std::str::from_utf8(header.as_bytes())
+                .ok()
+                .and_then(|value| RawCookie::parse(value.to_string()).ok())
+        })
+        .collect()
+}
+
+/// Whether `cookie`, set by `url`, replaces an account cookie
+fn updates_account(account: &CookieStore, cookie: &RawCookie, url: &Url) -> bool {
+    account
+        .matches(url)
+        .iter()
+        .any(|existing| existing.name() == cookie.name())
+}
+
+/// The `Set-Cookie` value of one `cookies.txt` line and the URL it is set
+/// from, or `None` if the cookie expired
+fn netscape_cookie(line: &str, http_only: bool, now: u64) -> Result<Option<(String, Url)>, String> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let [domain, include_subdomains, path, secure, expires, name, value] = fields[..] else {
+        return Err(format!("expected 7 fields, found {}", fields.len()));
+    };
+
+    let host = domain.trim_start_matches('.');
+    if host.is_empty() {
+        return Err("no domain".to_string());
+    }
+    let expires: u64 = expires
+        .parse()
+        .map_err(|_| format!("invalid expiry {expires}"))?;
+    if expires != 0 && expires <= now {
+        return Ok(None);
+    }
+    let secure = secure.eq_ignore_ascii_case("TRUE");
+
+    let mut set_cookie = format!("{name}={value}; Path={path}");
+    if include_subdomains.eq_ignore_ascii_case("TRUE") {
+        set_cookie.push_str(&format!("; Domain={host}"));
+    }
+    // Zero marks session cookies
+    if expires != 0 {
+        set_cookie.push_str(&format!("; Max-Age={}", expires - now));
+    }
+    if secure {
+        set_cookie.push_str("; Secure");
+    }
+    if http_only {
+        set_cookie.push_str("; HttpOnly");
+    }
+
+    let scheme = if secure { "https" } else { "http" };
+    let url = Url::parse(&format!("{scheme}://{host}{path}")).map_err(|e| e.to_string())?;
+    Ok(Some((set_cookie, url)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore as _;
+
+    const COOKIES_TXT: &str = "# Netscape HTTP Cookie File\n\
+        \n\
+        .youtube.com\tTRUE\t/\tTRUE\t4102444800\tSID\taccount-sid\n\
+        #HttpOnly_.youtube.com\tTRUE\t/\tTRUE\t4102444800\t__Secure-3PSID\tsecure-sid\n\
+        www.youtube.com\tFALSE\t/\tFALSE\t0\tPREF\tf6=40000000\n\
+        .youtube.com\tTRUE\t/\tTRUE\t1000\tEXPIRED\tgone\n";
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    fn cookie_names(jar: &YoutubeCookieJar, url: &str) -> Vec<String> {
+        let mut names: Vec<_> = jar
+            .cookies_for(&self::url(url))
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_load_netscape() {
+        let jar = YoutubeCookieJar::new();
+        assert_eq!(jar.load_netscape(COOKIES_TXT).unwrap(), 3);
+
+        assert_eq!(
+            cookie_names(&jar, "https://www.youtube.com/youtubei/v1/player"),
+            ["PREF", "SID", "__Secure-3PSID"]
+        );
+        // Host-only and secure cookies stay on their host and scheme
+        assert_eq!(
+            cookie_names(&jar, "https://music.youtube.com/"),
+            ["SID", "__Secure-3PSID"]
+        );
+        assert_eq!(cookie_names(&jar, "http://www.youtube.com/"), ["PREF"]);
+
+        assert!(matches!(
+            jar.load_netscape(".youtube.com\tTRUE\t/\tTRUE\n"),
+            Err(YoutubeError::ParseError(message)) if message.contains("line 1")
+        ));
+    }
+
+    #[test]
+    fn test_clear_sequence_keeps_account_cookies() {
+        let jar = YoutubeCookieJar::new();
+        jar.load_netscape(COOKIES_TXT).unwrap();
+        let player = url("https://www.youtube.com/youtubei/v1/player");
+
+        let set_cookies = [
+            HeaderValue::from_static("YSC=sequence; Path=/; Domain=youtube.com"),
+            HeaderValue::from_static("SID=rotated; Path=/; Domain=youtube.com; Secure"),
+        ];
+        jar.set_cookies(&mut set_cookies.iter(), &player);
+        jar.set_sequence_cookies(&set_cookies, &player, jar.shared_sequence());
+        let header = jar.header_for(&player, jar.shared_sequence()).unwrap();
+        let header = header.to_str().unwrap();
+        assert!(header.contains("SID=rotated") && header.contains("YSC=sequence"));
+        // The reqwest client itself only sends account cookies
+        let header = jar.cookies(&player).unwrap();
+        assert!(!header.to_str().unwrap().contains("YSC"));
+
+        jar.clear_sequence();
+        assert_eq!(
+            cookie_names(&jar, player.as_str()),
+            ["PREF", "SID", "__Secure-3PSID"]
+        );
+        assert!(jar
+            .cookies_for(&player)
+            .contains(&("SID".to_string(), "rotated".to_string())));
+
+        jar.clear();
+        assert!(jar.cookies(&player).is_none());
+    }
+
+    #[test]
+    fn test_sequences_keep_their_own_cookies() {
+        let jar = YoutubeCookieJar::new();
+        jar.load_netscape(COOKIES_TXT).unwrap();
+        let player = url("https://www.youtube.com/youtubei/v1/player");
+        let (first, second) = (CookieSequence::new(), CookieSequence::new());
+
+        let set_cookie = |value: &'static str, sequence: &CookieSequence| {
+            jar.set_sequence_cookies(&[HeaderValue::from_static(value)], &player, sequence);
+        };
+        set_cookie("YSC=first; Path=/", &first);
+        set_cookie("YSC=second; Path=/", &second);
+        second.clear();
+
+        let ysc = |sequence: &CookieSequence| {
+            jar.sequence_cookies_for(&player, sequence)
+                .into_iter()
+                .find(|(name, _)| name == "YSC")
+                .map(|(_, value)| value)
+        };
+        assert_eq!(ysc(&first).as_deref(), Some("first"));
+        assert_eq!(ysc(&second), None);
+        assert_eq!(ysc(jar.shared_sequence()), None);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_restore() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("cookies.json");
+
+        let jar = YoutubeCookieJar::new();
+        jar.load_netscape(COOKIES_TXT).unwrap();
+        jar.save(&path).await.unwrap();
+
+        let restored = YoutubeCookieJar::new();
+        // Session cookies end with the run that saved them
+        assert_eq!(restored.restore(&path).await.unwrap(), 2);
+        assert_eq!(
+            cookie_names(&restored, "https://www.youtube.com/"),
+            ["SID", "__Secure-3PSID"]
+        );
+    }
+}
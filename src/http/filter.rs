@@ -1,8 +1,14 @@
 // use std::collections::HashMap; // Currently unused
+use super::retry::retry_after;
+use super::{
+    CookieSequence, InnertubeEndpoint, ProxyPool, ProxySelection, RateLimiter, RetryDecision,
+    RetryPolicy, RoutePlanner, YoutubeCookieJar,
+};
 use crate::error::YoutubeError;
-use cookie_store::CookieStore;
-use reqwest::{Client, Request, Response};
-use std::sync::Arc;
+use reqwest::{Client, Proxy, Request, Response};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
@@ -18,9 +24,27 @@ pub struct RequestContext {
     pub is_search_request: bool,
     pub is_browse_request: bool,
     pub is_next_request: bool,
+    /// Address the request was sent from, when a route planner picked one
+    pub local_address: Option<IpAddr>,
+    /// Identifier of the client sending the request, e.g. `MUSIC`, set by
+    /// the HTTP client
+    pub client_identifier: Option<String>,
+    /// Request sequence whose cookies the request sends and keeps, set by
+    /// the HTTP client; the jar's shared sequence when `None`
+    pub cookie_sequence: Option<Arc<CookieSequence>>,
 }
 
-/// Rate limiting state tracking
+impl RequestContext {
+    /// Key rate limits and backoff of the sending client are tracked under
+    pub fn client_key(&self) -> &str {
+        self.client_identifier
+            .as_deref()
+            .or(self.client_name.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// Rate limiting state tracking, per client
 #[derive(Debug)]
 struct RateLimitState {
     last_429_time: Option<Instant>,
@@ -47,22 +71,51 @@ impl Default for RateLimitState {
 /// - Rate limiting detection (429 status code handling)
 /// - Connection reset retry logic
 /// - Request context attribute management
-#[derive(Debug)]
+///
+/// Clones share their state.
+#[derive(Debug, Clone)]
 pub struct YoutubeHttpContextFilter {
-    cookie_store: Arc<RwLock<CookieStore>>,
-    rate_limit_state: Arc<RwLock<RateLimitState>>,
+    cookie_jar: Arc<YoutubeCookieJar>,
+    rate_limit_state: Arc<RwLock<HashMap<String, RateLimitState>>>,
     visitor_id_tracker: Arc<RwLock<Option<String>>>,
+    route_planner: Option<Arc<RoutePlanner>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl YoutubeHttpContextFilter {
     pub fn new() -> Self {
         Self {
-            cookie_store: Arc::new(RwLock::new(CookieStore::default())),
-            rate_limit_state: Arc::new(RwLock::new(RateLimitState::default())),
+            cookie_jar: Arc::new(YoutubeCookieJar::new()),
+            rate_limit_state: Arc::new(RwLock::new(HashMap::new())),
             visitor_id_tracker: Arc::new(RwLock::new(None)),
+            route_planner: None,
+            rate_limiter: None,
         }
     }
 
+    /// Hold requests back until `rate_limiter` lets them through
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Keep cookies in `cookie_jar`, e.g. one holding account cookies
+    pub fn with_cookie_jar(mut self, cookie_jar: Arc<YoutubeCookieJar>) -> Self {
+        self.cookie_jar = cookie_jar;
+        self
+    }
+
+    /// The jar requests take their cookies from
+    pub fn cookie_jar(&self) -> &Arc<YoutubeCookieJar> {
+        &self.cookie_jar
+    }
+
+    /// Mark the address of rate limited requests as failing in `route_planner`
+    pub fn with_route_planner(mut self, route_planner: Arc<RoutePlanner>) -> Self {
+        self.route_planner = Some(route_planner);
+        self
+    }
+
     /// Apply request filtering - inject headers and manage context
     pub async fn apply_request_filter(
         &self,
@@ -116,6 +169,16 @@ impl YoutubeHttpContextFilter {
             );
         }
 
+        // Send the account cookies and those of the request sequence
+        if let Some(cookies) = self
+            .cookie_jar
+            .header_for(request.url(), self.cookie_sequence(context))
+        {
+            request
+                .headers_mut()
+                .insert(reqwest::header::COOKIE, cookies);
+        }
+
         // Apply standard YouTube API headers
         self.apply_standard_headers(&mut request)?;
 
@@ -130,53 +193,81 @@ impl YoutubeHttpContextFilter {
     ) -> Result<Response, YoutubeError> {
         let status = response.status();
 
+        self.cookie_jar.set_sequence_cookies(
+            response.headers().get_all(reqwest::header::SET_COOKIE),
+            response.url(),
+            self.cookie_sequence(context),
+        );
+
         // Handle rate limiting (429 status)
         if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-            self.handle_rate_limit().await?;
+            self.handle_rate_limit(context, retry_after(response.headers()))
+                .await?;
             return Err(YoutubeError::RateLimited(
                 "YouTube API rate limit exceeded".to_string(),
             ));
         }
 
-        // Reset rate limit state on successful request
+        // Reset the client's rate limit state on successful request
         if status.is_success() {
-            let mut rate_limit = self.rate_limit_state.write().await;
-            rate_limit.consecutive_429s = 0;
-            rate_limit.backoff_duration = Duration::from_secs(1);
+            self.rate_limit_state
+                .write()
+                .await
+                .remove(context.client_key());
         }
 
-        // Clear cookies after request sequence (mimics Java behavior)
+        // A player request ends the request sequence of a track load
         if context.is_player_request {
-            self.clear_cookies().await;
+            self.clear_cookies(context);
         }
 
         Ok(response)
     }
 
-    /// Handle rate limiting with exponential backoff
-    async fn handle_rate_limit(&self) -> Result<(), YoutubeError> {
-        let mut rate_limit = self.rate_limit_state.write().await;
+    /// Handle rate limiting with exponential backoff of the sending client,
+    /// backing off at least as long as YouTube's `Retry-After` asks
+    pub(crate) async fn handle_rate_limit(
+        &self,
+        context: &RequestContext,
+        retry_after: Option<Duration>,
+    ) -> Result<(), YoutubeError> {
+        if let (Some(planner), Some(address)) = (&self.route_planner, context.local_address) {
+            planner.mark_failing(address);
+        }
+
+        let client = context.client_key();
+        let mut states = self.rate_limit_state.write().await;
+        let rate_limit = states.entry(client.to_string()).or_default();
 
         rate_limit.last_429_time = Some(Instant::now());
         rate_limit.consecutive_429s += 1;
 
         // Exponential backoff: 1s, 2s, 4s, 8s, max 60s
         let backoff_secs = std::cmp::min(1u64 << (rate_limit.consecutive_429s - 1), 60);
-        rate_limit.backoff_duration = Duration::from_secs(backoff_secs);
+        rate_limit.backoff_duration =
+            Duration::from_secs(backoff_secs).max(retry_after.unwrap_or_default());
 
         log::warn!(
-            "Rate limited by YouTube API. Backing off for {} seconds (attempt {})",
-            backoff_secs,
+            "Client {client} rate limited by YouTube API. Backing off for {} seconds (attempt {})",
+            rate_limit.backoff_duration.as_secs(),
             rate_limit.consecutive_429s
         );
 
         Ok(())
     }
 
-    /// Clear cookies (mimics Java BasicCookieStore clearing)
-    async fn clear_cookies(&self) {
-        let mut store = self.cookie_store.write().await;
-        *store = CookieStore::default();
+    /// The request sequence `context` belongs to
+    fn cookie_sequence<'a>(&'a self, context: &'a RequestContext) -> &'a CookieSequence {
+        context
+            .cookie_sequence
+            .as_deref()
+            .unwrap_or(self.cookie_jar.shared_sequence())
+    }
+
+    /// Clear the cookies set during the request sequence of `context`,
+    /// keeping account cookies (mimics Java BasicCookieStore clearing)
+    fn clear_cookies(&self, context: &RequestContext) {
+        self.cookie_sequence(context).clear();
         log::debug!("Cleared cookie store after request sequence");
     }
 
@@ -257,9 +348,11 @@ impl YoutubeHttpContextFilter {
         }
     }
 
-    /// Check if we should wait due to rate limiting
-    pub async fn should_wait_for_rate_limit(&self) -> Option<Duration> {
-        let rate_limit = self.rate_limit_state.read().await;
+    /// Check if the client identified by `client` should wait due to rate
+    /// limiting
+    pub async fn should_wait_for_rate_limit(&self, client: &str) -> Option<Duration> {
+        let states = self.rate_limit_state.read().await;
+        let rate_limit = states.get(client)?;
 
         if let Some(last_429) = rate_limit.last_429_time {
             let elapsed = last_429.elapsed();
@@ -271,6 +364,25 @@ impl YoutubeHttpContextFilter {
         None
     }
 
+    /// Wait out the sending client's backoff, then until the rate limiter
+    /// lets the request through
+    pub async fn wait_before_request(&self, context: &RequestContext) {
+        let client = context.client_key();
+        if let Some(wait_duration) = self.should_wait_for_rate_limit(client).await {
+            log::info!(
+                "Waiting {} seconds due to rate limiting of client {client}",
+                wait_duration.as_secs()
+            );
+            tokio::time::sleep(wait_duration).await;
+        }
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter
+                .acquire(client, InnertubeEndpoint::from_context(context))
+                .await;
+        }
+    }
+
     /// Set visitor ID for future requests
     pub async fn set_visitor_id(&self, visitor_id: String) {
         let mut tracker = self.visitor_id_tracker.write().await;
@@ -291,100 +403,409 @@ impl Default for YoutubeHttpContextFilter {
     }
 }
 
+/// Clients bound to a local address or proxy kept around for reuse; nano
+/// switching uses a new address for nearly every request, so the cache is
+/// bounded
+const MAX_ROUTED_CLIENTS: usize = 64;
+
+/// Builder with the settings shared by every client sending YouTube requests
+pub(crate) fn client_builder() -> reqwest::ClientBuilder {
+    Client::builder().timeout(Duration::from_secs(30))
+}
+
+/// Proxy pools requests are sent through
+#[derive(Debug, Clone, Default)]
+struct ProxyPools {
+    default: Option<Arc<ProxyPool>>,
+    clients: HashMap<String, Arc<ProxyPool>>,
+}
+
+/// Where a routed client sends its requests from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Route {
+    Address(IpAddr),
+    Proxy { pool: String, proxy: String },
+}
+
+/// Where a request is sent from
+///
+/// Stream URLs are only valid from the address that requested them, so the
+/// route a player request took is kept to stream through.
+#[derive(Debug, Clone, Default)]
+pub enum RequestRoute {
+    /// The default client, from the machine's own address
+    #[default]
+    Direct,
+    /// An address picked by the route planner
+    Address(IpAddr),
+    /// A proxy picked from `pool`
+    Proxy {
+        pool: Arc<ProxyPool>,
+        selection: Box<ProxySelection>,
+    },
+}
+
+impl RequestRoute {
+    /// The proxy the route goes through, without credentials
+    pub fn proxy(&self) -> Option<&str> {
+        match self {
+            RequestRoute::Proxy { selection, .. } => Some(selection.proxy()),
+            _ => None,
+        }
+    }
+}
+
 /// HTTP Client wrapper with YouTube-specific filtering
+///
+/// Every underlying client, whether bound to an address or a proxy, keeps
+/// its cookies in the filter's cookie jar.
 #[derive(Debug, Clone)]
 pub struct YoutubeHttpClient {
     client: Client,
     filter: Arc<YoutubeHttpContextFilter>,
+    route_planner: Option<Arc<RoutePlanner>>,
+    routed_clients: Arc<Mutex<HashMap<Route, Client>>>,
+    proxy_pools: Arc<ProxyPools>,
+    retry_policy: Arc<RetryPolicy>,
+    /// Identifier of the client this copy sends requests for
+    client_identifier: Option<String>,
+    /// Route every request of this copy takes, instead of picking one per
+    /// request
+    route: Option<RequestRoute>,
+    /// Request sequence every request of this copy belongs to
+    cookie_sequence: Option<Arc<CookieSequence>>,
 }
 
 impl YoutubeHttpClient {
     pub fn new() -> Result<Self, YoutubeError> {
-        let client = Client::builder()
-            .cookie_store(true)
-            .timeout(Duration::from_secs(30))
-            .build()
-            .map_err(|e| YoutubeError::HttpError(format!("Failed to create HTTP client: {e}")))?;
-
+        let filter = YoutubeHttpContextFilter::new();
         Ok(Self {
-            client,
-            filter: Arc::new(YoutubeHttpContextFilter::new()),
+            client: Self::build_client(filter.cookie_jar(), None, None)?,
+            filter: Arc::new(filter),
+            route_planner: None,
+            routed_clients: Arc::new(Mutex::new(HashMap::new())),
+            proxy_pools: Arc::new(ProxyPools::default()),
+            retry_policy: Arc::new(RetryPolicy::default()),
+            client_identifier: None,
+            route: None,
+            cookie_sequence: None,
         })
     }
 
+    /// Retry failed requests as `retry_policy` allows
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Arc::new(retry_policy);
+        self
+    }
+
+    /// The policy failed requests are retried by
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Keep cookies in `cookie_jar`
+    ///
+    /// Like [`Self::with_route_planner`], call this before the client is
+    /// shared.
+    pub fn with_cookie_jar(
+        mut self,
+        cookie_jar: Arc<YoutubeCookieJar>,
+    ) -> Result<Self, YoutubeError> {
+        self.client = Self::build_client(&cookie_jar, None, None)?;
+        self.filter = Arc::new((*self.filter).clone().with_cookie_jar(cookie_jar));
+        self.routed_clients = Arc::new(Mutex::new(HashMap::new()));
+        Ok(self)
+    }
+
+    /// The jar every request takes its cookies from
+    pub fn cookie_jar(&self) -> &Arc<YoutubeCookieJar> {
+        self.filter.cookie_jar()
+    }
+
+    /// Send requests through `pool`, unless their client has its own pool
+    pub fn with_proxy_pool(mut self, pool: Arc<ProxyPool>) -> Self {
+        Arc::make_mut(&mut self.proxy_pools).default = Some(pool);
+        self
+    }
+
+    /// Send the requests of the client identified by `client` through `pool`
+    pub fn with_client_proxy_pool(
+        mut self,
+        client: impl Into<String>,
+        pool: Arc<ProxyPool>,
+    ) -> Self {
+        Arc::make_mut(&mut self.proxy_pools)
+            .clients
+            .insert(client.into(), pool);
+        self
+    }
+
+    /// Copy sending requests for the client identified by `identifier`,
+    /// through that client's proxy pool
+    pub fn for_client(&self, identifier: &str) -> Self {
+        Self {
+            client_identifier: Some(identifier.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// The proxy pool requests of this copy go through, if any
+    pub fn proxy_pool(&self) -> Option<&Arc<ProxyPool>> {
+        self.client_identifier
+            .as_ref()
+            .and_then(|identifier| self.proxy_pools.clients.get(identifier))
+            .or(self.proxy_pools.default.as_ref())
+    }
+
+    /// Copy sending every request through `route`
+    pub fn with_route(&self, route: RequestRoute) -> Self {
+        Self {
+            route: Some(route),
+            ..self.clone()
+        }
+    }
+
+    /// Copy whose requests belong to `cookie_sequence`, e.g. the one of a
+    /// track load, rather than the jar's shared sequence
+    pub fn with_cookie_sequence(&self, cookie_sequence: Arc<CookieSequence>) -> Self {
+        Self {
+            cookie_sequence: Some(cookie_sequence),
+            ..self.clone()
+        }
+    }
+
+    /// The route requests of this copy take: the one set with
+    /// [`Self::with_route`], or else a proxy of this copy's pool for `key`,
+    /// or else an address of the route planner
+    pub fn select_route(&self, key: &str) -> Result<RequestRoute, YoutubeError> {
+        if let Some(route) = &self.route {
+            return Ok(route.clone());
+        }
+        if let Some(pool) = self.proxy_pool() {
+            return Ok(RequestRoute::Proxy {
+                pool: pool.clone(),
+                selection: Box::new(pool.select(key)),
+            });
+        }
+        match &self.route_planner {
+            Some(planner) => Ok(RequestRoute::Address(planner.next_address()?)),
+            None => Ok(RequestRoute::Direct),
+        }
+    }
+
+    /// Client to stream playback URLs with
+    ///
+    /// Goes the way of the route set with [`Self::with_route`], which should
+    /// be the one the player request took, since stream URLs are only valid
+    /// from the address that requested them.
+    pub fn playback_client(&self) -> Result<Client, YoutubeError> {
+        let key = self.client_identifier.as_deref().unwrap_or_default();
+        self.route_client(&self.select_route(key)?)
+    }
+
+    /// The client sending through `route`
+    fn route_client(&self, route: &RequestRoute) -> Result<Client, YoutubeError> {
+        match route {
+            RequestRoute::Direct => Ok(self.client.clone()),
+            RequestRoute::Address(address) => self.bound_client(*address),
+            RequestRoute::Proxy { pool, selection } => self.proxied_client(pool, selection),
+        }
+    }
+
+    /// Send every filtered request from an address picked by `route_planner`
+    ///
+    /// Replaces the filter, so call this before the client is shared.
+    pub fn with_route_planner(mut self, route_planner: Arc<RoutePlanner>) -> Self {
+        self.filter = Arc::new(
+            (*self.filter)
+                .clone()
+                .with_route_planner(route_planner.clone()),
+        );
+        self.route_planner = Some(route_planner);
+        self
+    }
+
+    /// Hold requests back until `rate_limiter` lets them through
+    ///
+    /// Like [`Self::with_route_planner`], call this before the client is
+    /// shared.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.filter = Arc::new((*self.filter).clone().with_rate_limiter(rate_limiter));
+        self
+    }
+
+    /// The route planner picking source addresses, if any
+    pub fn route_planner(&self) -> Option<&Arc<RoutePlanner>> {
+        self.route_planner.as_ref()
+    }
+
+    fn build_client(
+        cookie_jar: &Arc<YoutubeCookieJar>,
+        local_address: Option<IpAddr>,
+        proxy: Option<Proxy>,
+    ) -> Result<Client, YoutubeError> {
+        let mut builder = client_builder()
+            .cookie_provider(cookie_jar.clone())
+            .local_address(local_address);
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy);
+        }
+        builder
+            .build()
+            .map_err(|e| YoutubeError::HttpError(format!("Failed to create HTTP client: {e}")))
+    }
+
+    /// A client sending through the proxy picked in `selection`
+    fn proxied_client(
+        &self,
+        pool: &ProxyPool,
+        selection: &ProxySelection,
+    ) -> Result<Client, YoutubeError> {
+        let route = Route::Proxy {
+            pool: pool.name().to_string(),
+            proxy: selection.proxy().to_string(),
+        };
+        self.routed_client(route, None, Some(selection.reqwest_proxy().clone()))
+    }
+
+    /// A client sending from `local_address`
+    fn bound_client(&self, local_address: IpAddr) -> Result<Client, YoutubeError> {
+        self.routed_client(Route::Address(local_address), Some(local_address), None)
+    }
+
+    fn routed_client(
+        &self,
+        route: Route,
+        local_address: Option<IpAddr>,
+        proxy: Option<Proxy>,
+    ) -> Result<Client, YoutubeError> {
+        let mut clients = self.routed_clients.lock().unwrap();
+        if let Some(client) = clients.get(&route) {
+            return Ok(client.clone());
+        }
+
+        if clients.len() >= MAX_ROUTED_CLIENTS {
+            clients.clear();
+        }
+        let client = Self::build_client(self.filter.cookie_jar(), local_address, proxy)?;
+        clients.insert(route, client.clone());
+        Ok(client)
+    }
+
     /// Execute request with YouTube-specific filtering
     pub async fn execute_with_context(
         &self,
         request: Request,
-        context: RequestContext,
+        mut context: RequestContext,
     ) -> Result<Response, YoutubeError> {
-        // Check rate limiting
-        if let Some(wait_duration) = self.filter.should_wait_for_rate_limit().await {
-            log::info!(
-                "Waiting {} seconds due to rate limiting",
-                wait_duration.as_secs()
-            );
-            tokio::time::sleep(wait_duration).await;
+        if context.client_identifier.is_none() {
+            context.client_identifier = self.client_identifier.clone();
         }
+        if context.cookie_sequence.is_none() {
+            context.cookie_sequence = self.cookie_sequence.clone();
+        }
+
+        // Check rate limiting
+        self.filter.wait_before_request(&context).await;
 
         // Apply request filtering
         let filtered_request = self.filter.apply_request_filter(request, &context).await?;
 
+        // Pick the proxy, or else the source address if routing between
+        // addresses
+        let route = self.select_route(context.client_key())?;
+        if let RequestRoute::Address(address) = route {
+            context.local_address = Some(address);
+        }
+        let client = self.route_client(&route)?;
+
         // Execute request with retry logic
-        let response = self.execute_with_retry(filtered_request).await?;
+        let result = self.execute_with_retry(&client, filtered_request).await;
+        let response = match &route {
+            RequestRoute::Proxy { pool, selection } => {
+                Self::check_proxy_result(pool, selection, result)?
+            }
+            _ => result?,
+        };
 
         // Apply response filtering
         self.filter.apply_response_filter(response, &context).await
     }
 
-    /// Execute request with connection reset retry logic
-    async fn execute_with_retry(&self, request: Request) -> Result<Response, YoutubeError> {
-        const MAX_RETRIES: u32 = 3;
-        let mut last_error = None;
-        let mut current_request = Some(request);
-
-        for attempt in 0..MAX_RETRIES {
-            // Get the request for this attempt
-            let req = if attempt == 0 {
-                // First attempt: use the original request
-                current_request.take().unwrap()
-            } else {
-                // Subsequent attempts: try to clone the original request
-                match current_request.as_ref().and_then(|r| r.try_clone()) {
-                    Some(cloned) => cloned,
-                    None => {
-                        // If we can't clone, we can't retry
-                        log::warn!("Cannot clone request for retry, stopping retries");
-                        break;
-                    }
-                }
+    /// Record the outcome of a request sent through `selection`, blaming the
+    /// proxy for requests that never reached YouTube
+    fn check_proxy_result(
+        pool: &ProxyPool,
+        selection: &ProxySelection,
+        result: Result<Response, YoutubeError>,
+    ) -> Result<Response, YoutubeError> {
+        let reason = match result {
+            Ok(response)
+                if response.status() == reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED =>
+            {
+                "proxy authentication required".to_string()
+            }
+            Ok(response) => {
+                pool.report_success(selection);
+                return Ok(response);
+            }
+            Err(e) => e.to_string(),
+        };
+
+        pool.report_failure(selection);
+        Err(YoutubeError::ProxyFailed {
+            proxy: selection.proxy().to_string(),
+            reason,
+        })
+    }
+
+    /// Execute request, retrying transient failures as the retry policy
+    /// allows
+    async fn execute_with_retry(
+        &self,
+        client: &Client,
+        mut request: Request,
+    ) -> Result<Response, YoutubeError> {
+        let mut attempt = 1;
+        loop {
+            // Requests with streaming bodies cannot be cloned, so are sent once
+            let next = request.try_clone();
+            let url = request.url().clone();
+            let result = client.execute(request).await;
+            let decision = match &result {
+                Ok(response) => self.retry_policy.on_status(
+                    response.status(),
+                    response.headers(),
+                    attempt,
+                    false,
+                ),
+                Err(e) => self.retry_policy.on_error(e, attempt),
+            };
+            let reason = || match &result {
+                Ok(response) => format!("answered {}", response.status()),
+                Err(e) => format!("failed: {e}"),
             };
 
-            match self.client.execute(req).await {
-                Ok(response) => return Ok(response),
-                Err(e) => {
-                    last_error = Some(e);
-
-                    // Only retry on connection errors and if we have more attempts
-                    if attempt < MAX_RETRIES - 1 {
-                        let wait_time = Duration::from_millis(100 * (1 << attempt)); // 100ms, 200ms, 400ms
-                        log::warn!(
-                            "Request failed (attempt {}), retrying in {:?}: {}",
-                            attempt + 1,
-                            wait_time,
-                            last_error.as_ref().unwrap()
-                        );
-                        tokio::time::sleep(wait_time).await;
-                    }
+            let (RetryDecision::Retry(delay), Some(next)) = (decision, next) else {
+                if matches!(decision, RetryDecision::Retry(_)) {
+                    log::warn!(
+                        "Request to {url} {} (attempt {attempt}), not retrying as its body cannot be replayed",
+                        reason()
+                    );
                 }
-            }
-        }
+                return result.map_err(|e| {
+                    YoutubeError::HttpError(format!("Request failed after {attempt} attempts: {e}"))
+                });
+            };
 
-        Err(YoutubeError::HttpError(format!(
-            "Request failed after {} attempts: {}",
-            MAX_RETRIES,
-            last_error.unwrap()
-        )))
+            log::warn!(
+                "Request to {url} {} (attempt {attempt}), retrying in {delay:?}",
+                reason()
+            );
+            tokio::time::sleep(delay).await;
+            request = next;
+            attempt += 1;
+        }
     }
 
     /// Get the underlying HTTP client
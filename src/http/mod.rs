@@ -1,12 +1,22 @@
 pub mod auth;
 pub mod client;
+pub mod cookies;
 pub mod filter;
 pub mod oauth;
+pub mod proxy;
+pub mod rate_limit;
+pub mod retry;
+pub mod route_planner;
 
 pub use auth::{
     AccessToken as LegacyAccessToken, YoutubeAccessTokenTracker as LegacyTokenTracker,
     YoutubeOauth2Handler as LegacyOauth2Handler,
 };
 pub use client::HttpClient;
-pub use filter::{RequestContext, YoutubeHttpClient, YoutubeHttpContextFilter};
+pub use cookies::{CookieSequence, YoutubeCookieJar};
+pub use filter::{RequestContext, RequestRoute, YoutubeHttpClient, YoutubeHttpContextFilter};
 pub use oauth::{AccessToken, YoutubeAccessTokenTracker, YoutubeOauth2Handler};
+pub use proxy::{ProxyAssignment, ProxyPool, ProxySelection, ProxyStatus};
+pub use rate_limit::{InnertubeEndpoint, RateLimit, RateLimiter};
+pub use retry::{RetryDecision, RetryPolicy};
+pub use route_planner::{FailingAddress, RoutePlanner, RoutePlannerStatus, RoutePlannerStrategy};
@@ -0,0 +1,360 @@
+use super::filter::client_builder;
+use crate::error::YoutubeError;
+use crate::plugin::{ProxyConfig, ProxyPoolConfig};
+use reqwest::{Client, Proxy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+/// Consecutive failures after which a proxy is considered unhealthy
+pub const DEFAULT_MAX_FAILURES: u32 = 3;
+
+/// How requests are spread over the proxies of a pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProxyAssignment {
+    /// Each request goes through the next healthy proxy
+    #[default]
+    RoundRobin,
+    /// Each client keeps its proxy until it becomes unhealthy, so stream
+    /// URLs are played from the address that requested them
+    Sticky,
+}
+
+/// Health of one proxy of a pool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProxyStatus {
+    pub proxy: String,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug)]
+struct PooledProxy {
+    /// Proxy URL without credentials, safe to log and report
+    label: String,
+    proxy: Proxy,
+    /// Client for health checks, without cookies
+    client: Client,
+    healthy: AtomicBool,
+    consecutive_failures: AtomicU32,
+}
+
+/// The proxy picked for a request
+#[derive(Debug, Clone)]
+pub struct ProxySelection {
+    index: usize,
+    proxy: String,
+    route: Proxy,
+}
+
+impl ProxySelection {
+    /// Proxy URL without credentials
+    pub fn proxy(&self) -> &str {
+        &self.proxy
+    }
+
+    /// The proxy to build clients sending through it with
+    pub(crate) fn reqwest_proxy(&self) -> &Proxy {
+        &self.route
+    }
+}
+
+/// A set of HTTP and SOCKS5 proxies requests are spread over
+///
+/// Proxies failing [`DEFAULT_MAX_FAILURES`] times in a row are skipped until
+/// a request or health check through them succeeds again. When every proxy
+/// is unhealthy, they are all used again rather than failing every request.
+#[derive(Debug)]
+pub struct ProxyPool {
+    name: String,
+    proxies: Vec<PooledProxy>,
+    assignment: ProxyAssignment,
+    max_failures: u32,
+    next: AtomicUsize,
+    sticky: Mutex<HashMap<String, usize>>,
+}
+
+impl ProxyPool {
+    /// Create a pool named `name`, e.g. for error messages
+    pub fn new(
+        name: impl Into<String>,
+        proxies: &[ProxyConfig],
+        assignment: ProxyAssignment,
+    ) -> Result<Self, YoutubeError> {
+        let name = name.into();
+        if proxies.is_empty() {
+            return Err(YoutubeError::ConfigurationError(format!(
+                "Proxy pool {name} has no proxies"
+            )));
+        }
+
+        let proxies = proxies
+            .iter()
+            .map(pooled_proxy)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name,
+            proxies,
+            assignment,
+            max_failures: DEFAULT_MAX_FAILURES,
+            next: AtomicUsize::new(0),
+            sticky: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Create a pool from plugin configuration
+    pub fn from_config(
+        name: impl Into<String>,
+        config: &ProxyPoolConfig,
+    ) -> Result<Self, YoutubeError> {
+        Ok(Self::new(name, &config.proxies, config.assignment)?
+            .with_max_failures(config.max_failures))
+    }
+
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn assignment(&self) -> ProxyAssignment {
+        self.assignment
+    }
+
+    /// Pick the proxy for a request of the client identified by `key`
+    pub fn select(&self, key: &str) -> ProxySelection {
+        let index = match self.assignment {
+            ProxyAssignment::RoundRobin => self.next_healthy(),
+            ProxyAssignment::Sticky => {
+                let mut sticky = self.sticky.lock().unwrap();
+                match sticky.get(key) {
+                    Some(&index) if self.is_healthy(index) => index,
+                    _ => {
+                        let index = self.next_healthy();
+                        sticky.insert(key.to_string(), index);
+                        index
+                    }
+                }
+            }
+        };
+
+        let proxy = &self.proxies[index];
+        ProxySelection {
+            index,
+            proxy: proxy.label.clone(),
+            route: proxy.proxy.clone(),
+        }
+    }
+
+    /// Record a request through `selection` that went through
+    pub fn report_success(&self, selection: &ProxySelection) {
+        let proxy = &self.proxies[selection.index];
+        proxy.consecutive_failures.store(0, Ordering::Relaxed);
+        if !proxy.healthy.swap(true, Ordering::Relaxed) {
+            log::info!("Proxy {} of pool {} recovered", proxy.label, self.name);
+        }
+    }
+
+    /// Record a request through `selection` that could not reach YouTube
+    pub fn report_failure(&self, selection: &ProxySelection) {
+        let proxy = &self.proxies[selection.index];
+        let failures = proxy.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= self.max_failures && proxy.healthy.swap(false, Ordering::Relaxed) {
+            log::warn!(
+                "Proxy {} of pool {} failed {failures} times in a row, skipping it",
+                proxy.label,
+                self.name
+            );
+        }
+    }
+
+    /// Request `url` through every proxy, marking each healthy or unhealthy
+    ///
+    /// Any HTTP response counts as healthy, since it means the proxy
+    /// forwarded the request.
+    pub async fn check_health(&self, url: &Url) {
+        for proxy in &self.proxies {
+            let healthy = match proxy.client.get(url.clone()).send().await {
+                Ok(response) => {
+                    response.status() != reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED
+                }
+                Err(e) => {
+                    log::debug!("Health check through proxy {} failed: {e}", proxy.label);
+                    false
+                }
+            };
+
+            if healthy {
+                proxy.consecutive_failures.store(0, Ordering::Relaxed);
+            } else {
+                proxy
+                    .consecutive_failures
+                    .store(self.max_failures, Ordering::Relaxed);
+            }
+            proxy.healthy.store(healthy, Ordering::Relaxed);
+        }
+    }
+
+    /// Check the health of every proxy each `interval`, until the pool is
+    /// dropped
+    pub fn spawn_health_checks(self: &Arc<Self>, url: Url, interval: Duration) {
+        let pool = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(pool) = pool.upgrade() else {
+                    break;
+                };
+                pool.check_health(&url).await;
+            }
+        });
+    }
+
+    pub fn status(&self) -> Vec<ProxyStatus> {
+        self.proxies
+            .iter()
+            .map(|proxy| ProxyStatus {
+                proxy: proxy.label.clone(),
+                healthy: proxy.healthy.load(Ordering::Relaxed),
+                consecutive_failures: proxy.consecutive_failures.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    fn is_healthy(&self, index: usize) -> bool {
+        self.proxies[index].healthy.load(Ordering::Relaxed)
+    }
+
+    fn next_healthy(&self) -> usize {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..self.proxies.len())
+            .map(|offset| (start + offset) % self.proxies.len())
+            .find(|&index| self.is_healthy(index))
+            .unwrap_or(start % self.proxies.len())
+    }
+}
+
+fn pooled_proxy(config: &ProxyConfig) -> Result<PooledProxy, YoutubeError> {
+    let invalid = |reason: String| {
+        YoutubeError::ConfigurationError(format!("Invalid proxy {}: {reason}", config.url))
+    };
+
+    let mut url = Url::parse(&config.url).map_err(|e| invalid(e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+        return Err(invalid(format!("unsupported scheme {}", url.scheme())));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| invalid("no host".to_string()))?;
+    let label = match url.port_or_known_default() {
+        Some(port) => format!("{}://{host}:{port}", url.scheme()),
+        None => format!("{}://{host}", url.scheme()),
+    };
+
+    // HTTP and SOCKS5 proxies alike take their credentials from the URL
+    if let Some(username) = &config.username {
+        url.set_username(username)
+            .map_err(|_| invalid("cannot carry credentials".to_string()))?;
+        url.set_password(config.password.as_deref())
+            .map_err(|_| invalid("cannot carry credentials".to_string()))?;
+    }
+
+    let proxy = Proxy::all(url.as_str()).map_err(|e| invalid(e.to_string()))?;
+    let client = client_builder()
+        .proxy(proxy.clone())
+        .build()
+        .map_err(|e| YoutubeError::HttpError(format!("Failed to create HTTP client: {e}")))?;
+
+    Ok(PooledProxy {
+        label,
+        proxy,
+        client,
+        healthy: AtomicBool::new(true),
+        consecutive_failures: AtomicU32::new(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(assignment: ProxyAssignment) -> ProxyPool {
+        ProxyPool::new(
+            "residential",
+            &[
+                ProxyConfig::new("http://10.0.0.1:3128")
+                    .set_credentials(Some("user".to_string()), Some("secret".to_string())),
+                ProxyConfig::new("socks5://10.0.0.2:1080"),
+                ProxyConfig::new("http://10.0.0.3"),
+            ],
+            assignment,
+        )
+        .unwrap()
+        .with_max_failures(2)
+    }
+
+    #[test]
+    fn test_round_robin_skips_unhealthy_proxies() {
+        let pool = pool(ProxyAssignment::RoundRobin);
+        let picked: Vec<_> = (0..3).map(|_| pool.select("WEB").proxy).collect();
+        assert_eq!(
+            picked,
+            [
+                "http://10.0.0.1:3128",
+                "socks5://10.0.0.2:1080",
+                "http://10.0.0.3:80"
+            ]
+        );
+
+        let first = pool.select("WEB");
+        assert_eq!(first.proxy(), "http://10.0.0.1:3128");
+        pool.report_failure(&first);
+        assert!(pool.status()[0].healthy);
+        pool.report_failure(&first);
+        assert!(!pool.status()[0].healthy);
+
+        let picked: Vec<_> = (0..4).map(|_| pool.select("WEB").proxy).collect();
+        assert!(!picked.contains(&"http://10.0.0.1:3128".to_string()));
+
+        pool.report_success(&first);
+        assert_eq!(pool.status()[0].consecutive_failures, 0);
+        assert!(pool.status()[0].healthy);
+    }
+
+    #[test]
+    fn test_sticky_assignment_keeps_proxy_per_client() {
+        let pool = pool(ProxyAssignment::Sticky);
+        let web = pool.select("WEB");
+        let ios = pool.select("IOS");
+        assert_ne!(web.proxy(), ios.proxy());
+        for _ in 0..3 {
+            assert_eq!(pool.select("WEB").proxy(), web.proxy());
+        }
+
+        pool.report_failure(&web);
+        pool.report_failure(&web);
+        assert_ne!(pool.select("WEB").proxy(), web.proxy());
+    }
+
+    #[test]
+    fn test_rejects_invalid_proxies() {
+        for url in ["ftp://10.0.0.1", "not a url"] {
+            let error =
+                ProxyPool::new("bad", &[ProxyConfig::new(url)], ProxyAssignment::RoundRobin)
+                    .unwrap_err();
+            assert!(
+                matches!(error, YoutubeError::ConfigurationError(_)),
+                "{url}"
+            );
+        }
+        assert!(ProxyPool::new("empty", &[], ProxyAssignment::RoundRobin).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use super::RequestContext;
+use crate::plugin::RateLimitConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Innertube endpoints with their own request limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InnertubeEndpoint {
+    Player,
+    Search,
+    Browse,
+    Next,
+}
+
+impl InnertubeEndpoint {
+    /// The endpoint a request is sent to, from its context flags
+    pub fn from_context(context: &RequestContext) -> Option<Self> {
+        if context.is_player_request {
+            Some(Self::Player)
+        } else if context.is_search_request {
+            Some(Self::Search)
+        } else if context.is_browse_request {
+            Some(Self::Browse)
+        } else if context.is_next_request {
+            Some(Self::Next)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Player => "player",
+            Self::Search => "search",
+            Self::Browse => "browse",
+            Self::Next => "next",
+        }
+    }
+}
+
+impl fmt::Display for InnertubeEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// At most `requests` requests per `interval_ms`, allowed in bursts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub interval_ms: u64,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, interval: Duration) -> Self {
+        Self {
+            requests,
+            interval_ms: interval.as_millis() as u64,
+        }
+    }
+
+    /// At most `requests` requests per minute
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    /// Tokens added per second
+    refill_rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        let capacity = f64::from(limit.requests.max(1));
+        let interval = Duration::from_millis(limit.interval_ms.max(1)).as_secs_f64();
+        Self {
+            capacity,
+            refill_rate: capacity / interval,
+            tokens: capacity,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Time until a token is available
+    fn wait_time(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_rate).min(self.capacity);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.refill_rate)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    global: Option<TokenBucket>,
+    endpoints: HashMap<InnertubeEndpoint, TokenBucket>,
+    clients: HashMap<String, TokenBucket>,
+}
+
+/// Token bucket limits applied before requests are sent, so that clients
+/// slow down before YouTube answers 429
+///
+/// A request takes a token from the global bucket, its endpoint's bucket
+/// and its client's bucket, waiting until all of them have one.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a limiter from plugin configuration
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        let mut limiter = Self::new();
+        if let Some(limit) = config.global {
+            limiter = limiter.with_global_limit(limit);
+        }
+        for (endpoint, limit) in &config.endpoints {
+            limiter = limiter.with_endpoint_limit(*endpoint, *limit);
+        }
+        for (client, limit) in &config.clients {
+            limiter = limiter.with_client_limit(client.clone(), *limit);
+        }
+        limiter
+    }
+
+    /// Limit every request
+    pub fn with_global_limit(self, limit: RateLimit) -> Self {
+        self.buckets.lock().unwrap().global = Some(TokenBucket::new(limit));
+        self
+    }
+
+    /// Limit requests to `endpoint`
+    pub fn with_endpoint_limit(self, endpoint: InnertubeEndpoint, limit: RateLimit) -> Self {
+        self.buckets
+            .lock()
+            .unwrap()
+            .endpoints
+            .insert(endpoint, TokenBucket::new(limit));
+        self
+    }
+
+    /// Limit requests of the client identified by `client`
+    pub fn with_client_limit(self, client: impl Into<String>, limit: RateLimit) -> Self {
+        self.buckets
+            .lock()
+            .unwrap()
+            .clients
+            .insert(client.into(), TokenBucket::new(limit));
+        self
+    }
+
+    /// Take tokens for a request if every bucket has one, or return how long
+    /// to wait before trying again
+    pub fn try_acquire(
+        &self,
+        client: &str,
+        endpoint: Option<InnertubeEndpoint>,
+    ) -> Result<(), Duration> {
+        let mut guard = self.buckets.lock().unwrap();
+        let buckets = &mut *guard;
+        let mut applicable: Vec<&mut TokenBucket> = buckets
+            .global
+            .iter_mut()
+            .chain(endpoint.and_then(|endpoint| buckets.endpoints.get_mut(&endpoint)))
+            .chain(buckets.clients.get_mut(client))
+            .collect();
+
+        let wait = applicable
+            .iter_mut()
+            .map(|bucket| bucket.wait_time())
+            .max()
+            .unwrap_or_default();
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for bucket in applicable {
+            bucket.tokens -= 1.0;
+        }
+        Ok(())
+    }
+
+    /// Wait until a request of `client` to `endpoint` may be sent
+    pub async fn acquire(&self, client: &str, endpoint: Option<InnertubeEndpoint>) {
+        while let Err(wait) = self.try_acquire(client, endpoint) {
+            log::debug!("Request of client {client} to {endpoint:?} waits {wait:?}");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_apply_per_endpoint_and_client() {
+        let limiter = RateLimiter::new()
+            .with_endpoint_limit(InnertubeEndpoint::Player, RateLimit::per_minute(2))
+            .with_client_limit("IOS", RateLimit::per_minute(1));
+        let player = Some(InnertubeEndpoint::Player);
+
+        assert!(limiter.try_acquire("WEB", player).is_ok());
+        assert!(limiter.try_acquire("WEB", player).is_ok());
+        let wait = limiter.try_acquire("WEB", player).unwrap_err();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+
+        // Other endpoints are not limited, except for the limited client
+        assert!(limiter
+            .try_acquire("WEB", Some(InnertubeEndpoint::Search))
+            .is_ok());
+        assert!(limiter.try_acquire("IOS", None).is_ok());
+        assert!(limiter.try_acquire("IOS", None).is_err());
+    }
+
+    #[test]
+    fn test_failed_acquire_takes_no_tokens() {
+        let limiter = RateLimiter::new()
+            .with_global_limit(RateLimit::per_minute(2))
+            .with_client_limit("IOS", RateLimit::per_minute(1));
+
+        assert!(limiter.try_acquire("IOS", None).is_ok());
+        assert!(limiter.try_acquire("IOS", None).is_err());
+        assert!(limiter.try_acquire("WEB", None).is_ok());
+        assert!(limiter.try_acquire("WEB", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_refill() {
+        let limiter =
+            RateLimiter::new().with_global_limit(RateLimit::new(1, Duration::from_millis(50)));
+
+        let start = Instant::now();
+        limiter.acquire("WEB", None).await;
+        limiter.acquire("WEB", None).await;
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
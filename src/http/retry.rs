@@ -0,0 +1,267 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::time::Duration;
+
+/// What to do about a failed request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// Send the request again after the delay
+    Retry(Duration),
+    /// The playback URL expired; resolve a new one before trying again
+    RefreshUrl,
+    /// Give up, returning the response or error as is
+    Fail,
+}
+
+/// When and how soon failed requests are sent again
+///
+/// Connection resets, timeouts and the statuses in `retry_statuses` are
+/// retried with jittered exponential backoff. A 429 is only retried when its
+/// `Retry-After` is short enough to wait out; otherwise the client backs off
+/// and the load moves on to the next client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts per request, including the first
+    pub max_attempts: u32,
+
+    /// Delay before the first retry in milliseconds, doubled for each
+    /// further retry
+    pub base_delay_ms: u64,
+
+    /// Upper bound of backoff delays in milliseconds
+    pub max_delay_ms: u64,
+
+    /// Statuses retried with backoff
+    pub retry_statuses: Vec<u16>,
+
+    /// Longest `Retry-After` of a 429 waited out before retrying, in
+    /// milliseconds
+    pub max_retry_after_ms: u64,
+
+    /// Time a whole load may take across clients and retries, in
+    /// milliseconds; unbounded when unset
+    pub load_deadline_ms: Option<u64>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 100,
+            max_delay_ms: 5_000,
+            retry_statuses: vec![500, 502, 503],
+            max_retry_after_ms: 5_000,
+            load_deadline_ms: Some(60_000),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the attempts per request, including the first
+    pub fn set_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry and the bound of backoff delays
+    pub fn set_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay_ms = base_delay.as_millis() as u64;
+        self.max_delay_ms = max_delay.as_millis() as u64;
+        self
+    }
+
+    /// Set the statuses retried with backoff
+    pub fn set_retry_statuses(mut self, retry_statuses: Vec<u16>) -> Self {
+        self.retry_statuses = retry_statuses;
+        self
+    }
+
+    /// Set the longest `Retry-After` waited out before retrying
+    pub fn set_max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after_ms = max_retry_after.as_millis() as u64;
+        self
+    }
+
+    /// Set the time a whole load may take
+    pub fn set_load_deadline(mut self, load_deadline: Option<Duration>) -> Self {
+        self.load_deadline_ms = load_deadline.map(|deadline| deadline.as_millis() as u64);
+        self
+    }
+
+    pub fn load_deadline(&self) -> Option<Duration> {
+        self.load_deadline_ms.map(Duration::from_millis)
+    }
+
+    /// Decide about a request that failed with `error` on its `attempt`th
+    /// attempt
+    pub fn on_error(&self, error: &reqwest::Error, attempt: u32) -> RetryDecision {
+        if attempt < self.max_attempts && is_transient(error) {
+            RetryDecision::Retry(self.backoff(attempt))
+        } else {
+            RetryDecision::Fail
+        }
+    }
+
+    /// Decide about a response with `status` to the `attempt`th attempt of
+    /// a request
+    ///
+    /// `playback` marks requests to stream URLs, which YouTube answers with
+    /// 403 or 410 once they expired.
+    pub fn on_status(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        attempt: u32,
+        playback: bool,
+    ) -> RetryDecision {
+        if playback && (status == StatusCode::FORBIDDEN || status == StatusCode::GONE) {
+            return RetryDecision::RefreshUrl;
+        }
+        if attempt >= self.max_attempts {
+            return RetryDecision::Fail;
+        }
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return match retry_after(headers) {
+                Some(delay) if delay <= Duration::from_millis(self.max_retry_after_ms) => {
+                    RetryDecision::Retry(delay)
+                }
+                _ => RetryDecision::Fail,
+            };
+        }
+        if self.retry_statuses.contains(&status.as_u16()) {
+            return RetryDecision::Retry(self.backoff(attempt));
+        }
+        RetryDecision::Fail
+    }
+
+    /// Delay after the `attempt`th attempt: the exponential delay with up to
+    /// half of it taken off at random, so clients don't retry in lockstep
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay_ms
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay_ms);
+        Duration::from_millis(fastrand::u64(exponential / 2..=exponential))
+    }
+}
+
+/// The delay a `Retry-After` header asks for, if given in seconds
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Whether `error` is a timeout or a connection failing before or during the
+/// exchange, which a new connection may not run into
+fn is_transient(error: &reqwest::Error) -> bool {
+    if error.is_timeout() || error.is_connect() {
+        return true;
+    }
+
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            if matches!(
+                io.kind(),
+                ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::UnexpectedEof
+                    | ErrorKind::TimedOut
+            ) {
+                return true;
+            }
+        }
+        source = error.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(retry_after: Option<&'static str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(retry_after) = retry_after {
+            headers.insert(RETRY_AFTER, HeaderValue::from_static(retry_after));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_status_decisions() {
+        let policy = RetryPolicy::new();
+        let none = headers(None);
+
+        assert!(matches!(
+            policy.on_status(StatusCode::BAD_GATEWAY, &none, 1, false),
+            RetryDecision::Retry(_)
+        ));
+        assert_eq!(
+            policy.on_status(StatusCode::BAD_GATEWAY, &none, 3, false),
+            RetryDecision::Fail
+        );
+        assert_eq!(
+            policy.on_status(StatusCode::NOT_FOUND, &none, 1, false),
+            RetryDecision::Fail
+        );
+
+        // Expired stream URLs are refreshed, other 403s are final
+        assert_eq!(
+            policy.on_status(StatusCode::FORBIDDEN, &none, 3, true),
+            RetryDecision::RefreshUrl
+        );
+        assert_eq!(
+            policy.on_status(StatusCode::FORBIDDEN, &none, 1, false),
+            RetryDecision::Fail
+        );
+    }
+
+    #[test]
+    fn test_too_many_requests_waits_out_short_retry_after() {
+        let policy = RetryPolicy::new();
+        let status = StatusCode::TOO_MANY_REQUESTS;
+
+        assert_eq!(
+            policy.on_status(status, &headers(Some("2")), 1, false),
+            RetryDecision::Retry(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.on_status(status, &headers(Some("120")), 1, false),
+            RetryDecision::Fail
+        );
+        assert_eq!(
+            policy.on_status(status, &headers(None), 1, false),
+            RetryDecision::Fail
+        );
+    }
+
+    #[test]
+    fn test_backoff_is_jittered_and_bounded() {
+        let policy =
+            RetryPolicy::new().set_backoff(Duration::from_millis(100), Duration::from_millis(300));
+
+        for _ in 0..20 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let capped = policy.backoff(10);
+            assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use crate::error::YoutubeError;
+use crate::plugin::RoutePlannerConfig;
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long an address stays failing after a 429, unless freed earlier
+pub const DEFAULT_FAILING_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How the route planner picks the source address of each request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RoutePlannerStrategy {
+    /// Keep one address and move to the next one once it is rate limited
+    RotateOnBan,
+    /// A random address of the block for every request
+    LoadBalance,
+    /// An address derived from the current time in nanoseconds for every
+    /// request, which needs a large IPv6 block
+    NanoSwitch,
+    /// Nano switch within one /64 of the block, moving to the next /64 once
+    /// an address is rate limited
+    RotatingNanoSwitch,
+}
+
+impl RoutePlannerStrategy {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RotateOnBan => "RotateOnBan",
+            Self::LoadBalance => "LoadBalance",
+            Self::NanoSwitch => "NanoSwitch",
+            Self::RotatingNanoSwitch => "RotatingNanoSwitch",
+        }
+    }
+}
+
+impl fmt::Display for RoutePlannerStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An address marked as failing after a 429
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailingAddress {
+    pub address: IpAddr,
+    /// Milliseconds since the Unix epoch
+    pub failing_since_ms: u64,
+}
+
+/// Snapshot of the route planner, e.g. for a status endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoutePlannerStatus {
+    pub strategy: RoutePlannerStrategy,
+    pub ip_block: String,
+    /// Address currently in use, for the rotating strategies
+    pub current_address: Option<IpAddr>,
+    pub failing_addresses: Vec<FailingAddress>,
+}
+
+#[derive(Debug, Default)]
+struct PlannerState {
+    rotate_index: u128,
+    failing: HashMap<IpAddr, SystemTime>,
+}
+
+/// Picks the local address outbound YouTube requests are sent from
+///
+/// Migrated from Lavaplayer's route planners: addresses are taken from one
+/// IP block, typically an IPv6 /64 or larger, and addresses that got rate
+/// limited are skipped until they are freed or their failing timeout ends.
+#[derive(Debug)]
+pub struct RoutePlanner {
+    block: IpNet,
+    strategy: RoutePlannerStrategy,
+    failing_timeout: Duration,
+    state: Mutex<PlannerState>,
+}
+
+impl RoutePlanner {
+    /// Create a planner over `ip_block`, in CIDR notation
+    pub fn new(ip_block: &str, strategy: RoutePlannerStrategy) -> Result<Self, YoutubeError> {
+        let block: IpNet = ip_block.parse().map_err(|e| {
+            YoutubeError::ConfigurationError(format!("Invalid IP block {ip_block}: {e}"))
+        })?;
+
+        Ok(Self {
+            block: block.trunc(),
+            strategy,
+            failing_timeout: DEFAULT_FAILING_TIMEOUT,
+            state: Mutex::new(PlannerState::default()),
+        })
+    }
+
+    /// Create a planner from plugin configuration
+    pub fn from_config(config: &RoutePlannerConfig) -> Result<Self, YoutubeError> {
+        Ok(Self::new(&config.ip_block, config.strategy)?
+            .with_failing_timeout(Duration::from_millis(config.failing_timeout_ms)))
+    }
+
+    pub fn with_failing_timeout(mut self, failing_timeout: Duration) -> Self {
+        self.failing_timeout = failing_timeout;
+        self
+    }
+
+    pub fn strategy(&self) -> RoutePlannerStrategy {
+        self.strategy
+    }
+
+    /// The address to send the next request from
+    ///
+    /// Fails when every address of the block is failing.
+    pub fn next_address(&self) -> Result<IpAddr, YoutubeError> {
+        let mut state = self.state.lock().unwrap();
+        let timeout = self.failing_timeout;
+        state
+            .failing
+            .retain(|_, since| since.elapsed().unwrap_or_default() < timeout);
+
+        let start = match self.strategy {
+            RoutePlannerStrategy::RotateOnBan => state.rotate_index,
+            RoutePlannerStrategy::LoadBalance => fastrand::u128(..),
+            RoutePlannerStrategy::NanoSwitch => now_nanos(),
+            RoutePlannerStrategy::RotatingNanoSwitch => {
+                let bits = self.rotation_bits();
+                (state.rotate_index << bits) | (now_nanos() & mask(bits))
+            }
+        };
+
+        // At most every failing address lies between start and a free one
+        for offset in 0..=state.failing.len() as u128 {
+            let index = start.wrapping_add(offset);
+            let address = self.address_at(index);
+            if !state.failing.contains_key(&address) {
+                if self.strategy == RoutePlannerStrategy::RotateOnBan {
+                    state.rotate_index = index & mask(self.host_bits());
+                }
+                return Ok(address);
+            }
+        }
+
+        Err(YoutubeError::RateLimited(format!(
+            "Every address of {} is failing",
+            self.block
+        )))
+    }
+
+    /// Mark `address` as rate limited, so it is skipped until freed
+    pub fn mark_failing(&self, address: IpAddr) {
+        if !self.block.contains(&address) {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.failing.insert(address, SystemTime::now()).is_none() {
+            log::warn!("Marked {address} as failing");
+        }
+        if self.strategy == RoutePlannerStrategy::RotatingNanoSwitch {
+            state.rotate_index = state.rotate_index.wrapping_add(1);
+        }
+    }
+
+    /// Free a failing address, returning whether it was failing
+    pub fn unmark_address(&self, address: IpAddr) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .failing
+            .remove(&address)
+            .is_some()
+    }
+
+    /// Free every failing address
+    pub fn unmark_all(&self) {
+        self.state.lock().unwrap().failing.clear();
+    }
+
+    pub fn is_failing(&self, address: IpAddr) -> bool {
+        self.state.lock().unwrap().failing.contains_key(&address)
+    }
+
+    pub fn status(&self) -> RoutePlannerStatus {
+        let state = self.state.lock().unwrap();
+        let current_address = match self.strategy {
+            RoutePlannerStrategy::RotateOnBan => Some(self.address_at(state.rotate_index)),
+            RoutePlannerStrategy::RotatingNanoSwitch => {
+                Some(self.address_at(state.rotate_index << self.rotation_bits()))
+            }
+            RoutePlannerStrategy::LoadBalance | RoutePlannerStrategy::NanoSwitch => None,
+        };
+
+        let mut failing_addresses: Vec<_> = state
+            .failing
+            .iter()
+            .map(|(address, since)| FailingAddress {
+                address: *address,
+                failing_since_ms: since
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+            })
+            .collect();
+        failing_addresses.sort_by_key(|failing| failing.address);
+
+        RoutePlannerStatus {
+            strategy: self.strategy,
+            ip_block: self.block.to_string(),
+            current_address,
+            failing_addresses,
+        }
+    }
+
+    fn host_bits(&self) -> u32 {
+        u32::from(self.block.max_prefix_len() - self.block.prefix_len())
+    }
+
+    /// Host bits that vary within one rotation of the rotating nano switch
+    fn rotation_bits(&self) -> u32 {
+        match self.block {
+            IpNet::V6(_) => self.host_bits().min(64),
+            IpNet::V4(_) => self.host_bits(),
+        }
+    }
+
+    fn address_at(&self, index: u128) -> IpAddr {
+        let offset = index & mask(self.host_bits());
+        match self.block {
+            IpNet::V4(block) => {
+                IpAddr::V4(Ipv4Addr::from(u32::from(block.network()) | offset as u32))
+            }
+            IpNet::V6(block) => IpAddr::V6(Ipv6Addr::from(u128::from(block.network()) | offset)),
+        }
+    }
+}
+
+fn mask(bits: u32) -> u128 {
+    u128::MAX.checked_shr(128 - bits).unwrap_or(0)
+}
+
+fn now_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn test_rotate_on_ban_moves_to_next_address() {
+        let planner = RoutePlanner::new("127.0.0.8/30", RoutePlannerStrategy::RotateOnBan).unwrap();
+        assert_eq!(planner.next_address().unwrap(), ip("127.0.0.8"));
+        assert_eq!(planner.next_address().unwrap(), ip("127.0.0.8"));
+
+        planner.mark_failing(ip("127.0.0.8"));
+        assert_eq!(planner.next_address().unwrap(), ip("127.0.0.9"));
+        assert_eq!(planner.status().current_address, Some(ip("127.0.0.9")));
+
+        for address in ["127.0.0.9", "127.0.0.10", "127.0.0.11"] {
+            planner.mark_failing(ip(address));
+        }
+        assert!(matches!(
+            planner.next_address(),
+            Err(YoutubeError::RateLimited(_))
+        ));
+
+        assert!(planner.unmark_address(ip("127.0.0.10")));
+        assert_eq!(planner.next_address().unwrap(), ip("127.0.0.10"));
+        assert_eq!(planner.status().failing_addresses.len(), 3);
+    }
+
+    #[test]
+    fn test_random_strategies_skip_failing_addresses() {
+        for strategy in [
+            RoutePlannerStrategy::LoadBalance,
+            RoutePlannerStrategy::NanoSwitch,
+            RoutePlannerStrategy::RotatingNanoSwitch,
+        ] {
+            let planner = RoutePlanner::new("127.0.0.8/30", strategy).unwrap();
+            for address in ["127.0.0.8", "127.0.0.9", "127.0.0.11"] {
+                planner.mark_failing(ip(address));
+            }
+            for _ in 0..16 {
+                assert_eq!(
+                    planner.next_address().unwrap(),
+                    ip("127.0.0.10"),
+                    "{strategy}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_addresses_stay_in_ipv6_block() {
+        let block: IpNet = "2001:db8:aa::/48".parse().unwrap();
+        let planner =
+            RoutePlanner::new("2001:db8:aa::/48", RoutePlannerStrategy::RotatingNanoSwitch)
+                .unwrap()
+                .with_failing_timeout(Duration::ZERO);
+
+        let first = planner.next_address().unwrap();
+        assert!(block.contains(&first));
+        assert_eq!(planner.status().current_address, Some(ip("2001:db8:aa::")));
+
+        // A ban moves to the next /64, and expired bans are forgotten
+        planner.mark_failing(first);
+        let next = planner.next_address().unwrap();
+        assert!(block.contains(&next));
+        assert_eq!(
+            planner.status().current_address,
+            Some(ip("2001:db8:aa:1::"))
+        );
+        assert!(planner.status().failing_addresses.is_empty());
+
+        // Addresses outside the block are never marked
+        planner.mark_failing(ip("2001:db8:bb::1"));
+        assert!(!planner.is_failing(ip("2001:db8:bb::1")));
+    }
+}
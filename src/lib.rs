@@ -7,12 +7,14 @@ pub mod api;
 pub mod cipher;
 pub mod client;
 pub mod config;
+pub mod container;
 pub mod error;
 pub mod http;
 pub mod manager;
 pub mod playlist;
 pub mod plugin;
 pub mod search;
+pub mod stream;
 pub mod track;
 pub mod utils;
 
@@ -22,15 +24,20 @@ pub use client::{
     MusicClient, TvClient, WebClient, WebEmbeddedClient,
 };
 pub use config::{ClientOptions, YoutubeSourceOptions};
-pub use error::{AudioItem, Result, YoutubeError};
+pub use container::{AudioPacket, Mp4Demuxer, WebmDemuxer};
+pub use error::{AudioItem, FailureSeverity, LoadAttempt, LoadFailure, Result, YoutubeError};
 pub use manager::YoutubeAudioSourceManager;
 pub use playlist::YoutubePlaylist;
 pub use search::YoutubeSearchResult;
-pub use track::{AudioTrackInfo, StreamFormat, TrackFormats, YoutubeAudioTrack};
+pub use stream::YoutubePersistentHttpStream;
+pub use track::{
+    AudioTrackInfo, FormatSelector, PlaybackUrl, StreamFormat, TrackFormats, YoutubeAudioTrack,
+};
 
 // Re-export plugin types
 pub use plugin::{
-    ClientProvider, ClientProviderV3, ClientProviderV4, PluginInfo, Pot, YoutubeConfig,
+    ClientProvider, ClientProviderV3, ClientProviderV4, CookieConfig, PluginInfo, Pot, ProxyConfig,
+    ProxyPoolConfig, RateLimitConfig, RemoteCipherConfig, RoutePlannerConfig, YoutubeConfig,
     YoutubeOauthConfig, YoutubePluginLoader, YoutubeRestHandler,
 };
 
@@ -1,11 +1,21 @@
-use crate::{AudioItem, Client, Result, YoutubeSourceOptions};
+use crate::cipher::{CipherError, PlayerScriptInfo, SignatureCipherManager};
+use crate::client::traits::ClientCapabilities;
+use crate::error::LoadFailure;
+use crate::http::{CookieSequence, RequestRoute, YoutubeHttpClient, YoutubeHttpContextFilter};
+use crate::search::SearchKind;
+use crate::track::{PlaybackUrl, YoutubeAudioTrack};
+use crate::{AudioItem, Client, Result, YoutubeError, YoutubeSourceOptions};
 
 #[derive(Clone)]
 pub struct YoutubeAudioSourceManager {
     pub options: YoutubeSourceOptions,
     pub clients: Vec<std::sync::Arc<dyn Client>>,
-    pub http_client: reqwest::Client,
-    // TODO: Add cipher manager, oauth handler, etc.
+    /// HTTP client shared by every client, so connection pool, cookies,
+    /// rate limit backoff and visitor data are shared across requests
+    pub http_client: YoutubeHttpClient,
+    /// Cipher manager shared by every clone, used to decipher stream URLs
+    pub cipher_manager: std::sync::Arc<SignatureCipherManager>,
+    // TODO: Add oauth handler, etc.
 }
 
 impl Default for YoutubeAudioSourceManager {
@@ -26,14 +36,54 @@ impl YoutubeAudioSourceManager {
             ),
             std::sync::Arc::new(crate::client::MusicClient::new()),
             std::sync::Arc::new(crate::client::AndroidClient::new()),
-            std::sync::Arc::new(crate::client::WebEmbeddedClient::new()),
         ];
 
-        Self {
-            options,
-            clients,
-            http_client: reqwest::Client::new(),
+        Self::from_parts(options, clients)
+    }
+
+    /// Replace the shared HTTP client, e.g. to share it between managers
+    ///
+    /// The cipher manager keeps its caches and solver and only fetches
+    /// player scripts through the new client.
+    pub fn with_http_client(mut self, http_client: YoutubeHttpClient) -> Self {
+        self.cipher_manager = std::sync::Arc::new(
+            (*self.cipher_manager)
+                .clone()
+                .with_http_client(http_client.clone()),
+        );
+        self.http_client = http_client;
+        self
+    }
+
+    /// The HTTP client every client sends its requests through
+    pub fn http_client(&self) -> &YoutubeHttpClient {
+        &self.http_client
+    }
+
+    /// The context filter backing the shared HTTP client
+    pub fn http_filter(&self) -> &std::sync::Arc<YoutubeHttpContextFilter> {
+        self.http_client.filter()
+    }
+
+    /// The current player script, for clients that require one
+    ///
+    /// `None` for clients that don't, or when the player cannot be looked up;
+    /// their requests then go out without a signature timestamp.
+    pub async fn player_script_for(&self, client: &dyn Client) -> Option<PlayerScriptInfo> {
+        if !client.requires_player_script() {
+            return None;
         }
+
+        self.cipher_manager
+            .current_player_script()
+            .await
+            .inspect_err(|e| {
+                log::warn!(
+                    "No player script for client {}: {e}",
+                    client.get_identifier()
+                )
+            })
+            .ok()
     }
 
     pub fn with_options_and_clients(
@@ -43,88 +93,329 @@ impl YoutubeAudioSourceManager {
         let arc_clients: Vec<std::sync::Arc<dyn Client>> =
             clients.into_iter().map(std::sync::Arc::from).collect();
 
+        Self::from_parts(options, arc_clients)
+    }
+
+    fn from_parts(options: YoutubeSourceOptions, clients: Vec<std::sync::Arc<dyn Client>>) -> Self {
+        let http_client = YoutubeHttpClient::default();
+        let cipher_manager = std::sync::Arc::new(
+            SignatureCipherManager::new().with_http_client(http_client.clone()),
+        );
+
         Self {
             options,
-            clients: arc_clients,
-            http_client: reqwest::Client::new(),
+            clients,
+            http_client,
+            cipher_manager,
         }
     }
 
+    /// Load an identifier by trying each capable client in order.
+    ///
+    /// Returns `Ok(None)` when no client found anything, and
+    /// [`YoutubeError::LoadFailed`] with every client's error when at least
+    /// one client failed and none succeeded.
     pub async fn load_item(&self, identifier: &str) -> Result<Option<AudioItem>> {
-        // TODO: Implement router logic
         let router = self.get_router(identifier).await?;
+        // The requests of a load are one sequence, with cookies of their own
+        let source = Self {
+            http_client: self
+                .http_client
+                .with_cookie_sequence(std::sync::Arc::new(CookieSequence::new())),
+            ..self.clone()
+        };
+        let mut failure = LoadFailure::new(identifier);
+        let deadline = self
+            .http_client
+            .retry_policy()
+            .load_deadline()
+            .map(|deadline| (deadline, tokio::time::Instant::now() + deadline));
 
         for client in &self.clients {
+            if !router.is_supported_by(&client.get_capabilities()) {
+                log::debug!(
+                    "Client \"{}\" does not support {:?}, skipping",
+                    client.get_identifier(),
+                    router
+                );
+                continue;
+            }
+
             if !client.can_handle_request(identifier) {
                 continue;
             }
 
+            // Move on rather than wait out a client's backoff after a 429
+            if let Some(cooldown) = self
+                .http_filter()
+                .should_wait_for_rate_limit(client.get_identifier())
+                .await
+            {
+                log::debug!(
+                    "Client {} is cooling down for {cooldown:?}, skipping",
+                    client.get_identifier()
+                );
+                failure.record(
+                    client.get_identifier(),
+                    YoutubeError::RateLimited(format!(
+                        "Cooling down for {}s after a 429",
+                        cooldown.as_secs().max(1)
+                    )),
+                );
+                continue;
+            }
+
             log::debug!(
                 "Attempting to load {} with client \"{}\"",
                 identifier,
                 client.get_identifier()
             );
 
-            match router.route(client.as_ref(), self).await {
+            let result = match deadline {
+                Some((limit, at)) => {
+                    match tokio::time::timeout_at(at, router.route(client.as_ref(), &source)).await
+                    {
+                        Ok(result) => result,
+                        Err(_) => {
+                            // Later clients would not get any time either
+                            failure.record(
+                                client.get_identifier(),
+                                YoutubeError::NetworkError(format!(
+                                    "Load exceeded its {limit:?} deadline"
+                                )),
+                            );
+                            break;
+                        }
+                    }
+                }
+                None => router.route(client.as_ref(), &source).await,
+            };
+
+            match result {
                 Ok(Some(item)) => return Ok(Some(item)),
                 Ok(None) => continue,
                 Err(e) => {
                     log::debug!("Client {} failed: {}", client.get_identifier(), e);
+                    failure.record(client.get_identifier(), e);
                     continue;
                 }
             }
         }
 
-        Ok(None)
+        // Only report "no matches" when no client actually failed
+        if failure.is_empty() {
+            Ok(None)
+        } else {
+            Err(YoutubeError::LoadFailed(failure))
+        }
+    }
+
+    /// Resolve a ready-to-stream URL for `track`.
+    ///
+    /// Clients that can load formats are tried in order. The best format from
+    /// the first one that succeeds is deciphered with the shared cipher
+    /// manager and passed through the client's `transform_playback_uri`.
+    ///
+    /// Once the n parameter transformation fails even with a refreshed player
+    /// script, only clients that need no player script are tried.
+    pub async fn resolve_playback_url(&self, track: &YoutubeAudioTrack) -> Result<PlaybackUrl> {
+        let video_id = track.info.video_id.as_str();
+        let mut failure = LoadFailure::new(video_id);
+        let mut player_script_broken = false;
+
+        for client in &self.clients {
+            if !client.supports_format_loading() || !client.get_capabilities().videos {
+                continue;
+            }
+
+            if player_script_broken && client.requires_player_script() {
+                log::debug!(
+                    "Skipping client {} for {}, the player script cannot transform n",
+                    client.get_identifier(),
+                    video_id
+                );
+                continue;
+            }
+
+            match self
+                .resolve_playback_url_with(client.as_ref(), video_id, None)
+                .await
+            {
+                Ok(playback_url) => return Ok(playback_url),
+                Err(e) => {
+                    log::debug!(
+                        "Client {} failed to resolve playback URL for {}: {}",
+                        client.get_identifier(),
+                        video_id,
+                        e
+                    );
+                    player_script_broken |= matches!(
+                        e,
+                        YoutubeError::CipherFailed(CipherError::NTransformFailed { .. })
+                    );
+                    failure.record(client.get_identifier(), e);
+                }
+            }
+        }
+
+        if failure.is_empty() {
+            return Err(YoutubeError::CannotBeLoaded(format!(
+                "No client can load formats for {video_id}"
+            )));
+        }
+
+        Err(YoutubeError::LoadFailed(failure))
+    }
+
+    /// Resolve a new URL for a stream opened from `previous`
+    ///
+    /// Uses the same client through the same route, since the stream keeps
+    /// sending from there.
+    pub async fn refresh_playback_url(
+        &self,
+        track: &YoutubeAudioTrack,
+        previous: &PlaybackUrl,
+    ) -> Result<PlaybackUrl> {
+        let client = self
+            .clients
+            .iter()
+            .find(|client| client.get_identifier() == previous.client)
+            .ok_or_else(|| {
+                YoutubeError::CannotBeLoaded(format!(
+                    "Client {} is no longer configured",
+                    previous.client
+                ))
+            })?;
+        self.resolve_playback_url_with(
+            client.as_ref(),
+            &track.info.video_id,
+            Some(previous.route.clone()),
+        )
+        .await
+    }
+
+    async fn resolve_playback_url_with(
+        &self,
+        client: &dyn Client,
+        video_id: &str,
+        route: Option<RequestRoute>,
+    ) -> Result<PlaybackUrl> {
+        // Stream URLs are only valid from the address that requested them, so
+        // the player request goes one route that streaming then takes too
+        let route = match route {
+            Some(route) => route,
+            None => self
+                .http_client
+                .for_client(client.get_identifier())
+                .select_route(client.get_identifier())?,
+        };
+        let source = Self {
+            http_client: self
+                .http_client
+                .with_route(route.clone())
+                .with_cookie_sequence(std::sync::Arc::new(CookieSequence::new())),
+            ..self.clone()
+        };
+
+        let formats = client.get_track_formats(&source, video_id).await?;
+        let selector = client
+            .get_options()
+            .format_selector
+            .as_ref()
+            .unwrap_or(&self.options.format_selector);
+        let format = formats.select_format(selector)?;
+
+        // A ciphered signature cannot be deciphered without a player script,
+        // and the URL would be rejected with a 403 if returned as is
+        if format.signature.is_some() && !client.requires_player_script() {
+            return Err(YoutubeError::Cipher(format!(
+                "Client {} returned a ciphered signature for {video_id} but uses no player script",
+                client.get_identifier()
+            )));
+        }
+
+        // Plain URLs without a signature or n parameter need no player script,
+        // and the n parameter of clients that don't use one is left as is
+        let ciphered = format.signature.is_some() || format.n_parameter.is_some();
+        let resolved = if ciphered && client.requires_player_script() {
+            self.cipher_manager
+                .resolve_format_url(&formats.player_script_url, format)
+                .await?
+        } else {
+            format.url.clone()
+        };
+
+        Ok(PlaybackUrl {
+            url: client.transform_playback_uri(&format.url, &resolved),
+            format: format.clone(),
+            client: client.get_identifier().to_string(),
+            route,
+        })
     }
 
     async fn get_router(&self, identifier: &str) -> Result<Router> {
         use crate::utils::UrlTools;
 
-        // Check if it's a direct video ID
+        // Prefixed searches (ytsearch:, ytmsearch:, ytpl:, ytch:) take precedence
+        if let Some((kind, query)) = SearchKind::parse(identifier) {
+            if !self.options.allow_search {
+                return Ok(Router::None);
+            }
+
+            return Ok(Router::Search {
+                query: query.to_string(),
+                kind,
+            });
+        }
+
+        // Bare IDs are only routed when the matching option is enabled
+        if UrlTools::is_valid_video_id(identifier) {
+            if !self.options.allow_direct_video_ids {
+                return Err(YoutubeError::OptionDisabled(format!(
+                    "Direct video IDs are not allowed: {identifier}"
+                )));
+            }
+
+            return Ok(Router::Video {
+                video_id: identifier.to_string(),
+            });
+        }
+
+        if UrlTools::is_valid_playlist_id(identifier) {
+            if !self.options.allow_direct_playlist_ids {
+                return Err(YoutubeError::OptionDisabled(format!(
+                    "Direct playlist IDs are not allowed: {identifier}"
+                )));
+            }
+
+            return Ok(Router::playlist(identifier.to_string(), None).unwrap_or(Router::None));
+        }
+
+        // Check if it's a video URL
         if let Some(video_id) = UrlTools::extract_video_id(identifier) {
             // Check if it also has a playlist
-            if let Some(playlist_id) = UrlTools::extract_playlist_id(identifier) {
-                if playlist_id.starts_with("RD") {
-                    return Ok(Router::Mix {
-                        mix_id: playlist_id,
-                        selected_video_id: Some(video_id),
-                    });
-                } else if !playlist_id.starts_with("LL")
-                    && !playlist_id.starts_with("WL")
-                    && !playlist_id.starts_with("LM")
-                {
-                    return Ok(Router::Playlist {
-                        playlist_id,
-                        selected_video_id: Some(video_id),
-                    });
-                }
+            if let Some(router) = UrlTools::extract_playlist_id(identifier)
+                .and_then(|playlist_id| Router::playlist(playlist_id, Some(video_id.clone())))
+            {
+                return Ok(router);
             }
             return Ok(Router::Video { video_id });
         }
 
-        // Check if it's a playlist URL
+        // Check if it's a playlist URL; private lists are neither loaded nor searched
         if let Some(playlist_id) = UrlTools::extract_playlist_id(identifier) {
-            if playlist_id.starts_with("RD") {
-                return Ok(Router::Mix {
-                    mix_id: playlist_id,
-                    selected_video_id: None,
-                });
-            } else if !playlist_id.starts_with("LL")
-                && !playlist_id.starts_with("WL")
-                && !playlist_id.starts_with("LM")
-            {
-                return Ok(Router::Playlist {
-                    playlist_id,
-                    selected_video_id: None,
-                });
-            }
+            return Ok(Router::playlist(playlist_id, None).unwrap_or(Router::None));
         }
 
-        // Check if search is allowed
-        if self.options.allow_search {
+        // Plain text without a prefix is searched as videos, as before prefixes
+        // existed; a prefix with an empty query is not
+        let has_prefix = SearchKind::ALL
+            .iter()
+            .any(|kind| identifier.starts_with(kind.prefix()));
+        if self.options.allow_search && !has_prefix && !identifier.trim().is_empty() {
             return Ok(Router::Search {
                 query: identifier.to_string(),
+                kind: SearchKind::Videos,
             });
         }
 
@@ -132,6 +423,7 @@ impl YoutubeAudioSourceManager {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 enum Router {
     Video {
         video_id: String,
@@ -142,6 +434,7 @@ enum Router {
     },
     Search {
         query: String,
+        kind: SearchKind,
     },
     Mix {
         mix_id: String,
@@ -151,6 +444,38 @@ enum Router {
 }
 
 impl Router {
+    /// Route a playlist ID, treating `RD` IDs as mixes and skipping private
+    /// lists (liked videos, watch later, liked music) that cannot be loaded
+    fn playlist(playlist_id: String, selected_video_id: Option<String>) -> Option<Router> {
+        if playlist_id.starts_with("RD") {
+            Some(Router::Mix {
+                mix_id: playlist_id,
+                selected_video_id,
+            })
+        } else if playlist_id.starts_with("LL")
+            || playlist_id.starts_with("WL")
+            || playlist_id.starts_with("LM")
+        {
+            None
+        } else {
+            Some(Router::Playlist {
+                playlist_id,
+                selected_video_id,
+            })
+        }
+    }
+
+    /// Whether a client with the given capabilities can serve this route
+    fn is_supported_by(&self, capabilities: &ClientCapabilities) -> bool {
+        match self {
+            Router::Video { .. } => capabilities.videos,
+            Router::Playlist { .. } => capabilities.playlists,
+            Router::Search { kind, .. } => kind.is_supported_by(capabilities),
+            Router::Mix { .. } => capabilities.mixes,
+            Router::None => false,
+        }
+    }
+
     async fn route(
         &self,
         client: &dyn Client,
@@ -166,7 +491,7 @@ impl Router {
                     .load_playlist(source, playlist_id, selected_video_id.as_deref())
                     .await
             }
-            Router::Search { query } => client.search(source, query).await,
+            Router::Search { query, kind } => client.search_with_kind(source, query, *kind).await,
             Router::Mix {
                 mix_id,
                 selected_video_id,
@@ -185,7 +510,588 @@ impl std::fmt::Debug for YoutubeAudioSourceManager {
         f.debug_struct("YoutubeAudioSourceManager")
             .field("options", &self.options)
             .field("clients_count", &self.clients.len())
-            .field("http_client", &"reqwest::Client")
+            .field("http_client", &"YoutubeHttpClient")
+            .field("cipher_manager", &"SignatureCipherManager")
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{AndroidClient, MusicClient};
+
+    async fn router_for(manager: &YoutubeAudioSourceManager, identifier: &str) -> Router {
+        manager.get_router(identifier).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_prefixes_are_stripped() {
+        let manager = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default(),
+            Vec::new(),
+        );
+
+        let cases = [
+            ("ytsearch:never gonna", SearchKind::Videos),
+            ("ytmsearch: never gonna ", SearchKind::Music),
+            ("ytpl:never gonna", SearchKind::Playlists),
+            ("ytch:never gonna", SearchKind::Channels),
+        ];
+
+        for (identifier, kind) in cases {
+            assert_eq!(
+                router_for(&manager, identifier).await,
+                Router::Search {
+                    query: "never gonna".to_string(),
+                    kind,
+                },
+                "identifier: {identifier}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unprefixed_text_is_searched_as_videos() {
+        let manager = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default(),
+            Vec::new(),
+        );
+
+        assert_eq!(
+            router_for(&manager, "never gonna give you up").await,
+            Router::Search {
+                query: "never gonna give you up".to_string(),
+                kind: SearchKind::Videos,
+            }
+        );
+        assert_eq!(router_for(&manager, "ytsearch:   ").await, Router::None);
+        assert_eq!(router_for(&manager, "   ").await, Router::None);
+    }
+
+    #[tokio::test]
+    async fn test_search_disabled() {
+        let manager = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default().set_allow_search(false),
+            Vec::new(),
+        );
+
+        assert_eq!(
+            router_for(&manager, "ytsearch:never gonna").await,
+            Router::None
+        );
+        assert_eq!(
+            router_for(&manager, "ytmsearch:never gonna").await,
+            Router::None
+        );
+        assert_eq!(
+            router_for(&manager, "never gonna give you up").await,
+            Router::None
+        );
+    }
+
+    #[test]
+    fn test_search_routes_are_capability_checked() {
+        let music = MusicClient::new().get_capabilities();
+        let android = AndroidClient::new().get_capabilities();
+
+        let music_search = Router::Search {
+            query: "test".to_string(),
+            kind: SearchKind::Music,
+        };
+        assert!(music_search.is_supported_by(&music));
+        assert!(!music_search.is_supported_by(&android));
+
+        let video_search = Router::Search {
+            query: "test".to_string(),
+            kind: SearchKind::Videos,
+        };
+        assert!(video_search.is_supported_by(&android));
+        assert!(!video_search.is_supported_by(&music));
+        assert!(!Router::None.is_supported_by(&android));
+    }
+
+    fn video(video_id: &str) -> Router {
+        Router::Video {
+            video_id: video_id.to_string(),
+        }
+    }
+
+    fn playlist(playlist_id: &str, selected_video_id: Option<&str>) -> Router {
+        Router::Playlist {
+            playlist_id: playlist_id.to_string(),
+            selected_video_id: selected_video_id.map(str::to_string),
+        }
+    }
+
+    fn mix(mix_id: &str, selected_video_id: Option<&str>) -> Router {
+        Router::Mix {
+            mix_id: mix_id.to_string(),
+            selected_video_id: selected_video_id.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_direct_id_options_matrix() {
+        const VIDEO: &str = "dQw4w9WgXcQ";
+        const PLAYLIST: &str = "PLrAXtmRdnEQy4Qy9RBqOQQ1";
+        const MIX: &str = "RDdQw4w9WgXcQ";
+
+        // (identifier, router when direct IDs are allowed, whether the
+        // identifier is a bare ID and must be rejected when they are not)
+        let cases = [
+            (
+                format!("https://www.youtube.com/watch?v={VIDEO}"),
+                video(VIDEO),
+                false,
+            ),
+            (
+                format!("https://youtube.com/watch?v={VIDEO}"),
+                video(VIDEO),
+                false,
+            ),
+            (format!("https://youtu.be/{VIDEO}"), video(VIDEO), false),
+            (
+                format!("https://www.youtube.com/embed/{VIDEO}"),
+                video(VIDEO),
+                false,
+            ),
+            (
+                format!("https://www.youtube.com/watch?v={VIDEO}&list={PLAYLIST}"),
+                playlist(PLAYLIST, Some(VIDEO)),
+                false,
+            ),
+            (
+                format!("https://www.youtube.com/watch?v={VIDEO}&list={MIX}"),
+                mix(MIX, Some(VIDEO)),
+                false,
+            ),
+            (
+                format!("https://www.youtube.com/watch?v={VIDEO}&list=WL"),
+                video(VIDEO),
+                false,
+            ),
+            (
+                format!("https://www.youtube.com/playlist?list={PLAYLIST}"),
+                playlist(PLAYLIST, None),
+                false,
+            ),
+            (
+                format!("https://www.youtube.com/playlist?list={MIX}"),
+                mix(MIX, None),
+                false,
+            ),
+            (
+                "https://www.youtube.com/playlist?list=LLxxxxxxxxxx".to_string(),
+                Router::None,
+                false,
+            ),
+            (VIDEO.to_string(), video(VIDEO), true),
+            (PLAYLIST.to_string(), playlist(PLAYLIST, None), true),
+            (MIX.to_string(), mix(MIX, None), true),
+            ("LLxxxxxxxxxx".to_string(), Router::None, true),
+            (
+                "not a youtube identifier".to_string(),
+                Router::Search {
+                    query: "not a youtube identifier".to_string(),
+                    kind: SearchKind::Videos,
+                },
+                false,
+            ),
+        ];
+
+        let allowed = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default()
+                .set_allow_direct_video_ids(true)
+                .set_allow_direct_playlist_ids(true),
+            Vec::new(),
+        );
+        let disallowed = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default()
+                .set_allow_direct_video_ids(false)
+                .set_allow_direct_playlist_ids(false),
+            Vec::new(),
+        );
+
+        for (identifier, expected, is_bare_id) in cases {
+            assert_eq!(
+                router_for(&allowed, &identifier).await,
+                expected,
+                "identifier: {identifier}"
+            );
+
+            let result = disallowed.get_router(&identifier).await;
+            if is_bare_id {
+                assert!(
+                    matches!(result, Err(YoutubeError::OptionDisabled(_))),
+                    "identifier: {identifier}, result: {result:?}"
+                );
+            } else {
+                assert_eq!(result.unwrap(), expected, "identifier: {identifier}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_direct_id_options_are_independent() {
+        let videos_only = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default().set_allow_direct_playlist_ids(false),
+            Vec::new(),
+        );
+        assert_eq!(
+            router_for(&videos_only, "dQw4w9WgXcQ").await,
+            video("dQw4w9WgXcQ")
+        );
+        assert!(matches!(
+            videos_only.get_router("PLrAXtmRdnEQy4Qy9RBqOQQ1").await,
+            Err(YoutubeError::OptionDisabled(_))
+        ));
+
+        let playlists_only = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default().set_allow_direct_video_ids(false),
+            Vec::new(),
+        );
+        assert!(matches!(
+            playlists_only.get_router("dQw4w9WgXcQ").await,
+            Err(YoutubeError::OptionDisabled(_))
+        ));
+        assert_eq!(
+            router_for(&playlists_only, "PLrAXtmRdnEQy4Qy9RBqOQQ1").await,
+            playlist("PLrAXtmRdnEQy4Qy9RBqOQQ1", None)
+        );
+    }
+
+    #[test]
+    fn test_http_client_is_shared() {
+        use crate::client::base::{NonMusicClient, NonMusicClientBase};
+        use crate::client::config::ClientConfig;
+
+        let manager = YoutubeAudioSourceManager::new();
+        let clone = manager.clone();
+        assert!(std::sync::Arc::ptr_eq(
+            manager.http_filter(),
+            clone.http_filter()
+        ));
+
+        let base = NonMusicClientBase::new(
+            YoutubeHttpClient::default(),
+            ClientConfig::android(),
+            "ANDROID".to_string(),
+        );
+        assert!(!std::sync::Arc::ptr_eq(
+            base.get_http_client().filter(),
+            manager.http_filter()
+        ));
+
+        let bound = base.for_source(&manager);
+        assert!(std::sync::Arc::ptr_eq(
+            bound.get_http_client().filter(),
+            manager.http_filter()
+        ));
+    }
+
+    /// Client serving fixed formats, to exercise the playback fallback
+    struct FormatsClient {
+        identifier: &'static str,
+        requires_player_script: bool,
+        formats: crate::TrackFormats,
+        options: crate::ClientOptions,
+        /// How long loading a video takes
+        load_delay: std::time::Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl Client for FormatsClient {
+        fn get_identifier(&self) -> &'static str {
+            self.identifier
+        }
+
+        fn get_options(&self) -> &crate::ClientOptions {
+            &self.options
+        }
+
+        fn can_handle_request(&self, _identifier: &str) -> bool {
+            true
+        }
+
+        fn requires_player_script(&self) -> bool {
+            self.requires_player_script
+        }
+
+        fn get_capabilities(&self) -> ClientCapabilities {
+            ClientCapabilities::android_standard()
+        }
+
+        async fn load_video(
+            &self,
+            _source: &YoutubeAudioSourceManager,
+            _video_id: &str,
+        ) -> Result<Option<AudioItem>> {
+            tokio::time::sleep(self.load_delay).await;
+            Ok(None)
+        }
+
+        async fn load_playlist(
+            &self,
+            _source: &YoutubeAudioSourceManager,
+            _playlist_id: &str,
+            _selected_video_id: Option<&str>,
+        ) -> Result<Option<AudioItem>> {
+            Ok(None)
+        }
+
+        async fn search(
+            &self,
+            _source: &YoutubeAudioSourceManager,
+            _query: &str,
+        ) -> Result<Option<AudioItem>> {
+            Ok(None)
+        }
+
+        async fn get_track_formats(
+            &self,
+            _source: &YoutubeAudioSourceManager,
+            _video_id: &str,
+        ) -> Result<crate::TrackFormats> {
+            Ok(self.formats.clone())
+        }
+
+        async fn load_mix(
+            &self,
+            _source: &YoutubeAudioSourceManager,
+            _mix_id: &str,
+            _selected_video_id: Option<&str>,
+        ) -> Result<Option<AudioItem>> {
+            Ok(None)
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_n_transform_falls_back_to_client_without_player_script() {
+        use crate::cipher::{CachedCipher, CipherCache, CipherCacheKey, MemoryCipherCache};
+        use crate::track::{AudioTrackInfo, StreamFormat, TrackFormats};
+        use url::Url;
+
+        // The cached player's n function returns its input, and the player
+        // cannot be fetched again since nothing listens on the discard port
+        let cache = std::sync::Arc::new(MemoryCipherCache::new());
+        cache
+            .put(CachedCipher {
+                key: CipherCacheKey::new("abc123", "19834"),
+                global_vars: String::new(),
+                sig_actions: String::new(),
+                sig_function: "var sig = function(c) { return c; }".to_string(),
+                n_function: "var n = function(c) { return c; }".to_string(),
+                cached_at: std::time::SystemTime::now(),
+            })
+            .await
+            .unwrap();
+        let player = Url::parse("http://127.0.0.1:9/s/player/abc123/base.js").unwrap();
+
+        let format = |query: &str, n_parameter: Option<&str>| StreamFormat {
+            info: Some(crate::track::FormatInfo::WebmOpus),
+            content_type: "audio/webm; codecs=\"opus\"".to_string(),
+            itag: 251,
+            bitrate: 128000,
+            content_length: 0,
+            audio_channels: 2,
+            url: Url::parse(&format!("https://example.com/videoplayback?{query}")).unwrap(),
+            n_parameter: n_parameter.map(String::from),
+            signature: None,
+            signature_key: None,
+            is_default_audio_track: true,
+            is_drc: false,
+            language: None,
+        };
+        let client = |identifier, requires_player_script, format| -> Box<dyn Client> {
+            Box::new(FormatsClient {
+                identifier,
+                requires_player_script,
+                formats: TrackFormats::new(vec![format], player.clone()),
+                options: crate::ClientOptions::default(),
+                load_delay: std::time::Duration::ZERO,
+            })
+        };
+
+        let mut manager = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default(),
+            vec![
+                client("WEB", true, format("n=abc", Some("abc"))),
+                client("TV", true, format("n=abc", Some("abc"))),
+                client("ANDROID", false, format("plain", None)),
+            ],
+        );
+        manager.cipher_manager =
+            std::sync::Arc::new(SignatureCipherManager::new().with_cipher_cache(cache));
+
+        let track = YoutubeAudioTrack {
+            info: AudioTrackInfo {
+                title: String::new(),
+                author: String::new(),
+                duration: std::time::Duration::ZERO,
+                video_id: "dQw4w9WgXcQ".to_string(),
+                is_stream: false,
+                uri: Url::parse("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap(),
+                thumbnail: None,
+                artwork_url: None,
+            },
+            source_manager: std::sync::Arc::new(manager),
+        };
+        let playback = track.resolve_playback_url().await.unwrap();
+
+        // TV needs the same broken player, so it is skipped
+        assert_eq!(playback.client, "ANDROID");
+        assert_eq!(
+            playback.url.as_str(),
+            "https://example.com/videoplayback?plain"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ciphered_signature_without_player_script_fails() {
+        use crate::track::{AudioTrackInfo, StreamFormat, TrackFormats};
+        use url::Url;
+
+        let player = Url::parse("https://www.youtube.com/s/player/abc123/base.js").unwrap();
+        let format = StreamFormat {
+            info: Some(crate::track::FormatInfo::WebmOpus),
+            content_type: "audio/webm; codecs=\"opus\"".to_string(),
+            itag: 251,
+            bitrate: 128000,
+            content_length: 0,
+            audio_channels: 2,
+            url: Url::parse("https://example.com/videoplayback?plain").unwrap(),
+            n_parameter: None,
+            signature: Some("ciphered".to_string()),
+            signature_key: Some("sig".to_string()),
+            is_default_audio_track: true,
+            is_drc: false,
+            language: None,
+        };
+        let manager = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default(),
+            vec![Box::new(FormatsClient {
+                identifier: "ANDROID",
+                requires_player_script: false,
+                formats: TrackFormats::new(vec![format], player),
+                options: crate::ClientOptions::default(),
+                load_delay: std::time::Duration::ZERO,
+            })],
+        );
+
+        let track = YoutubeAudioTrack {
+            info: AudioTrackInfo {
+                title: String::new(),
+                author: String::new(),
+                duration: std::time::Duration::ZERO,
+                video_id: "dQw4w9WgXcQ".to_string(),
+                is_stream: false,
+                uri: Url::parse("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap(),
+                thumbnail: None,
+                artwork_url: None,
+            },
+            source_manager: std::sync::Arc::new(manager),
+        };
+
+        let Err(YoutubeError::LoadFailed(failure)) = track.resolve_playback_url().await else {
+            panic!("expected the ciphered format to be rejected");
+        };
+        assert!(matches!(failure.attempts[0].error, YoutubeError::Cipher(_)));
+    }
+
+    #[tokio::test]
+    async fn test_load_skips_client_cooling_down() {
+        let player = url::Url::parse("https://www.youtube.com/s/player/abc123/base.js").unwrap();
+        let client = |identifier| -> Box<dyn Client> {
+            Box::new(FormatsClient {
+                identifier,
+                requires_player_script: false,
+                formats: crate::TrackFormats::new(Vec::new(), player.clone()),
+                options: crate::ClientOptions::default(),
+                load_delay: std::time::Duration::ZERO,
+            })
+        };
+        let manager = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default(),
+            vec![client("WEB"), client("ANDROID")],
+        );
+        assert!(manager.load_item("dQw4w9WgXcQ").await.unwrap().is_none());
+
+        // A 429 of one client leaves the others alone
+        let context = crate::http::RequestContext {
+            client_identifier: Some("WEB".to_string()),
+            ..Default::default()
+        };
+        manager
+            .http_filter()
+            .handle_rate_limit(&context, None)
+            .await
+            .unwrap();
+        assert!(manager
+            .http_filter()
+            .should_wait_for_rate_limit("ANDROID")
+            .await
+            .is_none());
+
+        let Err(YoutubeError::LoadFailed(failure)) = manager.load_item("dQw4w9WgXcQ").await else {
+            panic!("expected the cooling down client to be reported");
+        };
+        assert_eq!(failure.attempts.len(), 1);
+        assert_eq!(failure.attempts[0].client, "WEB");
+        assert!(failure.is_rate_limited());
+    }
+
+    #[tokio::test]
+    async fn test_load_stops_at_deadline() {
+        let player = url::Url::parse("https://www.youtube.com/s/player/abc123/base.js").unwrap();
+        let client = |identifier, load_delay| -> Box<dyn Client> {
+            Box::new(FormatsClient {
+                identifier,
+                requires_player_script: false,
+                formats: crate::TrackFormats::new(Vec::new(), player.clone()),
+                options: crate::ClientOptions::default(),
+                load_delay,
+            })
+        };
+        let http_client = YoutubeHttpClient::new().unwrap().with_retry_policy(
+            crate::http::RetryPolicy::new()
+                .set_load_deadline(Some(std::time::Duration::from_millis(50))),
+        );
+        let manager = YoutubeAudioSourceManager::with_options_and_clients(
+            YoutubeSourceOptions::default(),
+            vec![
+                client("WEB", std::time::Duration::from_secs(10)),
+                client("ANDROID", std::time::Duration::ZERO),
+            ],
+        )
+        .with_http_client(http_client);
+
+        let start = std::time::Instant::now();
+        let Err(YoutubeError::LoadFailed(failure)) = manager.load_item("dQw4w9WgXcQ").await else {
+            panic!("expected the load to exceed its deadline");
+        };
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        // Clients after the deadline are not tried
+        assert_eq!(failure.attempts.len(), 1);
+        assert_eq!(failure.attempts[0].client, "WEB");
+    }
+
+    #[test]
+    fn test_with_http_client_keeps_cipher_manager() {
+        let cache: std::sync::Arc<dyn crate::cipher::CipherCache> =
+            std::sync::Arc::new(crate::cipher::MemoryCipherCache::new());
+        let mut manager = YoutubeAudioSourceManager::new();
+        manager.cipher_manager =
+            std::sync::Arc::new(SignatureCipherManager::new().with_cipher_cache(cache.clone()));
+
+        let manager = manager.with_http_client(YoutubeHttpClient::new().unwrap());
+        assert!(std::sync::Arc::ptr_eq(
+            manager.cipher_manager.cipher_cache(),
+            &cache
+        ));
+    }
+}
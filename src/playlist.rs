@@ -6,6 +6,8 @@ pub struct YoutubePlaylist {
     pub tracks: Vec<YoutubeAudioTrack>,
     pub selected_track: Option<usize>,
     pub is_search_result: bool,
+    /// Playlist URL, set for playlists that were found through search without loading tracks
+    pub url: Option<String>,
 }
 
 impl YoutubePlaylist {
@@ -15,6 +17,7 @@ impl YoutubePlaylist {
             tracks: Vec::new(),
             selected_track: None,
             is_search_result: false,
+            url: None,
         }
     }
 
@@ -24,6 +27,7 @@ impl YoutubePlaylist {
             tracks,
             selected_track: None,
             is_search_result: false,
+            url: None,
         }
     }
 
@@ -1,5 +1,9 @@
-use super::{Pot, YoutubeOauthConfig};
-use crate::ClientOptions;
+use super::{
+    CookieConfig, Pot, ProxyPoolConfig, RateLimitConfig, RemoteCipherConfig, RoutePlannerConfig,
+    YoutubeOauthConfig,
+};
+use crate::http::RetryPolicy;
+use crate::{ClientOptions, FormatSelector};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -35,6 +39,38 @@ pub struct YoutubeConfig {
 
     /// OAuth configuration for authenticated access
     pub oauth: Option<YoutubeOauthConfig>,
+
+    /// Format selection used by clients without their own `format_selector` option
+    #[serde(default)]
+    pub format_selector: FormatSelector,
+
+    /// Cipher solving service used instead of the embedded JavaScript engine
+    #[serde(default)]
+    pub remote_cipher: Option<RemoteCipherConfig>,
+
+    /// Source addresses to rotate through when rate limited
+    #[serde(default)]
+    pub route_planner: Option<RoutePlannerConfig>,
+
+    /// Proxy pools by name
+    #[serde(default)]
+    pub proxy_pools: HashMap<String, ProxyPoolConfig>,
+
+    /// Pool used by clients that don't name one in their options
+    #[serde(default)]
+    pub proxy_pool: Option<String>,
+
+    /// Request limits applied before YouTube starts answering 429
+    #[serde(default)]
+    pub rate_limits: Option<RateLimitConfig>,
+
+    /// Account cookies and their persistence
+    #[serde(default)]
+    pub cookies: Option<CookieConfig>,
+
+    /// How failed requests are retried, and the deadline of each load
+    #[serde(default)]
+    pub retry_policy: Option<RetryPolicy>,
 }
 
 impl Default for YoutubeConfig {
@@ -48,6 +84,14 @@ impl Default for YoutubeConfig {
             clients: None,
             client_options: HashMap::new(),
             oauth: None,
+            format_selector: FormatSelector::default(),
+            remote_cipher: None,
+            route_planner: None,
+            proxy_pools: HashMap::new(),
+            proxy_pool: None,
+            rate_limits: None,
+            cookies: None,
+            retry_policy: None,
         }
     }
 }
@@ -106,6 +150,54 @@ impl YoutubeConfig {
         self
     }
 
+    /// Set the default format selector
+    pub fn set_format_selector(mut self, format_selector: FormatSelector) -> Self {
+        self.format_selector = format_selector;
+        self
+    }
+
+    /// Set the remote cipher solving service
+    pub fn set_remote_cipher(mut self, remote_cipher: Option<RemoteCipherConfig>) -> Self {
+        self.remote_cipher = remote_cipher;
+        self
+    }
+
+    /// Set the route planner
+    pub fn set_route_planner(mut self, route_planner: Option<RoutePlannerConfig>) -> Self {
+        self.route_planner = route_planner;
+        self
+    }
+
+    /// Add a proxy pool named `name`
+    pub fn add_proxy_pool(mut self, name: impl Into<String>, pool: ProxyPoolConfig) -> Self {
+        self.proxy_pools.insert(name.into(), pool);
+        self
+    }
+
+    /// Set the pool used by clients that don't name one
+    pub fn set_proxy_pool(mut self, proxy_pool: Option<String>) -> Self {
+        self.proxy_pool = proxy_pool;
+        self
+    }
+
+    /// Set the request limits
+    pub fn set_rate_limits(mut self, rate_limits: Option<RateLimitConfig>) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Set the cookie configuration
+    pub fn set_cookies(mut self, cookies: Option<CookieConfig>) -> Self {
+        self.cookies = cookies;
+        self
+    }
+
+    /// Set the retry policy
+    pub fn set_retry_policy(mut self, retry_policy: Option<RetryPolicy>) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     /// Get options for a specific client, returning default if not configured
     pub fn get_options_for_client(&self, client_name: &str) -> ClientOptions {
         self.client_options
@@ -188,4 +280,48 @@ mod tests {
             ClientOptions::default()
         );
     }
+
+    #[test]
+    fn test_format_selector_from_json() {
+        let config: YoutubeConfig = serde_json::from_value(serde_json::json!({
+            "enabled": true,
+            "allow_search": true,
+            "allow_direct_video_ids": true,
+            "allow_direct_playlist_ids": true,
+            "pot": null,
+            "clients": ["IOS"],
+            "client_options": {
+                "IOS": {
+                    "playback": true,
+                    "playlist_loading": true,
+                    "video_loading": true,
+                    "searching": true,
+                    "format_selector": { "codec_preference": ["Mp4AacLc"] }
+                }
+            },
+            "oauth": null,
+            "format_selector": { "max_bitrate": 96000, "preferred_language": "de" }
+        }))
+        .unwrap();
+
+        assert_eq!(config.format_selector.max_bitrate, Some(96000));
+        assert_eq!(
+            config.format_selector.preferred_language.as_deref(),
+            Some("de")
+        );
+        assert!(!config.format_selector.codec_preference.is_empty());
+
+        let ios = config
+            .get_options_for_client("IOS")
+            .format_selector
+            .unwrap();
+        assert_eq!(
+            ios.codec_preference,
+            vec![crate::track::FormatInfo::Mp4AacLc]
+        );
+        assert!(config
+            .get_options_for_client("WEB")
+            .format_selector
+            .is_none());
+    }
 }
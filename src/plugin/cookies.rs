@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+fn default_persist_interval_ms() -> u64 {
+    300_000
+}
+
+/// Cookie configuration for logged-in sessions
+///
+/// Cookies from `cookies_txt` are sent with every request. When
+/// `persist_file` is set, the cookies YouTube rotates are saved there and
+/// restored on the next start, unless `cookies_txt` was changed since.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CookieConfig {
+    /// Netscape `cookies.txt` exported from a logged-in browser
+    #[serde(default)]
+    pub cookies_txt: Option<String>,
+
+    /// JSON file cookies are persisted to across restarts
+    #[serde(default)]
+    pub persist_file: Option<String>,
+
+    /// How often cookies are persisted, in milliseconds
+    #[serde(default = "default_persist_interval_ms")]
+    pub persist_interval_ms: u64,
+}
+
+impl Default for CookieConfig {
+    fn default() -> Self {
+        Self {
+            cookies_txt: None,
+            persist_file: None,
+            persist_interval_ms: default_persist_interval_ms(),
+        }
+    }
+}
+
+impl CookieConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the Netscape `cookies.txt` to load
+    pub fn set_cookies_txt(mut self, cookies_txt: Option<String>) -> Self {
+        self.cookies_txt = cookies_txt;
+        self
+    }
+
+    /// Set the file cookies are persisted to
+    pub fn set_persist_file(mut self, persist_file: Option<String>) -> Self {
+        self.persist_file = persist_file;
+        self
+    }
+
+    /// Set how often cookies are persisted, in milliseconds
+    pub fn set_persist_interval_ms(mut self, persist_interval_ms: u64) -> Self {
+        self.persist_interval_ms = persist_interval_ms;
+        self
+    }
+}
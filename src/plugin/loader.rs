@@ -1,9 +1,15 @@
-use super::{ClientProvider, ClientProviderV3, ClientProviderV4, PluginInfo, YoutubeConfig};
+use super::{
+    ClientProvider, ClientProviderV3, ClientProviderV4, CookieConfig, PluginInfo, YoutubeConfig,
+};
 use crate::client::{
     AndroidClient, IosClient, MusicClient, TvClient, WebClient, WebEmbeddedClient,
 };
+use crate::http::{ProxyPool, RateLimiter, RoutePlanner, YoutubeCookieJar, YoutubeHttpClient};
 use crate::{Client, YoutubeAudioSourceManager, YoutubeSource, YoutubeSourceOptions};
 use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// YouTube plugin loader for Lavalink integration
 ///
@@ -65,7 +71,9 @@ impl YoutubePluginLoader {
             }
         }
 
-        let source = self.create_source_manager().await?;
+        let mut source = self.create_source_manager().await?;
+        self.configure_http_client(&mut source).await?;
+        self.configure_cipher(&mut source)?;
         self.configure_oauth(&source).await?;
         self.configure_po_token(&source)?;
 
@@ -101,6 +109,7 @@ impl YoutubePluginLoader {
                 .set_allow_search(config.is_search_allowed())
                 .set_allow_direct_video_ids(config.are_direct_video_ids_allowed())
                 .set_allow_direct_playlist_ids(config.are_direct_playlist_ids_allowed())
+                .set_format_selector(config.format_selector.clone())
         } else {
             YoutubeSourceOptions::default()
         }
@@ -133,7 +142,7 @@ impl YoutubePluginLoader {
                 .unwrap_or_default();
 
             let client: Box<dyn Client> = match client_name.as_str() {
-                "WEB" => Box::new(WebClient::new()?),
+                "WEB" => Box::new(WebClient::with_options(client_options)?),
                 "MUSIC" => Box::new(MusicClient::with_options(client_options)),
                 "ANDROID" => Box::new(AndroidClient::with_options(client_options)),
                 "ANDROID_VR" => Box::new(AndroidClient::vr_with_options(client_options)),
@@ -196,6 +205,135 @@ impl YoutubePluginLoader {
         Ok(())
     }
 
+    /// Send requests through the configured route planner, rate limiter and
+    /// proxy pools, with the configured cookies and retry policy
+    async fn configure_http_client(
+        &self,
+        source: &mut YoutubeAudioSourceManager,
+    ) -> crate::Result<()> {
+        let Some(config) = &self.config else {
+            return Ok(());
+        };
+
+        let mut http_client = YoutubeHttpClient::new()?;
+        let mut configured = false;
+
+        if let Some(cookies) = &config.cookies {
+            http_client = http_client.with_cookie_jar(Self::create_cookie_jar(cookies).await?)?;
+            configured = true;
+        }
+
+        if let Some(route_planner) = &config.route_planner {
+            let planner = RoutePlanner::from_config(route_planner)?;
+            info!(
+                "Routing requests through {} with strategy {}",
+                route_planner.ip_block, route_planner.strategy
+            );
+            http_client = http_client.with_route_planner(Arc::new(planner));
+            configured = true;
+        }
+
+        if let Some(retry_policy) = &config.retry_policy {
+            http_client = http_client.with_retry_policy(retry_policy.clone());
+            configured = true;
+        }
+
+        if let Some(rate_limits) = &config.rate_limits {
+            http_client =
+                http_client.with_rate_limiter(Arc::new(RateLimiter::from_config(rate_limits)));
+            configured = true;
+        }
+
+        let pools = Self::create_proxy_pools(config)?;
+        let pool = |name: &str| {
+            pools.get(name).cloned().ok_or_else(|| {
+                crate::YoutubeError::ConfigurationError(format!("Unknown proxy pool {name}"))
+            })
+        };
+        if let Some(name) = &config.proxy_pool {
+            http_client = http_client.with_proxy_pool(pool(name)?);
+            configured = true;
+        }
+        for client in &source.clients {
+            if let Some(name) = &client.get_options().proxy_pool {
+                debug!("Client {} uses proxy pool {name}", client.get_identifier());
+                http_client =
+                    http_client.with_client_proxy_pool(client.get_identifier(), pool(name)?);
+                configured = true;
+            }
+        }
+
+        if configured {
+            *source = source.clone().with_http_client(http_client);
+        }
+        Ok(())
+    }
+
+    /// Create the cookie jar, restoring persisted cookies unless the
+    /// cookies.txt changed since they were saved, and start persisting it
+    async fn create_cookie_jar(config: &CookieConfig) -> crate::Result<Arc<YoutubeCookieJar>> {
+        let modified = |path: &str| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let persisted = config
+            .persist_file
+            .as_deref()
+            .and_then(|path| modified(path).map(|saved_at| (path, saved_at)));
+        let cookies_txt = config.cookies_txt.as_deref();
+
+        let jar = Arc::new(YoutubeCookieJar::new());
+        match (persisted, cookies_txt) {
+            (Some((path, saved_at)), cookies_txt)
+                if cookies_txt
+                    .and_then(modified)
+                    .is_none_or(|changed_at| changed_at <= saved_at) =>
+            {
+                let restored = jar.restore(path).await?;
+                info!("Restored {restored} cookies from {path}");
+            }
+            (_, Some(path)) => {
+                let loaded = jar.load_netscape_file(path).await?;
+                info!("Loaded {loaded} cookies from {path}");
+            }
+            _ => {}
+        }
+
+        if let Some(path) = &config.persist_file {
+            jar.spawn_persistence(
+                path.into(),
+                Duration::from_millis(config.persist_interval_ms.max(1)),
+            );
+        }
+        Ok(jar)
+    }
+
+    /// Create the configured proxy pools, starting their health checks
+    fn create_proxy_pools(
+        config: &YoutubeConfig,
+    ) -> crate::Result<HashMap<String, Arc<ProxyPool>>> {
+        let mut pools = HashMap::new();
+        for (name, pool_config) in &config.proxy_pools {
+            let pool = Arc::new(ProxyPool::from_config(name.clone(), pool_config)?);
+            if let Some(interval_ms) = pool_config.health_check_interval_ms {
+                let url = url::Url::parse(&pool_config.health_check_url).map_err(|e| {
+                    crate::YoutubeError::ConfigurationError(format!(
+                        "Invalid health check URL of proxy pool {name}: {e}"
+                    ))
+                })?;
+                pool.spawn_health_checks(url, Duration::from_millis(interval_ms));
+            }
+            pools.insert(name.clone(), pool);
+        }
+        Ok(pools)
+    }
+
+    /// Select the cipher solver, e.g. a remote solving service
+    fn configure_cipher(&self, source: &mut YoutubeAudioSourceManager) -> crate::Result<()> {
+        if let Some(config) = &self.config {
+            let cipher_manager = (*source.cipher_manager).clone().with_config(config)?;
+            source.cipher_manager = std::sync::Arc::new(cipher_manager);
+        }
+        Ok(())
+    }
+
     /// Get the configuration
     pub fn get_config(&self) -> Option<&YoutubeConfig> {
         self.config.as_ref()
@@ -243,4 +381,90 @@ mod tests {
 
         assert_eq!(client_names, vec!["WEB", "MUSIC"]);
     }
+
+    #[tokio::test]
+    async fn test_remote_cipher_selects_solver() {
+        let loader = YoutubePluginLoader::new(Some(YoutubeConfig::new()));
+        let source = loader.configure_audio_source_manager().await.unwrap();
+        assert!(source.cipher_manager.solver().is_none());
+
+        let config = YoutubeConfig::new().set_remote_cipher(Some(crate::RemoteCipherConfig::new(
+            "http://127.0.0.1:9/solve",
+        )));
+        let loader = YoutubePluginLoader::new(Some(config));
+        let source = loader.configure_audio_source_manager().await.unwrap();
+        assert!(source.cipher_manager.solver().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_pools_are_assigned_per_client() {
+        let pool = |url: &str| crate::ProxyPoolConfig::new(vec![crate::ProxyConfig::new(url)]);
+        let mut config = YoutubeConfig::new()
+            .set_clients(vec!["WEB".to_string(), "IOS".to_string()])
+            .add_proxy_pool("default", pool("http://10.0.0.1:3128"))
+            .add_proxy_pool("mobile", pool("socks5://10.0.0.2:1080"))
+            .set_proxy_pool(Some("default".to_string()));
+        config.client_options.insert(
+            "IOS".to_string(),
+            crate::ClientOptions::default().set_proxy_pool(Some("mobile".to_string())),
+        );
+        let loader = YoutubePluginLoader::new(Some(config));
+        let source = loader.configure_audio_source_manager().await.unwrap();
+
+        let pool_of = |client: &str| {
+            source
+                .http_client()
+                .for_client(client)
+                .proxy_pool()
+                .unwrap()
+                .name()
+                .to_string()
+        };
+        assert_eq!(pool_of("WEB"), "default");
+        assert_eq!(pool_of("IOS"), "mobile");
+
+        let config = YoutubeConfig::new().set_proxy_pool(Some("missing".to_string()));
+        let loader = YoutubePluginLoader::new(Some(config));
+        assert!(loader.configure_audio_source_manager().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cookies_txt_is_loaded_into_http_client() {
+        let directory = tempfile::tempdir().unwrap();
+        let cookies_txt = directory.path().join("cookies.txt");
+        std::fs::write(
+            &cookies_txt,
+            ".youtube.com\tTRUE\t/\tTRUE\t4102444800\tSID\taccount\n",
+        )
+        .unwrap();
+
+        let config = YoutubeConfig::new().set_cookies(Some(
+            CookieConfig::new().set_cookies_txt(Some(cookies_txt.display().to_string())),
+        ));
+        let loader = YoutubePluginLoader::new(Some(config));
+        let source = loader.configure_audio_source_manager().await.unwrap();
+
+        let url = url::Url::parse("https://www.youtube.com/youtubei/v1/player").unwrap();
+        assert_eq!(
+            source.http_client().cookie_jar().cookies_for(&url),
+            [("SID".to_string(), "account".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_route_planner_config_binds_http_client() {
+        let config = YoutubeConfig::new().set_route_planner(Some(
+            crate::RoutePlannerConfig::new("2001:db8::/48")
+                .set_strategy(crate::http::RoutePlannerStrategy::LoadBalance),
+        ));
+        let loader = YoutubePluginLoader::new(Some(config));
+        let source = loader.configure_audio_source_manager().await.unwrap();
+
+        let status = source.http_client().route_planner().unwrap().status();
+        assert_eq!(status.ip_block, "2001:db8::/48");
+        assert_eq!(
+            status.strategy,
+            crate::http::RoutePlannerStrategy::LoadBalance
+        );
+    }
 }
@@ -1,16 +1,26 @@
 pub mod client_provider;
 pub mod config;
+pub mod cookies;
 pub mod info;
 pub mod loader;
 pub mod oauth_config;
 pub mod pot;
+pub mod proxy;
+pub mod rate_limit;
+pub mod remote_cipher;
 pub mod rest;
+pub mod route_planner;
 pub mod utils;
 
 pub use client_provider::{ClientProvider, ClientProviderV3, ClientProviderV4};
 pub use config::YoutubeConfig;
+pub use cookies::CookieConfig;
 pub use info::PluginInfo;
 pub use loader::YoutubePluginLoader;
 pub use oauth_config::YoutubeOauthConfig;
 pub use pot::Pot;
+pub use proxy::{ProxyConfig, ProxyPoolConfig};
+pub use rate_limit::RateLimitConfig;
+pub use remote_cipher::RemoteCipherConfig;
 pub use rest::YoutubeRestHandler;
+pub use route_planner::RoutePlannerConfig;
@@ -0,0 +1,118 @@
+use crate::http::ProxyAssignment;
+use serde::{Deserialize, Serialize};
+
+fn default_max_failures() -> u32 {
+    crate::http::proxy::DEFAULT_MAX_FAILURES
+}
+
+fn default_health_check_url() -> String {
+    "https://www.youtube.com/generate_204".to_string()
+}
+
+/// One HTTP or SOCKS5 proxy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// Proxy URL, e.g. `http://10.0.0.1:3128` or `socks5://10.0.0.2:1080`
+    pub url: String,
+
+    #[serde(default)]
+    pub username: Option<String>,
+
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl ProxyConfig {
+    /// Create a configuration for the proxy at `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            username: None,
+            password: None,
+        }
+    }
+
+    /// Set the credentials sent to the proxy
+    pub fn set_credentials(mut self, username: Option<String>, password: Option<String>) -> Self {
+        self.username = username;
+        self.password = password;
+        self
+    }
+}
+
+/// Proxy pool configuration
+///
+/// Pools are named in [`YoutubeConfig::proxy_pools`](super::YoutubeConfig)
+/// and assigned to every client, or to single clients through their
+/// options.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProxyPoolConfig {
+    pub proxies: Vec<ProxyConfig>,
+
+    #[serde(default)]
+    pub assignment: ProxyAssignment,
+
+    /// Consecutive failures after which a proxy is skipped
+    #[serde(default = "default_max_failures")]
+    pub max_failures: u32,
+
+    /// How often every proxy is checked, in milliseconds; never when unset
+    #[serde(default)]
+    pub health_check_interval_ms: Option<u64>,
+
+    /// URL requested through each proxy by health checks
+    #[serde(default = "default_health_check_url")]
+    pub health_check_url: String,
+}
+
+impl ProxyPoolConfig {
+    /// Create a round robin pool of `proxies`
+    pub fn new(proxies: Vec<ProxyConfig>) -> Self {
+        Self {
+            proxies,
+            assignment: ProxyAssignment::default(),
+            max_failures: default_max_failures(),
+            health_check_interval_ms: None,
+            health_check_url: default_health_check_url(),
+        }
+    }
+
+    /// Set how requests are spread over the proxies
+    pub fn set_assignment(mut self, assignment: ProxyAssignment) -> Self {
+        self.assignment = assignment;
+        self
+    }
+
+    /// Set the consecutive failures after which a proxy is skipped
+    pub fn set_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+
+    /// Set how often every proxy is checked, in milliseconds
+    pub fn set_health_check_interval_ms(mut self, interval_ms: Option<u64>) -> Self {
+        self.health_check_interval_ms = interval_ms;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_from_json() {
+        let config: ProxyPoolConfig = serde_json::from_value(serde_json::json!({
+            "proxies": [
+                { "url": "socks5://10.0.0.2:1080", "username": "user", "password": "secret" }
+            ],
+            "assignment": "Sticky"
+        }))
+        .unwrap();
+
+        assert_eq!(config.assignment, ProxyAssignment::Sticky);
+        assert_eq!(config.max_failures, 3);
+        assert_eq!(config.health_check_interval_ms, None);
+        assert_eq!(config.proxies[0].username.as_deref(), Some("user"));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::http::{InnertubeEndpoint, RateLimit};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Request limits applied before requests are sent
+///
+/// ```json
+/// {
+///   "global": { "requests": 120, "interval_ms": 60000 },
+///   "endpoints": { "player": { "requests": 30, "interval_ms": 60000 } },
+///   "clients": { "WEB": { "requests": 20, "interval_ms": 60000 } }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// Limit for every request
+    #[serde(default)]
+    pub global: Option<RateLimit>,
+
+    /// Limits per Innertube endpoint
+    #[serde(default)]
+    pub endpoints: HashMap<InnertubeEndpoint, RateLimit>,
+
+    /// Limits per client identifier, e.g. `WEB` or `IOS`
+    #[serde(default)]
+    pub clients: HashMap<String, RateLimit>,
+}
+
+impl RateLimitConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the limit for every request
+    pub fn set_global(mut self, limit: Option<RateLimit>) -> Self {
+        self.global = limit;
+        self
+    }
+
+    /// Set the limit for requests to `endpoint`
+    pub fn set_endpoint_limit(mut self, endpoint: InnertubeEndpoint, limit: RateLimit) -> Self {
+        self.endpoints.insert(endpoint, limit);
+        self
+    }
+
+    /// Set the limit for requests of the client identified by `client`
+    pub fn set_client_limit(mut self, client: impl Into<String>, limit: RateLimit) -> Self {
+        self.clients.insert(client.into(), limit);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let config: RateLimitConfig = serde_json::from_value(serde_json::json!({
+            "endpoints": { "player": { "requests": 30, "interval_ms": 60000 } },
+            "clients": { "WEB": { "requests": 20, "interval_ms": 60000 } }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            config,
+            RateLimitConfig::new()
+                .set_endpoint_limit(InnertubeEndpoint::Player, RateLimit::per_minute(30))
+                .set_client_limit("WEB", RateLimit::per_minute(20))
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+/// Remote cipher solving service configuration
+///
+/// When set, signatures and n parameters are solved by posting them to `url`
+/// instead of running the player script in the embedded JavaScript engine,
+/// which saves the engine's memory on every node.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RemoteCipherConfig {
+    /// Endpoint that solve requests are posted to
+    pub url: String,
+
+    /// Bearer token sent with every request, if the service requires one
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// Request timeout in milliseconds
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl RemoteCipherConfig {
+    /// Create a configuration for the service at `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            token: None,
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+
+    /// Set the bearer token
+    pub fn set_token(mut self, token: Option<String>) -> Self {
+        self.token = token.filter(|token| !token.is_empty());
+        self
+    }
+
+    /// Set the request timeout in milliseconds
+    pub fn set_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_from_json() {
+        let config: RemoteCipherConfig =
+            serde_json::from_value(serde_json::json!({ "url": "http://solver:8080/solve" }))
+                .unwrap();
+
+        assert_eq!(config, RemoteCipherConfig::new("http://solver:8080/solve"));
+        assert_eq!(config.timeout_ms, 10_000);
+        assert!(config.set_token(Some(String::new())).token.is_none());
+    }
+}
@@ -0,0 +1,50 @@
+use crate::http::RoutePlannerStrategy;
+use serde::{Deserialize, Serialize};
+
+fn default_strategy() -> RoutePlannerStrategy {
+    RoutePlannerStrategy::RotateOnBan
+}
+
+fn default_failing_timeout_ms() -> u64 {
+    crate::http::route_planner::DEFAULT_FAILING_TIMEOUT.as_millis() as u64
+}
+
+/// Route planner configuration
+///
+/// When set, outbound requests are sent from addresses of `ip_block`, moving
+/// away from addresses that YouTube rate limits.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RoutePlannerConfig {
+    /// Block to take addresses from, in CIDR notation, e.g. `2001:db8::/48`
+    pub ip_block: String,
+
+    #[serde(default = "default_strategy")]
+    pub strategy: RoutePlannerStrategy,
+
+    /// How long a rate limited address is skipped, in milliseconds
+    #[serde(default = "default_failing_timeout_ms")]
+    pub failing_timeout_ms: u64,
+}
+
+impl RoutePlannerConfig {
+    /// Create a configuration rotating through `ip_block`
+    pub fn new(ip_block: impl Into<String>) -> Self {
+        Self {
+            ip_block: ip_block.into(),
+            strategy: default_strategy(),
+            failing_timeout_ms: default_failing_timeout_ms(),
+        }
+    }
+
+    /// Set how addresses are picked
+    pub fn set_strategy(mut self, strategy: RoutePlannerStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set how long a rate limited address is skipped, in milliseconds
+    pub fn set_failing_timeout_ms(mut self, failing_timeout_ms: u64) -> Self {
+        self.failing_timeout_ms = failing_timeout_ms;
+        self
+    }
+}
@@ -1,10 +1,96 @@
+use crate::client::ClientCapabilities;
 use crate::{YoutubeAudioTrack, YoutubePlaylist};
 use std::time::Duration;
 
+/// Prefix for regular YouTube video searches
+pub const SEARCH_PREFIX: &str = "ytsearch:";
+/// Prefix for YouTube Music song searches
+pub const MUSIC_SEARCH_PREFIX: &str = "ytmsearch:";
+/// Prefix for playlist-only searches
+pub const PLAYLIST_SEARCH_PREFIX: &str = "ytpl:";
+/// Prefix for channel-only searches
+pub const CHANNEL_SEARCH_PREFIX: &str = "ytch:";
+
+/// Kind of search requested through an identifier prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchKind {
+    /// `ytsearch:` - regular video search
+    Videos,
+    /// `ytmsearch:` - YouTube Music song search
+    Music,
+    /// `ytpl:` - playlist-only search
+    Playlists,
+    /// `ytch:` - channel-only search
+    Channels,
+}
+
+impl SearchKind {
+    /// All search kinds, in the order their prefixes are matched
+    pub const ALL: [SearchKind; 4] = [
+        SearchKind::Videos,
+        SearchKind::Music,
+        SearchKind::Playlists,
+        SearchKind::Channels,
+    ];
+
+    /// Split a prefixed identifier into its search kind and trimmed query
+    ///
+    /// Returns `None` if the identifier has no known prefix or the query is empty.
+    pub fn parse(identifier: &str) -> Option<(SearchKind, &str)> {
+        Self::ALL.iter().find_map(|kind| {
+            identifier
+                .strip_prefix(kind.prefix())
+                .map(str::trim)
+                .filter(|query| !query.is_empty())
+                .map(|query| (*kind, query))
+        })
+    }
+
+    /// Get the identifier prefix for this search kind
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            SearchKind::Videos => SEARCH_PREFIX,
+            SearchKind::Music => MUSIC_SEARCH_PREFIX,
+            SearchKind::Playlists => PLAYLIST_SEARCH_PREFIX,
+            SearchKind::Channels => CHANNEL_SEARCH_PREFIX,
+        }
+    }
+
+    /// Check whether a client with the given capabilities can run this search
+    pub fn is_supported_by(&self, capabilities: &ClientCapabilities) -> bool {
+        match self {
+            SearchKind::Videos | SearchKind::Playlists | SearchKind::Channels => {
+                capabilities.search
+            }
+            SearchKind::Music => capabilities.music_search,
+        }
+    }
+
+    /// Innertube search `params` filter restricting results to this kind
+    pub fn search_params(&self) -> Option<&'static str> {
+        match self {
+            SearchKind::Videos | SearchKind::Music => None,
+            SearchKind::Playlists => Some("EgIQAw=="),
+            SearchKind::Channels => Some("EgIQAg=="),
+        }
+    }
+
+    /// Check whether a parsed search result belongs to this kind
+    pub fn matches(&self, result: &SearchResult) -> bool {
+        match self {
+            SearchKind::Videos | SearchKind::Music => result.is_video(),
+            SearchKind::Playlists => matches!(result, SearchResult::Playlist { .. }),
+            SearchKind::Channels => matches!(result, SearchResult::Channel { .. }),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct YoutubeSearchResult {
     pub tracks: Vec<YoutubeAudioTrack>,
     pub playlists: Vec<YoutubePlaylist>,
+    /// Channel results, only filled in by channel searches
+    pub channels: Vec<SearchResult>,
     pub query: String,
 }
 
@@ -13,6 +99,7 @@ impl YoutubeSearchResult {
         Self {
             tracks: Vec::new(),
             playlists: Vec::new(),
+            channels: Vec::new(),
             query,
         }
     }
@@ -21,6 +108,7 @@ impl YoutubeSearchResult {
         Self {
             tracks,
             playlists: Vec::new(),
+            channels: Vec::new(),
             query,
         }
     }
@@ -33,12 +121,16 @@ impl YoutubeSearchResult {
         self.playlists.push(playlist);
     }
 
+    pub fn add_channel(&mut self, channel: SearchResult) {
+        self.channels.push(channel);
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.tracks.is_empty() && self.playlists.is_empty()
+        self.tracks.is_empty() && self.playlists.is_empty() && self.channels.is_empty()
     }
 
     pub fn total_results(&self) -> usize {
-        self.tracks.len() + self.playlists.len()
+        self.tracks.len() + self.playlists.len() + self.channels.len()
     }
 }
 
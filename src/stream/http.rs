@@ -0,0 +1,427 @@
+use crate::error::YoutubeError;
+use crate::http::{RetryDecision, RetryPolicy};
+use crate::track::{PlaybackUrl, StreamFormat};
+use bytes::Bytes;
+use reqwest::{Client, Response};
+use std::future::Future;
+use std::io::{self, SeekFrom};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+use url::Url;
+
+/// Bytes requested per ranged request
+///
+/// YouTube throttles or rejects large open-ended requests, so streams are
+/// read in `range=start-end` chunks like the Java persistent HTTP stream.
+pub const DEFAULT_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Consecutive reconnects allowed without reading any bytes in between
+pub const DEFAULT_MAX_RECONNECTS: u32 = 3;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// Produces a fresh stream URL once the current one has expired
+pub type UrlRefresher = Arc<dyn Fn() -> BoxFuture<crate::Result<Url>> + Send + Sync>;
+
+enum State {
+    /// Nothing in flight; the next read continues the current response or
+    /// connects at the current position
+    Idle,
+    Refreshing(BoxFuture<crate::Result<Url>>),
+    /// Backing off before reconnecting
+    Waiting(Pin<Box<tokio::time::Sleep>>),
+    Connecting(BoxFuture<reqwest::Result<Response>>),
+    Reading(BoxFuture<(Response, reqwest::Result<Option<Bytes>>)>),
+}
+
+/// Seekable audio byte stream over a googlevideo format URL
+///
+/// Based on Java YoutubePersistentHttpStream, this provides:
+/// - Chunked `range=` requests instead of one long-lived connection
+/// - Transparent reconnects when the connection resets mid-chunk
+/// - URL refreshing when YouTube answers 403/410 for an expired URL
+/// - Backoff before reconnecting after server errors, per the retry policy
+/// - Byte offset seeking bounded by the format's `content_length`
+pub struct YoutubePersistentHttpStream {
+    client: Client,
+    url: Url,
+    content_length: Option<u64>,
+    format: Option<StreamFormat>,
+    chunk_size: u64,
+    max_reconnects: u32,
+    url_refresher: Option<UrlRefresher>,
+    retry_policy: RetryPolicy,
+    position: u64,
+    /// Exclusive end of the range requested by the current response
+    chunk_end: Option<u64>,
+    response: Option<Response>,
+    buffer: Bytes,
+    reconnects: u32,
+    state: State,
+}
+
+impl YoutubePersistentHttpStream {
+    pub fn new(client: Client, url: Url, content_length: Option<u64>) -> Self {
+        Self {
+            client,
+            url,
+            content_length: content_length.filter(|length| *length > 0),
+            format: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            max_reconnects: DEFAULT_MAX_RECONNECTS,
+            url_refresher: None,
+            retry_policy: RetryPolicy::default(),
+            position: 0,
+            chunk_end: None,
+            response: None,
+            buffer: Bytes::new(),
+            reconnects: 0,
+            state: State::Idle,
+        }
+    }
+
+    /// Create a stream over a resolved playback URL
+    pub fn from_playback_url(client: Client, playback_url: PlaybackUrl) -> Self {
+        let mut stream = Self::new(
+            client,
+            playback_url.url,
+            Some(playback_url.format.content_length),
+        );
+        stream.format = Some(playback_url.format);
+        stream
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    pub fn with_max_reconnects(mut self, max_reconnects: u32) -> Self {
+        self.max_reconnects = max_reconnects;
+        self
+    }
+
+    pub fn with_url_refresher(mut self, url_refresher: UrlRefresher) -> Self {
+        self.url_refresher = Some(url_refresher);
+        self
+    }
+
+    /// Retry failed range requests as `retry_policy` allows
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Current read position in bytes
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Total stream length, if known
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Format the stream was opened from, if it came from a playback URL
+    pub fn format(&self) -> Option<&StreamFormat> {
+        self.format.as_ref()
+    }
+
+    /// The URL currently used for requests
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.content_length
+            .is_some_and(|length| self.position >= length)
+    }
+
+    /// Build the URL for a ranged request; `end` is inclusive
+    fn range_url(url: &Url, start: u64, end: Option<u64>) -> Url {
+        let mut ranged = url.clone();
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| key != "range")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+
+        let range = match end {
+            Some(end) => format!("{start}-{end}"),
+            None => format!("{start}-"),
+        };
+
+        ranged
+            .query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair("range", &range);
+        ranged
+    }
+
+    fn connect(&mut self) -> BoxFuture<reqwest::Result<Response>> {
+        let chunk_end = self
+            .content_length
+            .map(|length| length.min(self.position.saturating_add(self.chunk_size)));
+        self.chunk_end = chunk_end;
+
+        let url = Self::range_url(&self.url, self.position, chunk_end.map(|end| end - 1));
+        log::debug!("Opening stream range {url}");
+
+        let request = self.client.get(url);
+        Box::pin(async move { request.send().await })
+    }
+
+    fn read_chunk(mut response: Response) -> BoxFuture<(Response, reqwest::Result<Option<Bytes>>)> {
+        Box::pin(async move {
+            let chunk = response.chunk().await;
+            (response, chunk)
+        })
+    }
+
+    /// Drop the current connection and try again from the current position
+    fn reconnect(&mut self, reason: &str) -> io::Result<()> {
+        self.response = None;
+        self.state = State::Idle;
+        self.reconnects += 1;
+
+        if self.reconnects > self.max_reconnects {
+            return Err(io::Error::other(YoutubeError::NetworkError(format!(
+                "Stream failed after {} reconnects: {reason}",
+                self.max_reconnects
+            ))));
+        }
+
+        log::warn!(
+            "Reconnecting stream at byte {} (attempt {}): {reason}",
+            self.position,
+            self.reconnects
+        );
+        Ok(())
+    }
+
+    fn handle_response(&mut self, response: Response) -> io::Result<()> {
+        let status = response.status();
+
+        if status.is_success() {
+            self.response = Some(response);
+            self.state = State::Idle;
+            return Ok(());
+        }
+
+        let attempt = self.reconnects + 1;
+        match self
+            .retry_policy
+            .on_status(status, response.headers(), attempt, true)
+        {
+            // The URL has most likely expired; fetch a new one if we can
+            RetryDecision::RefreshUrl => {
+                if let Some(refresher) = self.url_refresher.clone() {
+                    self.reconnect(&format!("stream URL expired ({status})"))?;
+                    self.state = State::Refreshing(refresher());
+                    return Ok(());
+                }
+            }
+            RetryDecision::Retry(delay) => {
+                self.reconnect(&format!("server answered {status}"))?;
+                self.state = State::Waiting(Box::pin(tokio::time::sleep(delay)));
+                return Ok(());
+            }
+            RetryDecision::Fail => {}
+        }
+
+        Err(io::Error::other(YoutubeError::HttpError(format!(
+            "Stream request failed with status: {status}"
+        ))))
+    }
+
+    fn handle_chunk(
+        &mut self,
+        response: Response,
+        chunk: reqwest::Result<Option<Bytes>>,
+    ) -> io::Result<()> {
+        match chunk {
+            Ok(Some(bytes)) => {
+                self.response = Some(response);
+                self.buffer = bytes;
+                self.state = State::Idle;
+                Ok(())
+            }
+            Ok(None) => match self.chunk_end {
+                // Range finished; the next read requests the following range
+                Some(end) if self.position >= end => {
+                    self.state = State::Idle;
+                    Ok(())
+                }
+                Some(end) => self.reconnect(&format!(
+                    "connection closed at byte {} before range end {end}",
+                    self.position
+                )),
+                None => {
+                    // Open-ended request: the body ending is the stream ending
+                    self.content_length = Some(self.position);
+                    self.state = State::Idle;
+                    Ok(())
+                }
+            },
+            Err(e) => self.reconnect(&e.to_string()),
+        }
+    }
+}
+
+impl AsyncRead for YoutubePersistentHttpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        loop {
+            if !this.buffer.is_empty() {
+                let count = this.buffer.len().min(buf.remaining());
+                buf.put_slice(&this.buffer.split_to(count));
+                this.position += count as u64;
+                this.reconnects = 0;
+                return Poll::Ready(Ok(()));
+            }
+
+            if buf.remaining() == 0 || this.is_at_end() {
+                return Poll::Ready(Ok(()));
+            }
+
+            match &mut this.state {
+                State::Idle => {
+                    this.state = match this.response.take() {
+                        Some(response) => State::Reading(Self::read_chunk(response)),
+                        None => State::Connecting(this.connect()),
+                    };
+                }
+                // Finished futures are dropped before handling their output,
+                // so a read after an error starts over rather than polling
+                // them again
+                State::Refreshing(future) => {
+                    let result = ready!(future.as_mut().poll(cx));
+                    this.state = State::Idle;
+                    this.url = result.map_err(io::Error::other)?;
+                    log::debug!("Refreshed stream URL");
+                }
+                State::Waiting(sleep) => {
+                    ready!(sleep.as_mut().poll(cx));
+                    this.state = State::Idle;
+                }
+                State::Connecting(future) => {
+                    let result = ready!(future.as_mut().poll(cx));
+                    this.state = State::Idle;
+                    match result {
+                        Ok(response) => this.handle_response(response)?,
+                        Err(e) => this.reconnect(&e.to_string())?,
+                    }
+                }
+                State::Reading(future) => {
+                    let (response, chunk) = ready!(future.as_mut().poll(cx));
+                    this.state = State::Idle;
+                    this.handle_chunk(response, chunk)?;
+                }
+            }
+        }
+    }
+}
+
+impl AsyncSeek for YoutubePersistentHttpStream {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+
+        let target = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => this.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => {
+                let length = this.content_length.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "cannot seek from the end of a stream with unknown length",
+                    )
+                })?;
+                length.checked_add_signed(delta)
+            }
+        }
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek position"))?;
+
+        if target == this.position {
+            return Ok(());
+        }
+
+        // Skip forward inside the buffered chunk instead of reconnecting
+        let buffered_end = this.position + this.buffer.len() as u64;
+        if target > this.position && target <= buffered_end {
+            let _ = this.buffer.split_to((target - this.position) as usize);
+            this.position = target;
+            return Ok(());
+        }
+
+        this.buffer = Bytes::new();
+        this.response = None;
+        this.state = State::Idle;
+        this.reconnects = 0;
+        this.position = target;
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+impl std::fmt::Debug for YoutubePersistentHttpStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("YoutubePersistentHttpStream")
+            .field("url", &self.url.as_str())
+            .field("position", &self.position)
+            .field("content_length", &self.content_length)
+            .field("chunk_size", &self.chunk_size)
+            .field("reconnects", &self.reconnects)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_url_replaces_existing_range() {
+        let url =
+            Url::parse("https://example.com/videoplayback?itag=251&range=0-10&n=abc").unwrap();
+
+        let ranged = YoutubePersistentHttpStream::range_url(&url, 100, Some(199));
+        assert_eq!(
+            ranged.as_str(),
+            "https://example.com/videoplayback?itag=251&n=abc&range=100-199"
+        );
+
+        let open = YoutubePersistentHttpStream::range_url(&url, 5, None);
+        assert_eq!(
+            open.as_str(),
+            "https://example.com/videoplayback?itag=251&n=abc&range=5-"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_seek_bounds() {
+        use tokio::io::AsyncSeekExt;
+
+        let url = Url::parse("https://example.com/videoplayback").unwrap();
+        let mut stream = YoutubePersistentHttpStream::new(Client::new(), url, Some(1000));
+
+        assert_eq!(stream.seek(SeekFrom::Start(250)).await.unwrap(), 250);
+        assert_eq!(stream.seek(SeekFrom::Current(-50)).await.unwrap(), 200);
+        assert_eq!(stream.seek(SeekFrom::End(-100)).await.unwrap(), 900);
+        assert!(stream.seek(SeekFrom::Current(-1000)).await.is_err());
+
+        let url = Url::parse("https://example.com/videoplayback").unwrap();
+        let mut unknown = YoutubePersistentHttpStream::new(Client::new(), url, None);
+        assert!(unknown.seek(SeekFrom::End(0)).await.is_err());
+    }
+}
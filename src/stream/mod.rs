@@ -0,0 +1,5 @@
+pub mod http;
+
+pub use http::{
+    UrlRefresher, YoutubePersistentHttpStream, DEFAULT_CHUNK_SIZE, DEFAULT_MAX_RECONNECTS,
+};
@@ -20,6 +20,60 @@ pub struct YoutubeAudioTrack {
     pub source_manager: std::sync::Arc<crate::YoutubeAudioSourceManager>,
 }
 
+impl YoutubeAudioTrack {
+    /// Resolve a ready-to-stream URL through the track's source manager
+    pub async fn resolve_playback_url(&self) -> crate::Result<PlaybackUrl> {
+        self.source_manager.resolve_playback_url(self).await
+    }
+
+    /// Open a seekable byte stream for the track's best format
+    ///
+    /// The stream shares the manager's HTTP client, going the route the
+    /// player request took, and re-resolves the playback URL through the
+    /// manager along that route when YouTube reports it expired.
+    pub async fn open_stream(&self) -> crate::Result<crate::stream::YoutubePersistentHttpStream> {
+        let playback_url = self.resolve_playback_url().await?;
+
+        let track = self.clone();
+        let previous = playback_url.clone();
+        let url_refresher: crate::stream::UrlRefresher = std::sync::Arc::new(move || {
+            let track = track.clone();
+            let previous = previous.clone();
+            Box::pin(async move {
+                track
+                    .source_manager
+                    .refresh_playback_url(&track, &previous)
+                    .await
+                    .map(|playback_url| playback_url.url)
+            })
+        });
+
+        let http_client = &self.source_manager.http_client;
+        Ok(
+            crate::stream::YoutubePersistentHttpStream::from_playback_url(
+                http_client
+                    .for_client(&playback_url.client)
+                    .with_route(playback_url.route.clone())
+                    .playback_client()?,
+                playback_url,
+            )
+            .with_url_refresher(url_refresher)
+            .with_retry_policy(http_client.retry_policy().clone()),
+        )
+    }
+}
+
+/// A deciphered stream URL together with the format it was resolved from
+#[derive(Debug, Clone)]
+pub struct PlaybackUrl {
+    pub url: Url,
+    pub format: StreamFormat,
+    /// Identifier of the client whose formats were used
+    pub client: String,
+    /// Route the player request took, which the stream must take too
+    pub route: crate::http::RequestRoute,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FormatInfo {
     WebmOpus,
@@ -62,6 +116,9 @@ pub struct StreamFormat {
     pub signature_key: Option<String>,
     pub is_default_audio_track: bool,
     pub is_drc: bool,
+    /// Language of the audio track, for videos with several dubbed tracks
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -78,20 +135,14 @@ impl TrackFormats {
         }
     }
 
+    /// Pick the best format with the default [`FormatSelector`]
     pub fn get_best_format(&self) -> crate::Result<&StreamFormat> {
-        let mut best_format: Option<&StreamFormat> = None;
-
-        for format in &self.formats {
-            if !format.is_default_audio_track {
-                continue;
-            }
-
-            if Self::is_better_format(format, best_format) {
-                best_format = Some(format);
-            }
-        }
+        self.select_format(&FormatSelector::default())
+    }
 
-        best_format.ok_or_else(|| {
+    /// Pick the best format according to `selector`
+    pub fn select_format(&self, selector: &FormatSelector) -> crate::Result<&StreamFormat> {
+        selector.select(&self.formats).ok_or_else(|| {
             let available_types: Vec<String> = self
                 .formats
                 .iter()
@@ -103,18 +154,296 @@ impl TrackFormats {
             ))
         })
     }
+}
+
+/// Policy deciding which stream format is played
+///
+/// The default policy keeps the original rule of picking the highest bitrate
+/// format with a known codec. With [`rank_by_codec`](Self::rank_by_codec),
+/// formats are ranked by codec preference first, then by bitrate. Bitrate and
+/// channel caps are soft: when no format fits them, the lowest bitrate format
+/// is used rather than failing playback.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatSelector {
+    /// Accepted formats, most preferred first; formats not listed are never picked
+    pub codec_preference: Vec<FormatInfo>,
+    /// Rank by `codec_preference` before bitrate instead of by bitrate alone
+    pub rank_by_codec: bool,
+    pub max_bitrate: Option<u64>,
+    pub max_channels: Option<u64>,
+    /// Prefer dynamic range compressed (`isDrc`) variants (`true`) or regular
+    /// ones (`false`); `None` ignores DRC
+    pub prefer_drc: Option<bool>,
+    /// Audio track language (e.g. `en` or `pt-BR`) for videos with several
+    /// dubbed tracks; the default track is used when it is unavailable
+    pub preferred_language: Option<String>,
+}
+
+impl Default for FormatSelector {
+    fn default() -> Self {
+        Self {
+            codec_preference: vec![
+                FormatInfo::WebmOpus,
+                FormatInfo::WebmVorbis,
+                FormatInfo::Mp4AacLc,
+                FormatInfo::WebmVideoVorbis,
+                FormatInfo::Mp4VideoAacLc,
+            ],
+            rank_by_codec: false,
+            max_bitrate: None,
+            max_channels: None,
+            prefer_drc: None,
+            preferred_language: None,
+        }
+    }
+}
+
+impl FormatSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_codec_preference(mut self, codec_preference: Vec<FormatInfo>) -> Self {
+        self.codec_preference = codec_preference;
+        self
+    }
+
+    pub fn set_rank_by_codec(mut self, rank_by_codec: bool) -> Self {
+        self.rank_by_codec = rank_by_codec;
+        self
+    }
+
+    pub fn set_max_bitrate(mut self, max_bitrate: Option<u64>) -> Self {
+        self.max_bitrate = max_bitrate;
+        self
+    }
+
+    pub fn set_max_channels(mut self, max_channels: Option<u64>) -> Self {
+        self.max_channels = max_channels;
+        self
+    }
+
+    pub fn set_prefer_drc(mut self, prefer_drc: bool) -> Self {
+        self.prefer_drc = Some(prefer_drc);
+        self
+    }
 
-    fn is_better_format(format: &StreamFormat, other: Option<&StreamFormat>) -> bool {
-        if format.info.is_none() {
-            return false;
+    pub fn set_preferred_language(mut self, preferred_language: Option<String>) -> Self {
+        self.preferred_language = preferred_language;
+        self
+    }
+
+    /// Pick the best format, or `None` if no format is acceptable
+    pub fn select<'a>(&self, formats: &'a [StreamFormat]) -> Option<&'a StreamFormat> {
+        let supported: Vec<(usize, &StreamFormat)> = formats
+            .iter()
+            .filter_map(|format| Some((self.codec_rank(format)?, format)))
+            .collect();
+
+        let language_matches: Vec<_> = match &self.preferred_language {
+            Some(language) => supported
+                .iter()
+                .copied()
+                .filter(|(_, format)| {
+                    format
+                        .language
+                        .as_deref()
+                        .is_some_and(|format_language| language_matches(format_language, language))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let candidates = if language_matches.is_empty() {
+            supported
+                .into_iter()
+                .filter(|(_, format)| format.is_default_audio_track)
+                .collect()
+        } else {
+            language_matches
+        };
+
+        let within_caps: Vec<_> = candidates
+            .iter()
+            .copied()
+            .filter(|(_, format)| self.within_caps(format))
+            .collect();
+
+        if within_caps.is_empty() {
+            return candidates
+                .into_iter()
+                .min_by_key(|(rank, format)| (format.bitrate, *rank))
+                .map(|(_, format)| format);
         }
 
-        match other {
-            None => true,
-            Some(other_format) => {
-                // Prefer higher bitrate
-                format.bitrate > other_format.bitrate
-            }
+        within_caps
+            .into_iter()
+            .min_by_key(|(rank, format)| {
+                (
+                    if self.rank_by_codec { *rank } else { 0 },
+                    self.prefer_drc
+                        .is_some_and(|prefer_drc| format.is_drc != prefer_drc),
+                    std::cmp::Reverse(format.bitrate),
+                )
+            })
+            .map(|(_, format)| format)
+    }
+
+    fn codec_rank(&self, format: &StreamFormat) -> Option<usize> {
+        let info = format.info?;
+        self.codec_preference
+            .iter()
+            .position(|preferred| *preferred == info)
+    }
+
+    fn within_caps(&self, format: &StreamFormat) -> bool {
+        self.max_bitrate.is_none_or(|max| format.bitrate <= max)
+            && self
+                .max_channels
+                .is_none_or(|max| format.audio_channels <= max)
+    }
+}
+
+/// Whether a format's language tag satisfies the preferred one
+///
+/// A preference without a region (`en`) accepts any regional variant (`en-US`).
+fn language_matches(format_language: &str, preferred: &str) -> bool {
+    format_language.eq_ignore_ascii_case(preferred)
+        || (!preferred.contains('-')
+            && format_language
+                .split('-')
+                .next()
+                .is_some_and(|primary| primary.eq_ignore_ascii_case(preferred)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(itag: u32, info: FormatInfo, bitrate: u64) -> StreamFormat {
+        StreamFormat {
+            info: Some(info),
+            content_type: info.mime_type().to_string(),
+            itag,
+            bitrate,
+            content_length: 0,
+            audio_channels: 2,
+            url: Url::parse(&format!("https://example.com/videoplayback?itag={itag}")).unwrap(),
+            n_parameter: None,
+            signature: None,
+            signature_key: None,
+            is_default_audio_track: true,
+            is_drc: false,
+            language: None,
         }
     }
+
+    fn selected(selector: &FormatSelector, formats: &[StreamFormat]) -> Option<u32> {
+        selector.select(formats).map(|format| format.itag)
+    }
+
+    #[test]
+    fn test_default_picks_highest_bitrate() {
+        let mut dubbed = format(251, FormatInfo::WebmOpus, 160_000);
+        dubbed.is_default_audio_track = false;
+        let mut unknown = format(18, FormatInfo::Mp4VideoAacLc, 500_000);
+        unknown.info = None;
+        let formats = vec![
+            format(251, FormatInfo::WebmOpus, 120_000),
+            format(140, FormatInfo::Mp4AacLc, 130_000),
+            dubbed,
+            unknown,
+        ];
+
+        assert_eq!(selected(&FormatSelector::default(), &formats), Some(140));
+    }
+
+    #[test]
+    fn test_codec_preference_beats_bitrate() {
+        let formats = vec![
+            format(140, FormatInfo::Mp4AacLc, 130_000),
+            format(251, FormatInfo::WebmOpus, 120_000),
+            format(250, FormatInfo::WebmOpus, 70_000),
+        ];
+
+        let by_codec = FormatSelector::new().set_rank_by_codec(true);
+        assert_eq!(selected(&by_codec, &formats), Some(251));
+        assert_eq!(
+            selected(
+                &FormatSelector::new().set_codec_preference(vec![FormatInfo::Mp4AacLc]),
+                &formats
+            ),
+            Some(140)
+        );
+        assert_eq!(
+            selected(
+                &FormatSelector::new().set_codec_preference(vec![FormatInfo::WebmVorbis]),
+                &formats
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_caps_are_soft() {
+        let mut surround = format(258, FormatInfo::Mp4AacLc, 384_000);
+        surround.audio_channels = 6;
+        let formats = vec![
+            surround,
+            format(251, FormatInfo::WebmOpus, 135_000),
+            format(250, FormatInfo::WebmOpus, 70_000),
+            format(139, FormatInfo::Mp4AacLc, 48_000),
+        ];
+
+        let capped = FormatSelector::new().set_max_bitrate(Some(100_000));
+        assert_eq!(selected(&capped, &formats), Some(250));
+
+        let stereo = FormatSelector::new().set_codec_preference(vec![FormatInfo::Mp4AacLc]);
+        assert_eq!(selected(&stereo, &formats), Some(258));
+        assert_eq!(
+            selected(&stereo.set_max_channels(Some(2)), &formats),
+            Some(139)
+        );
+
+        // Nothing fits, so the cheapest format is used
+        let tiny = FormatSelector::new().set_max_bitrate(Some(1_000));
+        assert_eq!(selected(&tiny, &formats), Some(139));
+    }
+
+    #[test]
+    fn test_drc_preference() {
+        let mut drc = format(251, FormatInfo::WebmOpus, 140_000);
+        drc.is_drc = true;
+        let formats = vec![drc, format(251, FormatInfo::WebmOpus, 135_000)];
+
+        // DRC is ignored by default, so the higher bitrate wins
+        let any = FormatSelector::default().select(&formats).unwrap();
+        assert!(any.is_drc);
+        let regular = FormatSelector::new()
+            .set_prefer_drc(false)
+            .select(&formats)
+            .unwrap();
+        assert!(!regular.is_drc);
+        let compressed = FormatSelector::new()
+            .set_prefer_drc(true)
+            .select(&formats)
+            .unwrap();
+        assert!(compressed.is_drc);
+    }
+
+    #[test]
+    fn test_preferred_language() {
+        let mut original = format(251, FormatInfo::WebmOpus, 135_000);
+        original.language = Some("en-US".to_string());
+        let mut dubbed = format(250, FormatInfo::WebmOpus, 70_000);
+        dubbed.language = Some("de-DE".to_string());
+        dubbed.is_default_audio_track = false;
+        let formats = vec![original, dubbed];
+
+        assert_eq!(selected(&FormatSelector::default(), &formats), Some(251));
+        let german = FormatSelector::new().set_preferred_language(Some("de".to_string()));
+        assert_eq!(selected(&german, &formats), Some(250));
+        let regional = FormatSelector::new().set_preferred_language(Some("de-AT".to_string()));
+        assert_eq!(selected(&regional, &formats), Some(251));
+    }
 }
@@ -16,45 +16,35 @@ pub struct UrlTools;
 
 impl UrlTools {
     /// Extract video ID from various YouTube URL formats
+    ///
+    /// Only URLs are accepted; use [`UrlTools::is_valid_video_id`] to check
+    /// for a bare video ID.
     pub fn extract_video_id(url: &str) -> Option<String> {
-        if let Ok(parsed_url) = Url::parse(url) {
-            // Handle different YouTube URL formats
-            match parsed_url.host_str() {
-                Some("www.youtube.com") | Some("youtube.com") => {
-                    if parsed_url.path() == "/watch" {
-                        // Standard watch URL: https://www.youtube.com/watch?v=VIDEO_ID
-                        parsed_url
-                            .query_pairs()
-                            .find(|(key, _)| key == "v")
-                            .map(|(_, value)| value.to_string())
-                    } else if parsed_url.path().starts_with("/embed/") {
-                        // Embed URL: https://www.youtube.com/embed/VIDEO_ID
-                        parsed_url
-                            .path()
-                            .strip_prefix("/embed/")
-                            .map(|id| id.to_string())
-                    } else {
-                        None
-                    }
-                }
-                Some("youtu.be") => {
-                    // Short URL: https://youtu.be/VIDEO_ID
-                    parsed_url.path().strip_prefix("/").map(|id| id.to_string())
+        let parsed_url = Url::parse(url).ok()?;
+
+        // Handle different YouTube URL formats
+        let video_id = match parsed_url.host_str()? {
+            "www.youtube.com" | "youtube.com" | "m.youtube.com" | "music.youtube.com" => {
+                if parsed_url.path() == "/watch" {
+                    // Standard watch URL: https://www.youtube.com/watch?v=VIDEO_ID
+                    parsed_url
+                        .query_pairs()
+                        .find(|(key, _)| key == "v")
+                        .map(|(_, value)| value.to_string())
+                } else {
+                    // Embed URL: https://www.youtube.com/embed/VIDEO_ID
+                    parsed_url
+                        .path()
+                        .strip_prefix("/embed/")
+                        .map(|id| id.to_string())
                 }
-                _ => None,
-            }
-        } else {
-            // Maybe it's just a video ID
-            if url.len() == 11
-                && url
-                    .chars()
-                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
-            {
-                Some(url.to_string())
-            } else {
-                None
             }
-        }
+            // Short URL: https://youtu.be/VIDEO_ID
+            "youtu.be" => parsed_url.path().strip_prefix('/').map(|id| id.to_string()),
+            _ => None,
+        }?;
+
+        Self::is_valid_video_id(&video_id).then_some(video_id)
     }
 
     /// Extract playlist ID from YouTube URL
@@ -74,7 +64,7 @@ impl UrlTools {
         id.len() == 11
             && id
                 .chars()
-                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
     }
 
     /// Check if a string is a valid YouTube playlist ID format
@@ -86,6 +76,9 @@ impl UrlTools {
             || id.starts_with("RD")
             || id.starts_with("LM"))
             && id.len() >= 10
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
     }
 
     /// Parse URL query parameters into a HashMap
@@ -190,9 +183,31 @@ mod tests {
         );
 
         assert_eq!(
-            UrlTools::extract_video_id("dQw4w9WgXcQ"),
+            UrlTools::extract_video_id("https://music.youtube.com/watch?v=dQw4w9WgXcQ"),
             Some("dQw4w9WgXcQ".to_string())
         );
+
+        // Bare IDs and malformed IDs are not URLs
+        assert_eq!(UrlTools::extract_video_id("dQw4w9WgXcQ"), None);
+        assert_eq!(UrlTools::extract_video_id("hello world"), None);
+        assert_eq!(
+            UrlTools::extract_video_id("https://www.youtube.com/watch?v=short"),
+            None
+        );
+        assert_eq!(
+            UrlTools::extract_video_id("https://example.com/watch?v=dQw4w9WgXcQ"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_valid_video_id() {
+        assert!(UrlTools::is_valid_video_id("dQw4w9WgXcQ"));
+        assert!(UrlTools::is_valid_video_id("a-b_c-d_e-f"));
+        assert!(!UrlTools::is_valid_video_id("helloworld"));
+        assert!(!UrlTools::is_valid_video_id("hello world"));
+        // 11 bytes, but only 10 characters with a non-ASCII letter
+        assert!(!UrlTools::is_valid_video_id("dQw4w9WgXé"));
     }
 
     #[test]
@@ -204,4 +219,13 @@ mod tests {
             Some("PLrAXtmRdnEQy4Qy9RBqOQQ1".to_string())
         );
     }
+
+    #[test]
+    fn test_is_valid_playlist_id() {
+        assert!(UrlTools::is_valid_playlist_id("PLrAXtmRdnEQy4Qy9RBqOQQ1"));
+        assert!(UrlTools::is_valid_playlist_id("RDdQw4w9WgXcQ"));
+        assert!(!UrlTools::is_valid_playlist_id("PLshort"));
+        assert!(!UrlTools::is_valid_playlist_id("PL never gonna give"));
+        assert!(!UrlTools::is_valid_playlist_id("dQw4w9WgXcQ"));
+    }
 }
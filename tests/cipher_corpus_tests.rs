@@ -0,0 +1,217 @@
+//! Regression harness over a corpus of player layouts
+//!
+//! The scripts in `tests/fixtures/layout_corpus` are hand-written, each one
+//! mimicking a player layout the extractors have to handle; they are not
+//! archived players. Every script goes through `ScriptParser`, the basic
+//! `SignatureCipher` and `AdvancedSignatureCipher`, and the results are
+//! compared with `expected.json`, which `generate_expected.js` computes by
+//! running the scripts themselves in node and which also names the strategy
+//! that should find each n function. Problems are collected per script so
+//! that a new layout shows every pattern it breaks at once.
+//!
+//! Real players archived in `tests/fixtures/archived_players`, with the
+//! signature and n samples yt-dlp publishes for them, are run through
+//! `AdvancedSignatureCipher` the same way once fetched.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use youtube_source_rs::cipher::{AdvancedSignatureCipher, ScriptParser, SignatureCipher};
+
+#[derive(Debug, Deserialize)]
+struct LayoutExpectation {
+    signature_timestamp: String,
+    /// Extraction steps known to fail on this layout
+    expected_failures: Vec<String>,
+    /// Strategy expected to find the n function
+    n_strategy: String,
+    signatures: Vec<Sample>,
+    n: Vec<Sample>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Sample {
+    input: String,
+    output: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArchivedPlayer {
+    #[serde(default)]
+    signatures: Vec<Sample>,
+    #[serde(default)]
+    n: Vec<Sample>,
+}
+
+fn corpus_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/layout_corpus")
+}
+
+fn expectations() -> BTreeMap<String, LayoutExpectation> {
+    serde_json::from_str(include_str!("fixtures/layout_corpus/expected.json")).unwrap()
+}
+
+/// Compare deciphered samples, noting each mismatch
+fn check_samples(
+    problems: &mut Vec<String>,
+    label: &str,
+    samples: &[Sample],
+    decipher: impl Fn(&str) -> youtube_source_rs::Result<String>,
+) {
+    for sample in samples {
+        match decipher(&sample.input) {
+            Ok(output) if output == sample.output => {}
+            Ok(output) => problems.push(format!(
+                "{label}({}) gave {output}, expected {}",
+                sample.input, sample.output
+            )),
+            Err(e) => problems.push(format!("{label}({}) failed: {e}", sample.input)),
+        }
+    }
+}
+
+/// Run one layout through extraction and both ciphers, returning its problems
+fn check_layout(script: &str, expected: &LayoutExpectation) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let failed = ScriptParser::failed_extractions(script);
+    for (step, error) in &failed {
+        if !expected
+            .expected_failures
+            .iter()
+            .any(|name| name == step.name())
+        {
+            problems.push(format!("{step} extraction failed: {error}"));
+        }
+    }
+    for name in &expected.expected_failures {
+        if !failed.iter().any(|(step, _)| step.name() == name) {
+            problems.push(format!(
+                "{name} extraction now succeeds, remove it from expected_failures"
+            ));
+        }
+    }
+
+    if let Ok(found) = ScriptParser::find_n_function(script) {
+        if found.strategy.name() != expected.n_strategy {
+            problems.push(format!(
+                "n function found with the {} strategy, expected {}",
+                found.strategy, expected.n_strategy
+            ));
+        }
+    }
+
+    if let Ok(timestamp) = ScriptParser::extract_timestamp(script) {
+        if timestamp != expected.signature_timestamp {
+            problems.push(format!(
+                "timestamp {timestamp} extracted, expected {}",
+                expected.signature_timestamp
+            ));
+        }
+    }
+
+    // The basic cipher only needs the sig actions and function
+    if let Ok(operations) = ScriptParser::extract_signature_operations(script) {
+        let cipher = SignatureCipher::new(operations);
+        check_samples(&mut problems, "basic sig", &expected.signatures, |input| {
+            cipher.decipher_signature(input)
+        });
+    }
+
+    if failed.is_empty() {
+        match AdvancedSignatureCipher::from_script(script) {
+            Ok(cipher) => {
+                check_samples(&mut problems, "sig", &expected.signatures, |input| {
+                    cipher.decipher_signature(input)
+                });
+                check_samples(&mut problems, "n", &expected.n, |input| {
+                    cipher.transform_n_parameter(input)
+                });
+            }
+            Err(e) => problems.push(format!("advanced cipher failed: {e}")),
+        }
+    }
+
+    problems
+}
+
+#[test]
+fn test_layout_corpus() {
+    let expected = expectations();
+    let mut scripts: Vec<String> = std::fs::read_dir(corpus_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".js") && name != "generate_expected.js")
+        .collect();
+    scripts.sort();
+    assert_eq!(
+        scripts,
+        expected.keys().cloned().collect::<Vec<_>>(),
+        "every corpus script needs an entry in expected.json"
+    );
+
+    let mut report = Vec::new();
+    for (name, expectation) in &expected {
+        let script = std::fs::read_to_string(corpus_dir().join(name)).unwrap();
+        for problem in check_layout(&script, expectation) {
+            report.push(format!("{name}: {problem}"));
+        }
+    }
+
+    assert!(
+        report.is_empty(),
+        "layout corpus regressions:\n{}",
+        report.join("\n")
+    );
+}
+
+#[test]
+fn test_harness_reports_broken_layout() {
+    let expected = expectations();
+    let script = std::fs::read_to_string(corpus_dir().join("split_lookup_table.js")).unwrap();
+
+    // A layout whose sig actions object is no longer declared with `var`
+    let broken = script.replace("var Ww={", "Ww={");
+    let problems = check_layout(&broken, &expected["split_lookup_table.js"]);
+
+    assert_eq!(problems.len(), 1, "{problems:?}");
+    assert!(problems[0].starts_with("sig_actions extraction failed"));
+}
+
+#[test]
+#[ignore] // Only run with --ignored flag once fetch_players.js fetched the players
+fn test_archived_players() {
+    let archive = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/archived_players");
+    let players: BTreeMap<String, ArchivedPlayer> =
+        serde_json::from_str(include_str!("fixtures/archived_players/players.json")).unwrap();
+
+    let mut report = Vec::new();
+    for (hash, player) in &players {
+        let script = std::fs::read_to_string(archive.join(format!("{hash}.js")))
+            .unwrap_or_else(|e| panic!("{hash}.js: {e}, run fetch_players.js first"));
+
+        let mut problems = Vec::new();
+        match AdvancedSignatureCipher::from_script(&script) {
+            Ok(cipher) => {
+                check_samples(&mut problems, "sig", &player.signatures, |input| {
+                    cipher.decipher_signature(input)
+                });
+                check_samples(&mut problems, "n", &player.n, |input| {
+                    cipher.transform_n_parameter(input)
+                });
+            }
+            Err(e) => problems.push(format!("advanced cipher failed: {e}")),
+        }
+        report.extend(
+            problems
+                .into_iter()
+                .map(|problem| format!("{hash}: {problem}")),
+        );
+    }
+
+    assert!(
+        report.is_empty(),
+        "archived player regressions:\n{}",
+        report.join("\n")
+    );
+}
@@ -195,6 +195,101 @@ fn test_cipher_performance_characteristics() {
     );
 }
 
+/// Hand-written sig actions layouts with signatures deciphered by running the
+/// script itself in node; archived players are checked by
+/// `test_signature_operations_match_archived_players` (see `tests/fixtures/sig_layouts/generate_expected.js`)
+#[test]
+fn test_signature_operations_match_sig_layouts() {
+    use youtube_source_rs::cipher::{ScriptParser, SignatureCipher};
+
+    let layouts =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sig_layouts");
+    let expected: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/sig_layouts/expected.json")).unwrap();
+    let expected = expected.as_object().unwrap();
+    assert!(expected.len() >= 4, "expected several sig layouts");
+
+    for (file, samples) in expected {
+        let script = std::fs::read_to_string(layouts.join(file)).unwrap();
+        let operations = ScriptParser::extract_signature_operations(&script)
+            .unwrap_or_else(|e| panic!("{file}: {e}"));
+        let cipher = SignatureCipher::new(operations);
+
+        for sample in samples.as_array().unwrap() {
+            assert_eq!(
+                cipher
+                    .decipher_signature(sample["input"].as_str().unwrap())
+                    .unwrap(),
+                sample["output"].as_str().unwrap(),
+                "{file}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_signature_operations_from_sig_layout() {
+    use youtube_source_rs::cipher::{CipherOperation, ScriptParser};
+
+    let script = include_str!("fixtures/sig_layouts/splice_swap_reverse.js");
+    assert_eq!(
+        ScriptParser::extract_signature_operations(script).unwrap(),
+        vec![
+            CipherOperation::Swap(43),
+            CipherOperation::Slice(2),
+            CipherOperation::Reverse,
+            CipherOperation::Swap(23),
+            CipherOperation::Slice(1),
+            CipherOperation::Reverse,
+        ]
+    );
+
+    let legacy = include_str!("fixtures/sig_layouts/legacy_slice.js");
+    assert_eq!(
+        ScriptParser::extract_signature_operations(legacy).unwrap()[..2],
+        [CipherOperation::Slice(2), CipherOperation::Reverse]
+    );
+}
+
+/// Signatures deciphered by real archived players, as published with
+/// yt-dlp's signature tests (see `tests/fixtures/archived_players`)
+#[test]
+#[ignore] // Only run with --ignored flag once fetch_players.js fetched the players
+fn test_signature_operations_match_archived_players() {
+    use youtube_source_rs::cipher::{ScriptParser, SignatureCipher};
+
+    let players =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/archived_players");
+    let expected: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/archived_players/players.json")).unwrap();
+
+    let mut problems = Vec::new();
+    for (hash, player) in expected.as_object().unwrap() {
+        let Some(samples) = player["signatures"].as_array() else {
+            continue;
+        };
+        let script = std::fs::read_to_string(players.join(format!("{hash}.js")))
+            .unwrap_or_else(|e| panic!("{hash}.js: {e}, run fetch_players.js first"));
+        let operations = match ScriptParser::extract_signature_operations(&script) {
+            Ok(operations) => operations,
+            Err(e) => {
+                problems.push(format!("{hash}: {e}"));
+                continue;
+            }
+        };
+        let cipher = SignatureCipher::new(operations);
+
+        for sample in samples {
+            let output = cipher.decipher_signature(sample["input"].as_str().unwrap());
+            if output.as_deref().ok() != sample["output"].as_str() {
+                problems.push(format!("{hash}: deciphered {output:?}"));
+            }
+        }
+    }
+
+    assert!(problems.is_empty(), "{}", problems.join("\n"));
+}
+
 #[cfg(feature = "mock-testing")]
 mod mock_cipher_tests {
     use wiremock::matchers::{method, path};
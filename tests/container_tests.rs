@@ -0,0 +1,107 @@
+//! Demuxer tests over the checked-in container fixtures
+//!
+//! The MP4 fixtures are produced by `tests/fixtures/mp4/generate.py`.
+
+use std::io::Cursor;
+use std::time::Duration;
+use youtube_source_rs::container::Mp4Demuxer;
+use youtube_source_rs::YoutubeError;
+
+const AAC_AUDIO: &[u8] = include_bytes!("fixtures/mp4/aac_audio.mp4");
+const AAC_MUXED: &[u8] = include_bytes!("fixtures/mp4/aac_muxed.mp4");
+
+const FRAMES_PER_FRAGMENT: u64 = 43;
+const FRAGMENTS: u64 = 3;
+
+fn frame_time(index: u64) -> Duration {
+    Duration::from_nanos(index * 1024 * 1_000_000_000 / 44100)
+}
+
+async fn collect(demuxer: &mut Mp4Demuxer<Cursor<&'static [u8]>>) -> Vec<(Duration, Vec<u8>)> {
+    let mut packets = Vec::new();
+    while let Some(packet) = demuxer.next_packet().await.unwrap() {
+        packets.push((packet.timestamp, packet.data));
+    }
+    packets
+}
+
+/// Assert packets are consecutive fixture frames starting at `first`
+fn assert_frames(packets: &[(Duration, Vec<u8>)], first: u64) {
+    for (index, (timestamp, data)) in (first..).zip(packets) {
+        assert_eq!(*timestamp, frame_time(index), "timestamp of frame {index}");
+        assert_eq!(data.len() as u64, 20 + index % 13, "size of frame {index}");
+        assert!(data.iter().all(|byte| u64::from(*byte) == index & 0xFF));
+    }
+}
+
+#[tokio::test]
+async fn test_mp4_parses_track_and_segment_index() {
+    let demuxer = Mp4Demuxer::open(Cursor::new(AAC_AUDIO)).await.unwrap();
+
+    let track = demuxer.track();
+    assert_eq!(track.track_id, 1);
+    assert_eq!(track.timescale, 44100);
+    assert_eq!(track.sample_rate, 44100);
+    assert_eq!(track.channels, 2);
+    assert_eq!(track.decoder_config, vec![0x12, 0x10]);
+    assert_eq!(track.audio_object_type(), Some(2));
+
+    let segments = demuxer.segments();
+    assert_eq!(segments.len(), FRAGMENTS as usize);
+    assert_eq!(segments[0].timestamp, Duration::ZERO);
+    assert_eq!(segments[1].timestamp, frame_time(FRAMES_PER_FRAGMENT));
+    assert_eq!(segments[0].offset + segments[0].size, segments[1].offset);
+    assert_eq!(
+        demuxer.duration(),
+        Some(frame_time(FRAMES_PER_FRAGMENT * FRAGMENTS))
+    );
+}
+
+#[tokio::test]
+async fn test_mp4_reads_access_units_in_order() {
+    let mut demuxer = Mp4Demuxer::open(Cursor::new(AAC_AUDIO)).await.unwrap();
+
+    let packets = collect(&mut demuxer).await;
+    assert_eq!(packets.len() as u64, FRAMES_PER_FRAGMENT * FRAGMENTS);
+    assert_frames(&packets, 0);
+}
+
+#[tokio::test]
+async fn test_mp4_seeks_with_segment_index() {
+    let mut demuxer = Mp4Demuxer::open(Cursor::new(AAC_AUDIO)).await.unwrap();
+
+    let target = Duration::from_millis(1500);
+    let segment_start = demuxer.seek(target).await.unwrap();
+    assert_eq!(segment_start, frame_time(FRAMES_PER_FRAGMENT));
+
+    let packets = collect(&mut demuxer).await;
+    let first = (0..).find(|index| frame_time(*index) >= target).unwrap();
+    assert_eq!(
+        packets.len() as u64,
+        FRAMES_PER_FRAGMENT * FRAGMENTS - first
+    );
+    assert_frames(&packets, first);
+
+    // Seeking back to the start replays everything
+    assert_eq!(demuxer.seek(Duration::ZERO).await.unwrap(), Duration::ZERO);
+    let packets = collect(&mut demuxer).await;
+    assert_eq!(packets.len() as u64, FRAMES_PER_FRAGMENT * FRAGMENTS);
+}
+
+#[tokio::test]
+async fn test_mp4_skips_video_track_of_muxed_stream() {
+    let mut demuxer = Mp4Demuxer::open(Cursor::new(AAC_MUXED)).await.unwrap();
+
+    assert_eq!(demuxer.tracks().len(), 1);
+    assert_eq!(demuxer.track().track_id, 2);
+
+    let packets = collect(&mut demuxer).await;
+    assert_eq!(packets.len() as u64, FRAMES_PER_FRAGMENT * FRAGMENTS);
+    assert_frames(&packets, 0);
+
+    // No sidx, so there is nothing to seek with
+    assert!(matches!(
+        demuxer.seek(Duration::from_secs(1)).await,
+        Err(YoutubeError::UnsupportedOperation(_))
+    ));
+}
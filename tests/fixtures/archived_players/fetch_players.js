@@ -0,0 +1,26 @@
+// Downloads the archived players listed in players.json next to it, as
+// <hash>.js, skipping those already present. Run with
+// `node fetch_players.js`, then check the scripts in.
+//
+// The players are real base.js files YouTube still serves under their hash.
+// Their signature and n samples are the ones published with yt-dlp's
+// test/test_youtube_signature.py, so they come from neither this crate nor
+// the scripts' own extraction.
+const fs = require("fs");
+const path = require("path");
+
+const players = JSON.parse(fs.readFileSync(path.join(__dirname, "players.json"), "utf8"));
+
+(async () => {
+  for (const [hash, player] of Object.entries(players)) {
+    const file = path.join(__dirname, `${hash}.js`);
+    if (fs.existsSync(file)) continue;
+
+    const response = await fetch(player.url);
+    if (!response.ok) {
+      throw new Error(`${player.url} answered ${response.status}`);
+    }
+    fs.writeFileSync(file, await response.text());
+    console.log(`Fetched ${hash}`);
+  }
+})();
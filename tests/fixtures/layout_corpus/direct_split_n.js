@@ -0,0 +1,8 @@
+var _yt_player={};(function(g){var window=this;'use strict';var Z="reverse;length;splice;enhanced_except_;join;push;shift".split(";"),ha,Qo,Tl,Rn;g.Ct=function(a,b){return a.length>b?a.slice(0,b):a};var AH={signatureTimestamp:20214,clientName:"WEB"};
+var Qo={dg:function(a,b){a.splice(0,b)},
+mu:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c},
+Pz:function(a){a.reverse()}};
+Tl=function(a){a=a.split("");Qo.Pz(a,2);Qo.mu(a,17);Qo.dg(a,1);Qo.mu(a,44);Qo.Pz(a,6);Qo.dg(a,2);return a.join("")};
+g.Ul=function(a,b){var c=Tl(b);a.set("sig",c);return a};
+Rn=function(a){var b=a.split(""),c=[function(d){d[Z[0]]()},function(d,e){e=(e%d[Z[1]]+d[Z[1]])%d[Z[1]];d[Z[2]](e,1)},function(d){d[Z[5]](d[Z[6]]())},11,-4];try{c[1](b,c[3]);c[0](b);c[2](b);c[1](b,c[4])}catch(d){return Z[3]+a}return b[Z[4]]("")};
+g.Sn=function(a){var b=a.get("n");b&&(b=Rn(b),a.set("n",b));return a};})(_yt_player);
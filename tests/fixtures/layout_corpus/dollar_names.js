@@ -0,0 +1,8 @@
+var _yt_player={};(function(g){var window=this;'use strict';var $L=["","split","length","push","shift","enhanced_except_","join","unshift"],ha,$w,$Q,Ub$;g.Ct=function(a,b){return a.length>b?a.slice(0,b):a};var AH={signatureTimestamp:20073,clientName:"WEB"};
+var $w={$q:function(a,b){a.splice(0,b)},
+xY:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c},
+k$:function(a){a.reverse()}};
+$Q=function(a){a=a.split("");$w.xY(a,9);$w.k$(a,41);$w.$q(a,3);$w.xY(a,60);$w.k$(a,12);return a.join("")};
+g.aRa=function(a,b){var c=$Q(b);a.set("sig",c);return a};
+Ub$=function(a){var b=a[$L[1]]($L[0]),c=[function(d,e){for(e=(e%d[$L[2]]+d[$L[2]])%d[$L[2]];e--;)d[$L[7]](d.pop())},function(d){d[$L[3]](d[$L[4]]())},-5,3];if(typeof AH==="undefined")return a;try{c[0](b,c[2]);c[1](b);c[0](b,c[3]);c[1](b);c[1](b)}catch(d){return $L[5]+a}return b[$L[6]]($L[0])};
+g.Vb$=function(a){var b=a.get("n");b&&(b=Ub$(b),a.set("n",b));return a};})(_yt_player);
@@ -0,0 +1,48 @@
+// Regenerates expected.json by running each corpus script in node.
+//
+// The scripts are hand-written, not archived players or excerpts of them. Each
+// one mimics a player layout the extractors have to handle (a global lookup
+// table, sig actions object, signature function and n function, minified
+// inside the `_yt_player` wrapper) and is named after that layout. Signature
+// and n outputs come from node running the script, so they are independent of
+// the Rust extraction, but passing says nothing about current players; real
+// ones are checked against ../archived_players. Run with
+// `node generate_expected.js`.
+const fs = require("fs");
+const path = require("path");
+
+// Function names per script, the extraction steps ScriptParser is known to
+// fail on, and the strategy expected to find the n function. A layout that
+// starts or stops failing a step, or moves to another strategy, is then
+// reported by the harness until this list is updated.
+const LAYOUTS = {
+  "split_lookup_table.js": { sig: "Sza", n: "Xma", expectedFailures: [], nStrategy: "regex" },
+  "dollar_names.js": { sig: "$Q", n: "Ub$", expectedFailures: [], nStrategy: "regex" },
+  // n function splits its argument directly instead of through the lookup table
+  "direct_split_n.js": { sig: "Tl", n: "Rn", expectedFailures: [], nStrategy: "structural" },
+  // n function reads a table declared after an unrelated one
+  "late_lookup_table.js": { sig: "Kb", n: "Hb", expectedFailures: [], nStrategy: "inlined_globals" },
+};
+
+const SIGNATURES = [
+  "AOq0QJ8wRAIgXmPlOPSBkkUs1bYFYlJCfe29xx8j7v1pDL0QwbdV96sCIEzpWqMGkFR20CFOg51Tp-7vj_EMu-m37KtXJ2OySqa0q",
+  "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghij",
+];
+const N_VALUES = ["Kt7cVQGZq4OnNqyP", "0aT5-Xk_bC3dEf9"];
+
+const corpus = {};
+for (const [file, names] of Object.entries(LAYOUTS)) {
+  const script = fs.readFileSync(path.join(__dirname, file), "utf8");
+  const body = script
+    .replace(/^var _yt_player=\{\};\(function\(g\)\{/, "")
+    .replace(/\}\)\(_yt_player\);\s*$/, "");
+  const player = new Function("g", `${body};return {sig: ${names.sig}, n: ${names.n}};`).call({}, {});
+  corpus[file] = {
+    signature_timestamp: script.match(/signatureTimestamp:(\d+)/)[1],
+    expected_failures: names.expectedFailures,
+    n_strategy: names.nStrategy,
+    signatures: SIGNATURES.map((input) => ({ input, output: player.sig(input) })),
+    n: N_VALUES.map((input) => ({ input, output: player.n(input) })),
+  };
+}
+fs.writeFileSync(path.join(__dirname, "expected.json"), JSON.stringify(corpus, null, 2) + "\n");
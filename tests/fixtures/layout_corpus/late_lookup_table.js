@@ -0,0 +1,8 @@
+var _yt_player={};(function(g){var window=this;'use strict';var Jq="en de fr".split(" ");var ha=null,$z=["","split","reverse","length","splice","enhanced_except_","join","push","shift"],Ov,Kb,Hb;g.Ct=function(a,b){return a.length>b?a.slice(0,b):a};var AH={signatureTimestamp:20377,clientName:"WEB"};
+var Ov={fX:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c},
+Rq:function(a){a.reverse()},
+y7:function(a,b){a.splice(0,b)}};
+Kb=function(a){a=a.split("");Ov.Rq(a,31);Ov.fX(a,9);Ov.y7(a,3);Ov.fX(a,52);Ov.Rq(a,4);return a.join("")};
+g.Lb=function(a,b){var c=Kb(b);a.set("sig",c);return a};
+Hb=function(a){var b=a[$z[1]]($z[0]),c=[function(d){d[$z[2]]()},function(d,e){e=(e%d[$z[3]]+d[$z[3]])%d[$z[3]];d[$z[4]](e,1)},function(d){d[$z[7]](d[$z[8]]())},5,-9];try{c[0](b);c[1](b,c[3]);c[2](b);c[1](b,c[4])}catch(d){return $z[5]+a}return b[$z[6]]($z[0])};
+g.Ib=function(a){var b=a.get("n");b&&(b=Hb(b),a.set("n",b));return a};})(_yt_player);
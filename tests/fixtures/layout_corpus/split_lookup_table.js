@@ -0,0 +1,8 @@
+var _yt_player={};(function(g){var window=this;'use strict';var L="|split|reverse|length|x|splice|enhanced_except_|join".split("|"),ha,ka,Ww,Sza,Xma;g.Ct=function(a,b){return a.length>b?a.slice(0,b):a};var AH={signatureTimestamp:19834,clientName:"WEB"};
+var Ww={Yy:function(a){a.reverse()},
+oN:function(a,b){a.splice(0,b)},
+Iw:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+Sza=function(a){a=a.split("");Ww.Iw(a,43);Ww.oN(a,2);Ww.Yy(a,7);Ww.Iw(a,23);Ww.oN(a,1);Ww.Yy(a,59);return a.join("")};
+g.Tza=function(a,b){var c=Sza(b);a.set("sig",c);return a};
+Xma=function(a){var b=a[L[1]](L[0]),c=[L[4],-12,function(d){d[L[2]]()},function(d,e){e=(e%d[L[3]]+d[L[3]])%d[L[3]];d[L[5]](e,1)},7];try{c[2](b);c[3](b,c[1]);c[3](b,c[4]);c[2](b)}catch(d){return L[6]+a}return b[L[7]](L[0])};
+g.Yma=function(a){var b=a.get("n");b&&(b=Xma(b),a.set("n",b));return a};})(_yt_player);
@@ -0,0 +1,183 @@
+#!/usr/bin/env python3
+"""Generate the fragmented MP4 fixtures used by the MP4 demuxer tests.
+
+Sample payloads are synthetic: sample `i` of a track is `size(i)` bytes of
+`i & 0xFF`, so tests can check both ordering and boundaries.
+
+- aac_audio.mp4: AAC-LC only, one fragment per second and a `sidx` index,
+  shaped like YouTube's itag 140 streams.
+- aac_muxed.mp4: a video track interleaved with AAC-LC in every fragment,
+  with no `sidx`, shaped like the muxed Mp4VideoAacLc formats.
+"""
+import struct
+from pathlib import Path
+
+TIMESCALE = 44100
+FRAME_DURATION = 1024
+FRAMES_PER_FRAGMENT = 43
+FRAGMENTS = 3
+# AudioSpecificConfig: AAC-LC, 44.1 kHz, stereo
+AUDIO_SPECIFIC_CONFIG = bytes([0x12, 0x10])
+
+
+def box(kind, *payload):
+    body = b"".join(payload)
+    return struct.pack(">I4s", 8 + len(body), kind) + body
+
+
+def full_box(kind, version, flags, *payload):
+    return box(kind, struct.pack(">I", (version << 24) | flags), *payload)
+
+
+def descriptor(tag, body):
+    return bytes([tag, len(body)]) + body
+
+
+def audio_size(index):
+    return 20 + index % 13
+
+
+def video_size(index):
+    return 40 + index % 5
+
+
+def payload(index, size):
+    return bytes([index & 0xFF]) * size
+
+
+def trak(track_id, handler, sample_entry):
+    tkhd = full_box(b"tkhd", 0, 3, struct.pack(">IIII", 0, 0, track_id, 0), bytes(60))
+    mdhd = full_box(b"mdhd", 0, 0, struct.pack(">IIIIHH", 0, 0, TIMESCALE, 0, 0x55C4, 0))
+    hdlr = full_box(b"hdlr", 0, 0, struct.pack(">I4s", 0, handler), bytes(12), b"\0")
+    stsd = full_box(b"stsd", 0, 0, struct.pack(">I", 1), sample_entry)
+    empty_tables = [
+        full_box(b"stts", 0, 0, struct.pack(">I", 0)),
+        full_box(b"stsc", 0, 0, struct.pack(">I", 0)),
+        full_box(b"stsz", 0, 0, struct.pack(">II", 0, 0)),
+        full_box(b"stco", 0, 0, struct.pack(">I", 0)),
+    ]
+    stbl = box(b"stbl", stsd, *empty_tables)
+    return box(b"trak", tkhd, box(b"mdia", mdhd, hdlr, box(b"minf", stbl)))
+
+
+def mp4a():
+    es = descriptor(
+        0x03,
+        struct.pack(">HB", 1, 0)
+        + descriptor(
+            0x04,
+            struct.pack(">BB", 0x40, 0x15) + bytes(3) + struct.pack(">II", 128000, 128000)
+            + descriptor(0x05, AUDIO_SPECIFIC_CONFIG),
+        )
+        + descriptor(0x06, b"\x02"),
+    )
+    esds = full_box(b"esds", 0, 0, es)
+    entry = bytes(6) + struct.pack(">H", 1) + bytes(8) + struct.pack(">HHI", 2, 16, 0)
+    entry += struct.pack(">I", TIMESCALE << 16)
+    return box(b"mp4a", entry, esds)
+
+
+def avc1():
+    entry = bytes(6) + struct.pack(">H", 1) + bytes(16) + struct.pack(">HH", 64, 64)
+    entry += bytes(50)
+    return box(b"avc1", entry)
+
+
+def moov(tracks):
+    mvhd = full_box(b"mvhd", 0, 0, struct.pack(">IIII", 0, 0, 1000, 0), bytes(80))
+    traks = [trak(track_id, handler, entry) for track_id, handler, entry in tracks]
+    trexes = [
+        full_box(b"trex", 0, 0, struct.pack(">IIIII", track_id, 1, FRAME_DURATION, 0, 0))
+        for track_id, _, _ in tracks
+    ]
+    return box(b"moov", mvhd, *traks, box(b"mvex", *trexes))
+
+
+def ftyp():
+    return box(b"ftyp", b"dash", struct.pack(">I", 0), b"iso6avc1mp41")
+
+
+def traf(track_id, base_time, sizes, data_offset, base_is_moof):
+    tfhd_flags = 0x20000 if base_is_moof else 0
+    tfhd = full_box(b"tfhd", 0, tfhd_flags, struct.pack(">I", track_id))
+    tfdt = full_box(b"tfdt", 1, 0, struct.pack(">Q", base_time))
+    # data-offset-present | sample-size-present
+    trun = full_box(
+        b"trun",
+        0,
+        0x201,
+        struct.pack(">Ii", len(sizes), data_offset),
+        *(struct.pack(">I", size) for size in sizes),
+    )
+    return box(b"traf", tfhd, tfdt, trun)
+
+
+def fragment(sequence, tracks, base_is_moof):
+    """tracks: list of (track_id, base_time, [payloads])"""
+
+    def build(offsets):
+        trafs = [
+            traf(track_id, base_time, [len(p) for p in payloads], offset, base_is_moof)
+            for (track_id, base_time, payloads), offset in zip(tracks, offsets)
+        ]
+        return box(b"moof", full_box(b"mfhd", 0, 0, struct.pack(">I", sequence)), *trafs)
+
+    # Offsets are relative to the moof start; its size does not depend on them
+    moof_size = len(build([0] * len(tracks)))
+    offsets, position = [], moof_size + 8
+    for _, _, payloads in tracks:
+        offsets.append(position)
+        position += sum(len(p) for p in payloads)
+    mdat = box(b"mdat", *(p for _, _, payloads in tracks for p in payloads))
+    return build(offsets) + mdat
+
+
+def sidx(fragments):
+    refs = b"".join(
+        struct.pack(">III", len(data), FRAMES_PER_FRAGMENT * FRAME_DURATION, 0x90000000)
+        for data in fragments
+    )
+    return full_box(
+        b"sidx", 0, 0, struct.pack(">IIIIHH", 1, TIMESCALE, 0, 0, 0, len(fragments)), refs
+    )
+
+
+def audio_payloads(fragment_index):
+    first = fragment_index * FRAMES_PER_FRAGMENT
+    return [payload(i, audio_size(i)) for i in range(first, first + FRAMES_PER_FRAGMENT)]
+
+
+def audio_only():
+    fragments = [
+        fragment(
+            n + 1,
+            [(1, n * FRAMES_PER_FRAGMENT * FRAME_DURATION, audio_payloads(n))],
+            base_is_moof=True,
+        )
+        for n in range(FRAGMENTS)
+    ]
+    header = ftyp() + moov([(1, b"soun", mp4a())])
+    return header + sidx(fragments) + b"".join(fragments)
+
+
+def muxed():
+    fragments = []
+    for n in range(FRAGMENTS):
+        base_time = n * FRAMES_PER_FRAGMENT * FRAME_DURATION
+        first = n * 4
+        video = [payload(i, video_size(i)) for i in range(first, first + 4)]
+        fragments.append(
+            fragment(
+                n + 1,
+                [(1, base_time, video), (2, base_time, audio_payloads(n))],
+                base_is_moof=True,
+            )
+        )
+    header = ftyp() + moov([(1, b"vide", avc1()), (2, b"soun", mp4a())])
+    return header + b"".join(fragments)
+
+
+if __name__ == "__main__":
+    directory = Path(__file__).parent
+    (directory / "aac_audio.mp4").write_bytes(audio_only())
+    (directory / "aac_muxed.mp4").write_bytes(muxed())
@@ -0,0 +1,6 @@
+var _yt_player={};(function(g){var window=this;'use strict';var $F={signatureTimestamp:19953};g.$G=function(){return null};
+var $x={t$:function(a,b){a.splice(0,b)},
+Z$:function(a){a.reverse()},
+m_:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+q$a=function(a){a=a.split("");$x.Z$(a,74);$x.m_(a,9);$x.t$(a,3);$x.m_(a,68);$x.Z$(a,31);$x.t$(a,2);$x.m_(a,40);return a.join("")};
+g.r$a=function(a){return q$a(a)};})(_yt_player);
@@ -0,0 +1,29 @@
+// Regenerates expected.json by running each script's signature function in node.
+//
+// The scripts are hand-written, not cut from real players: each one mimics a
+// minified sig actions layout (operation order, `$` names, slice instead of
+// splice) inside the `_yt_player` wrapper. The outputs come from node
+// evaluating the same script, so they check that the Rust operations match
+// the JavaScript they were parsed from, not that the layouts match current
+// players; real ones are checked against ../archived_players. Run with
+// `node generate_expected.js`.
+const fs = require("fs");
+const path = require("path");
+
+const SAMPLES = [
+  "AOq0QJ8wRAIgXmPlOPSBkkUs1bYFYlJCfe29xx8j7v1pDL0QwbdV96sCIEzpWqMGkFR20CFOg51Tp-7vj_EMu-m37KtXJ2OySqa0q",
+  "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghij",
+];
+
+const fixtures = {};
+for (const file of fs.readdirSync(__dirname).filter((f) => f.endsWith(".js")).sort()) {
+  if (file === path.basename(__filename)) continue;
+  const script = fs.readFileSync(path.join(__dirname, file), "utf8");
+  const name = script.match(/\n([$\w]+)=function\(a\)\{a=a\.split\(""\)/)[1];
+  const body = script
+    .replace(/^var _yt_player=\{\};\(function\(g\)\{/, "")
+    .replace(/\}\)\(_yt_player\);\s*$/, "");
+  const sig = new Function("g", `${body};return ${name};`).call({}, {});
+  fixtures[file] = SAMPLES.map((input) => ({ input, output: sig(input) }));
+}
+fs.writeFileSync(path.join(__dirname, "expected.json"), JSON.stringify(fixtures, null, 2) + "\n");
@@ -0,0 +1,6 @@
+var _yt_player={};(function(g){var window=this;g.qA=function(a){return a};var yt={sts:16815};
+var Qo={mu:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b]=c},
+Ko:function(a){return a.reverse()},
+Ex:function(a,b){return a.slice(b)}};
+lj=function(a){a=a.split("");a=Qo.Ex(a,2);a=Qo.Ko(a,38);Qo.mu(a,17);a=Qo.Ex(a,3);Qo.mu(a,8);return a.join("")};
+g.mj=function(a){return lj(a)};})(_yt_player);
@@ -0,0 +1,6 @@
+var _yt_player={};(function(g){var window=this;'use strict';var ha,ka,la;g.Ct=function(a,b){return a.length>b?a.slice(0,b):a};var AH={signatureTimestamp:18610,clientName:"WEB"};
+var Ww={Yy:function(a){a.reverse()},
+oN:function(a,b){a.splice(0,b)},
+Iw:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+Sza=function(a){a=a.split("");Ww.Iw(a,43);Ww.oN(a,2);Ww.Yy(a,7);Ww.Iw(a,23);Ww.oN(a,1);Ww.Yy(a,59);return a.join("")};
+g.Tza=function(a,b){var c=Sza(b);a.set("sig",c);return a};})(_yt_player);
@@ -0,0 +1,4 @@
+var _yt_player={};(function(g){var window=this;'use strict';g.Yb=function(a){return a};var config={sts:19219,lang:"en"};
+var LE={Ld:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c},Kg:function(a){a.reverse()},GS:function(a,b){a.splice(0,b)}};
+ugb=function(a){a=a.split("");LE.GS(a,3);LE.Ld(a,64);LE.GS(a,1);LE.Kg(a,46);LE.Ld(a,36);LE.Ld(a,51);LE.Kg(a,11);return a.join("")};
+g.vgb=function(a){return ugb(a)};})(_yt_player);
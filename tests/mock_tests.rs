@@ -405,3 +405,1572 @@ mod mock_youtube_api_tests {
         assert!(script_content.contains("slice"));
     }
 }
+
+#[cfg(feature = "mock-testing")]
+mod mock_music_client_tests {
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tokio::io::AsyncReadExt;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use youtube_source_rs::cipher::SignatureCipherManager;
+    use youtube_source_rs::client::{MusicClient, TvClient};
+    use youtube_source_rs::http::{
+        ProxyAssignment, ProxyPool, RateLimit, RateLimiter, YoutubeHttpClient,
+    };
+    use youtube_source_rs::track::FormatInfo;
+    use youtube_source_rs::{
+        AudioItem, Client, ClientOptions, FailureSeverity, FormatSelector, ProxyConfig,
+        YoutubeAudioSourceManager, YoutubeError, YoutubeSourceOptions,
+    };
+
+    fn fixture(name: &str) -> Value {
+        let raw = match name {
+            "search" => include_str!("fixtures/music/search.json"),
+            "player" => include_str!("fixtures/music/player.json"),
+            "playlist" => include_str!("fixtures/music/playlist.json"),
+            "playlist_continuation" => include_str!("fixtures/music/playlist_continuation.json"),
+            "next" => include_str!("fixtures/music/next.json"),
+            "unplayable" => include_str!("fixtures/music/unplayable.json"),
+            _ => panic!("unknown fixture {name}"),
+        };
+        serde_json::from_str(raw).unwrap()
+    }
+
+    async fn mock_endpoint(server: &MockServer, endpoint: &str, body: Value) {
+        Mock::given(method("POST"))
+            .and(path(format!("/youtubei/v1/{endpoint}")))
+            .and(header("referer", "https://music.youtube.com/"))
+            .and(body_partial_json(
+                json!({"context": {"client": {"clientName": "WEB_REMIX"}}}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    /// Keep player script lookups on the mock server, which has no player
+    fn offline_player(
+        mut manager: YoutubeAudioSourceManager,
+        server: &MockServer,
+    ) -> YoutubeAudioSourceManager {
+        manager.cipher_manager =
+            std::sync::Arc::new(SignatureCipherManager::new().with_base_url(server.uri()));
+        manager
+    }
+
+    fn music_client(server: &MockServer) -> MusicClient {
+        MusicClient::new().with_api_base_url(format!("{}/youtubei/v1", server.uri()))
+    }
+
+    #[tokio::test]
+    async fn test_music_search_parses_shelf() {
+        let server = MockServer::start().await;
+        mock_endpoint(&server, "search", fixture("search")).await;
+
+        let manager = offline_player(YoutubeAudioSourceManager::new(), &server);
+        let item = music_client(&server)
+            .search(&manager, "never gonna give you up")
+            .await
+            .unwrap();
+
+        let Some(AudioItem::SearchResult(result)) = item else {
+            panic!("expected a search result, got {item:?}");
+        };
+
+        // The artist entry in the second shelf is not a track
+        assert_eq!(result.tracks.len(), 2);
+
+        let first = &result.tracks[0].info;
+        assert_eq!(first.video_id, "lYBUbBu4W08");
+        assert_eq!(first.title, "Never Gonna Give You Up");
+        assert_eq!(first.author, "Rick Astley");
+        assert_eq!(first.duration, Duration::from_secs(214));
+        assert_eq!(
+            first.artwork_url.as_deref(),
+            Some("https://lh3.googleusercontent.com/never-gonna=w120-h120")
+        );
+
+        assert_eq!(result.tracks[1].info.duration, Duration::from_secs(3805));
+    }
+
+    #[tokio::test]
+    async fn test_music_search_no_matches() {
+        let server = MockServer::start().await;
+        mock_endpoint(&server, "search", json!({"contents": {}})).await;
+
+        let manager = offline_player(YoutubeAudioSourceManager::new(), &server);
+        let item = music_client(&server)
+            .search(&manager, "nothing")
+            .await
+            .unwrap();
+
+        assert!(item.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_music_load_video() {
+        let server = MockServer::start().await;
+        mock_endpoint(&server, "player", fixture("player")).await;
+
+        let manager = offline_player(YoutubeAudioSourceManager::new(), &server);
+        let item = music_client(&server)
+            .load_video(&manager, "lYBUbBu4W08")
+            .await
+            .unwrap();
+
+        let Some(AudioItem::Track(track)) = item else {
+            panic!("expected a track, got {item:?}");
+        };
+
+        assert_eq!(track.info.title, "Never Gonna Give You Up");
+        assert_eq!(track.info.duration, Duration::from_secs(214));
+        assert_eq!(
+            track.info.artwork_url.as_deref(),
+            Some("https://lh3.googleusercontent.com/never-gonna=w544-h544")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_music_load_video_unplayable() {
+        let server = MockServer::start().await;
+        mock_endpoint(&server, "player", fixture("unplayable")).await;
+
+        let manager = offline_player(YoutubeAudioSourceManager::new(), &server);
+        let err = music_client(&server)
+            .load_video(&manager, "lYBUbBu4W08")
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("Sign in to confirm your age"));
+    }
+
+    #[tokio::test]
+    async fn test_manager_reports_structured_load_failure() {
+        let server = MockServer::start().await;
+        mock_endpoint(&server, "player", fixture("unplayable")).await;
+
+        let manager = offline_player(
+            YoutubeAudioSourceManager::with_options_and_clients(
+                YoutubeSourceOptions::default(),
+                vec![Box::new(music_client(&server))],
+            ),
+            &server,
+        );
+        let err = manager.load_item("lYBUbBu4W08").await.unwrap_err();
+
+        let YoutubeError::LoadFailed(failure) = err else {
+            panic!("expected a load failure, got {err:?}");
+        };
+
+        assert_eq!(failure.identifier, "lYBUbBu4W08");
+        assert_eq!(failure.attempts.len(), 1);
+        assert_eq!(failure.attempts[0].client, "MUSIC");
+        assert!(matches!(
+            failure.attempts[0].error,
+            YoutubeError::Unplayable { .. }
+        ));
+        assert_eq!(
+            failure.attempts[0].playability_reason.as_deref(),
+            Some("Sign in to confirm your age")
+        );
+        assert_eq!(failure.severity(), FailureSeverity::Common);
+        assert_eq!(failure.cause_message(), "Sign in to confirm your age");
+    }
+
+    #[tokio::test]
+    async fn test_music_track_formats() {
+        let server = MockServer::start().await;
+        mock_endpoint(&server, "player", fixture("player")).await;
+
+        let manager = offline_player(YoutubeAudioSourceManager::new(), &server);
+        let formats = music_client(&server)
+            .get_track_formats(&manager, "lYBUbBu4W08")
+            .await
+            .unwrap();
+
+        assert_eq!(formats.formats.len(), 2);
+        assert_eq!(
+            formats.player_script_url.as_str(),
+            "https://www.youtube.com/s/player/e7567ecf/player_ias.vflset/en_US/base.js"
+        );
+
+        let best = formats.get_best_format().unwrap();
+        assert_eq!(best.itag, 251);
+        assert_eq!(best.info, Some(FormatInfo::WebmOpus));
+    }
+
+    #[tokio::test]
+    async fn test_manager_resolves_playback_url() {
+        let server = MockServer::start().await;
+
+        // A plain format URL needs no deciphering
+        let mut player = fixture("player");
+        player["streamingData"]["adaptiveFormats"][0]["url"] = json!(
+            "https://rr1---sn-example.googlevideo.com/videoplayback?expire=1700000000&itag=251"
+        );
+        mock_endpoint(&server, "player", player).await;
+
+        let manager = offline_player(
+            YoutubeAudioSourceManager::with_options_and_clients(
+                YoutubeSourceOptions::default(),
+                vec![Box::new(music_client(&server))],
+            ),
+            &server,
+        );
+        let Some(AudioItem::Track(track)) = manager.load_item("lYBUbBu4W08").await.unwrap() else {
+            panic!("expected a track");
+        };
+
+        let playback = track.resolve_playback_url().await.unwrap();
+
+        assert_eq!(playback.client, "MUSIC");
+        assert_eq!(playback.format.itag, 251);
+        assert_eq!(playback.format.info, Some(FormatInfo::WebmOpus));
+        assert_eq!(
+            playback.url.as_str(),
+            "https://rr1---sn-example.googlevideo.com/videoplayback?expire=1700000000&itag=251"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_format_selector_overrides_manager() {
+        let server = MockServer::start().await;
+
+        let mut player = fixture("player");
+        for (index, itag) in [(0, 251), (1, 140)] {
+            player["streamingData"]["adaptiveFormats"][index]["url"] = json!(format!(
+                "https://rr1---sn-example.googlevideo.com/videoplayback?itag={itag}"
+            ));
+        }
+        mock_endpoint(&server, "player", player).await;
+
+        let aac_only = FormatSelector::new().set_codec_preference(vec![FormatInfo::Mp4AacLc]);
+        let client = MusicClient::with_options(ClientOptions::new().set_format_selector(aac_only))
+            .with_api_base_url(format!("{}/youtubei/v1", server.uri()));
+        let manager = offline_player(
+            YoutubeAudioSourceManager::with_options_and_clients(
+                YoutubeSourceOptions::default(),
+                vec![Box::new(client)],
+            ),
+            &server,
+        );
+        let Some(AudioItem::Track(track)) = manager.load_item("lYBUbBu4W08").await.unwrap() else {
+            panic!("expected a track");
+        };
+
+        let playback = track.resolve_playback_url().await.unwrap();
+        assert_eq!(playback.format.itag, 140);
+    }
+
+    #[tokio::test]
+    async fn test_player_requests_carry_signature_timestamp() {
+        let server = MockServer::start().await;
+
+        // Without playerConfig in the response, formats use the tracked player
+        let mut player = fixture("player");
+        player.as_object_mut().unwrap().remove("playerConfig");
+        Mock::given(method("GET"))
+            .and(path("/iframe_api"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"var scriptUrl = 'https:\/\/www.youtube.com\/s\/player\/0a1b2c3d\/www-widgetapi.vflset\/www-widgetapi.js';"#,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/s/player/0a1b2c3d/player_ias.vflset/en_US/base.js"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("var cfg={signatureTimestamp:20123,foo:1};"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/youtubei/v1/player"))
+            .and(body_partial_json(json!({
+                "playbackContext": {"contentPlaybackContext": {"signatureTimestamp": 20123}}
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(player))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let manager = offline_player(YoutubeAudioSourceManager::new(), &server);
+        let client = music_client(&server);
+
+        // The player is looked up once and reused until the refresh interval
+        let formats = client
+            .get_track_formats(&manager, "lYBUbBu4W08")
+            .await
+            .unwrap();
+        client.load_video(&manager, "lYBUbBu4W08").await.unwrap();
+
+        let player = manager
+            .cipher_manager
+            .current_player_script()
+            .await
+            .unwrap();
+        assert_eq!(player.version, "0a1b2c3d");
+        assert_eq!(player.signature_timestamp, 20123);
+        assert_eq!(formats.player_script_url, player.url);
+    }
+
+    #[tokio::test]
+    async fn test_failed_n_transform_refreshes_player_script() {
+        let server = MockServer::start().await;
+
+        let mut player = fixture("player");
+        player.as_object_mut().unwrap().remove("playerConfig");
+        mock_endpoint(&server, "player", player).await;
+        Mock::given(method("GET"))
+            .and(path("/iframe_api"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"var scriptUrl = 'https:\/\/www.youtube.com\/s\/player\/3a1f9c2e\/www-widgetapi.vflset\/www-widgetapi.js';"#,
+            ))
+            .mount(&server)
+            .await;
+
+        // The first copy of the player throws in its n function, as a script
+        // cached from a bad deploy would
+        let script = include_str!("fixtures/layout_corpus/split_lookup_table.js");
+        let player_path = "/s/player/3a1f9c2e/player_ias.vflset/en_US/base.js";
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(script.replace("try{", "try{null.x;")),
+            )
+            .up_to_n_times(1)
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .respond_with(ResponseTemplate::new(200).set_body_string(script))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let manager = offline_player(
+            YoutubeAudioSourceManager::with_options_and_clients(
+                YoutubeSourceOptions::default(),
+                vec![Box::new(music_client(&server))],
+            ),
+            &server,
+        );
+        let Some(AudioItem::Track(track)) = manager.load_item("lYBUbBu4W08").await.unwrap() else {
+            panic!("expected a track");
+        };
+
+        let playback = track.resolve_playback_url().await.unwrap();
+        assert_eq!(playback.format.itag, 251);
+        assert!(
+            playback.url.as_str().ends_with("&n=aDeFgHiJ"),
+            "{}",
+            playback.url
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_loads_fetch_player_script_once() {
+        let server = MockServer::start().await;
+
+        let script = include_str!("fixtures/layout_corpus/split_lookup_table.js");
+        let player_path = "/s/player/3a1f9c2e/player_ias.vflset/en_US/base.js";
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(script)
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let manager = std::sync::Arc::new(SignatureCipherManager::new());
+        let url = url::Url::parse(&format!("{}{player_path}", server.uri())).unwrap();
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let manager = manager.clone();
+                let url = url.clone();
+                tokio::spawn(async move { manager.get_advanced_cipher(&url).await })
+            })
+            .collect();
+
+        for task in tasks {
+            let cipher = task.await.unwrap().unwrap();
+            assert_eq!(
+                cipher.transform_n_parameter("Kt7cVQGZq4OnNqyP").unwrap(),
+                "Kt7cVQGq4ONqyP"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signature_timestamp_reuses_fetched_player_script() {
+        let server = MockServer::start().await;
+
+        // Only the timestamp can be read, the cipher cannot be extracted
+        let player_path = "/s/player/0a1b2c3d/player_ias.vflset/en_US/base.js";
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("var AH={signatureTimestamp:20123,clientName:\"WEB\"};"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let manager = SignatureCipherManager::new();
+        let url = url::Url::parse(&format!("{}{player_path}", server.uri())).unwrap();
+        assert_eq!(manager.signature_timestamp(&url).await.unwrap(), 20123);
+        assert!(manager.get_cipher(&url).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_player_script_error_page_is_not_cached() {
+        let server = MockServer::start().await;
+
+        let player_path = "/s/player/0a1b2c3d/player_ias.vflset/en_US/base.js";
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .respond_with(
+                ResponseTemplate::new(404)
+                    .set_body_string("<html>var AH={signatureTimestamp:1};</html>"),
+            )
+            .mount(&server)
+            .await;
+
+        let manager = SignatureCipherManager::new();
+        let url = url::Url::parse(&format!("{}{player_path}", server.uri())).unwrap();
+        let Err(YoutubeError::HttpError(message)) = manager.signature_timestamp(&url).await else {
+            panic!("expected the error page to be rejected");
+        };
+        assert!(message.contains("404"), "{message}");
+        assert_eq!(manager.get_cache_stats().await.total_entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_n_failures_refresh_player_script_once() {
+        let server = MockServer::start().await;
+
+        let script = include_str!("fixtures/layout_corpus/split_lookup_table.js");
+        let player_path = "/s/player/3a1f9c2e/player_ias.vflset/en_US/base.js";
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(script.replace("try{", "try{null.x;")),
+            )
+            .up_to_n_times(1)
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(script)
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let manager = std::sync::Arc::new(SignatureCipherManager::new());
+        let url = url::Url::parse(&format!("{}{player_path}", server.uri())).unwrap();
+        let format = youtube_source_rs::StreamFormat {
+            info: Some(FormatInfo::WebmOpus),
+            content_type: "audio/webm; codecs=\"opus\"".to_string(),
+            itag: 251,
+            bitrate: 128000,
+            content_length: 0,
+            audio_channels: 2,
+            url: url::Url::parse("https://example.com/videoplayback?itag=251").unwrap(),
+            n_parameter: Some("Kt7cVQGZq4OnNqyP".to_string()),
+            signature: None,
+            signature_key: None,
+            is_default_audio_track: true,
+            is_drc: false,
+            language: None,
+        };
+
+        // Every task sees the broken n function, but only one refetches
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let manager = manager.clone();
+                let url = url.clone();
+                let format = format.clone();
+                tokio::spawn(async move { manager.resolve_format_url(&url, &format).await })
+            })
+            .collect();
+
+        for task in tasks {
+            let resolved = task.await.unwrap().unwrap();
+            assert!(
+                resolved.as_str().ends_with("&n=Kt7cVQGq4ONqyP"),
+                "{resolved}"
+            );
+        }
+    }
+
+    fn proxy_pool(url: &str) -> std::sync::Arc<ProxyPool> {
+        let proxy = ProxyConfig::new(url)
+            .set_credentials(Some("user".to_string()), Some("secret".to_string()));
+        std::sync::Arc::new(ProxyPool::new("music", &[proxy], ProxyAssignment::RoundRobin).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_music_requests_go_through_client_proxy() {
+        // The mock server acts as the proxy for an unreachable API host
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/youtubei/v1/search"))
+            .and(header("proxy-authorization", "Basic dXNlcjpzZWNyZXQ="))
+            .respond_with(ResponseTemplate::new(200).set_body_json(fixture("search")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_client_proxy_pool("MUSIC", proxy_pool(&server.uri()));
+        let manager = offline_player(
+            YoutubeAudioSourceManager::new().with_http_client(http_client),
+            &server,
+        );
+        let item = MusicClient::new()
+            .with_api_base_url("http://youtube.invalid/youtubei/v1")
+            .search(&manager, "never gonna give you up")
+            .await
+            .unwrap();
+
+        assert!(matches!(item, Some(AudioItem::SearchResult(_))));
+    }
+
+    #[tokio::test]
+    async fn test_player_scripts_go_through_proxy() {
+        // The mock server acts as the proxy for an unreachable player host
+        let server = MockServer::start().await;
+        let player_path = "/s/player/3a1f9c2e/player_ias.vflset/en_US/base.js";
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .and(header("proxy-authorization", "Basic dXNlcjpzZWNyZXQ="))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(include_str!("fixtures/layout_corpus/split_lookup_table.js")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_proxy_pool(proxy_pool(&server.uri()));
+        let manager = YoutubeAudioSourceManager::new().with_http_client(http_client);
+        let url = url::Url::parse(&format!("http://youtube.invalid{player_path}")).unwrap();
+        let cipher = manager
+            .cipher_manager
+            .get_advanced_cipher(&url)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            cipher.transform_n_parameter("Kt7cVQGZq4OnNqyP").unwrap(),
+            "Kt7cVQGq4ONqyP"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tv_requests_use_client_proxy_and_rate_limit() {
+        // Nothing listens on the discard port, so every request fails fast
+        let client = TvClient::html5_embedded();
+        let limiter = RateLimiter::new().with_client_limit(
+            client.get_identifier(),
+            RateLimit::new(1, Duration::from_millis(200)),
+        );
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_rate_limiter(std::sync::Arc::new(limiter))
+            .with_client_proxy_pool(client.get_identifier(), proxy_pool("http://127.0.0.1:9"));
+        let manager = YoutubeAudioSourceManager::new().with_http_client(http_client);
+
+        let start = std::time::Instant::now();
+        for _ in 0..2 {
+            let Err(error) = client.load_mix(&manager, "RDdQw4w9WgXcQ", None).await else {
+                panic!("expected the proxy to fail");
+            };
+            assert_eq!(error.failed_proxy(), Some("http://127.0.0.1:9"));
+        }
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn test_stream_goes_through_proxy_of_player_request() {
+        // Two mock servers act as round-robin proxies for unreachable API and
+        // media hosts, each handing out media URLs that name it
+        let proxies = [MockServer::start().await, MockServer::start().await];
+        for (index, proxy) in proxies.iter().enumerate() {
+            let mut player = fixture("player");
+            player["streamingData"]["adaptiveFormats"][0]["url"] = json!(format!(
+                "http://media.invalid/videoplayback?itag=251&proxy={index}"
+            ));
+            mock_endpoint(proxy, "player", player).await;
+            Mock::given(method("GET"))
+                .and(path("/videoplayback"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0; 16]))
+                .mount(proxy)
+                .await;
+        }
+
+        let configs: Vec<_> = proxies
+            .iter()
+            .map(|proxy| ProxyConfig::new(proxy.uri()))
+            .collect();
+        let pool = ProxyPool::new("music", &configs, ProxyAssignment::RoundRobin).unwrap();
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_client_proxy_pool("MUSIC", std::sync::Arc::new(pool));
+        let manager = offline_player(
+            YoutubeAudioSourceManager::with_options_and_clients(
+                YoutubeSourceOptions::default(),
+                vec![Box::new(
+                    MusicClient::new().with_api_base_url("http://youtube.invalid/youtubei/v1"),
+                )],
+            )
+            .with_http_client(http_client),
+            &proxies[0],
+        );
+        let Some(AudioItem::Track(track)) = manager.load_item("lYBUbBu4W08").await.unwrap() else {
+            panic!("expected a track");
+        };
+
+        let mut stream = track.open_stream().await.unwrap();
+        let mut buffer = [0; 16];
+        stream.read_exact(&mut buffer).await.unwrap();
+
+        let mut streamed = 0;
+        for (index, proxy) in proxies.iter().enumerate() {
+            for request in proxy.received_requests().await.unwrap() {
+                if request.url.path() == "/videoplayback" {
+                    assert!(
+                        request.url.as_str().contains(&format!("proxy={index}")),
+                        "{}",
+                        request.url
+                    );
+                    streamed += 1;
+                }
+            }
+        }
+        assert_eq!(streamed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_failure_is_reported_in_load_error() {
+        let server = MockServer::start().await;
+
+        // Nothing listens on the discard port
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_client_proxy_pool("MUSIC", proxy_pool("http://127.0.0.1:9"));
+        let manager = offline_player(
+            YoutubeAudioSourceManager::with_options_and_clients(
+                YoutubeSourceOptions::default(),
+                vec![Box::new(music_client(&server))],
+            )
+            .with_http_client(http_client),
+            &server,
+        );
+
+        let Err(YoutubeError::LoadFailed(failure)) = manager.load_item("lYBUbBu4W08").await else {
+            panic!("expected a load failure");
+        };
+        assert_eq!(failure.attempts[0].client, "MUSIC");
+        assert_eq!(failure.failed_proxies(), vec!["http://127.0.0.1:9"]);
+        assert_eq!(failure.severity(), FailureSeverity::Suspicious);
+    }
+
+    #[tokio::test]
+    async fn test_default_clients_report_load_failure() {
+        // Nothing listens on the discard port, so every default client fails
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_proxy_pool(proxy_pool("http://127.0.0.1:9"));
+        let manager = YoutubeAudioSourceManager::new().with_http_client(http_client);
+
+        let Err(YoutubeError::LoadFailed(failure)) = manager.load_item("dQw4w9WgXcQ").await else {
+            panic!("expected a load failure");
+        };
+        let clients: Vec<_> = failure
+            .attempts
+            .iter()
+            .map(|attempt| attempt.client.as_str())
+            .collect();
+        assert_eq!(clients, ["WEB", "MUSIC", "ANDROID"]);
+    }
+
+    #[tokio::test]
+    async fn test_client_rate_limit_spaces_requests() {
+        let server = MockServer::start().await;
+        mock_endpoint(&server, "search", fixture("search")).await;
+
+        let limiter = RateLimiter::new()
+            .with_client_limit("MUSIC", RateLimit::new(1, Duration::from_millis(200)));
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_rate_limiter(std::sync::Arc::new(limiter));
+        let manager = offline_player(
+            YoutubeAudioSourceManager::new().with_http_client(http_client),
+            &server,
+        );
+
+        let start = std::time::Instant::now();
+        for _ in 0..2 {
+            music_client(&server)
+                .search(&manager, "never gonna give you up")
+                .await
+                .unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn test_health_check_marks_dead_proxies() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/generate_204"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let pool = ProxyPool::new(
+            "checked",
+            &[
+                ProxyConfig::new("http://127.0.0.1:9"),
+                ProxyConfig::new(server.uri()),
+            ],
+            ProxyAssignment::RoundRobin,
+        )
+        .unwrap();
+        pool.check_health(&"http://youtube.invalid/generate_204".parse().unwrap())
+            .await;
+
+        let status = pool.status();
+        assert!(!status[0].healthy);
+        assert!(status[1].healthy);
+        for _ in 0..3 {
+            assert_eq!(pool.select("MUSIC").proxy(), status[1].proxy);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_music_load_playlist_with_continuation() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/youtubei/v1/browse"))
+            .and(body_partial_json(
+                json!({"continuation": "music-playlist-page-2"}),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(fixture("playlist_continuation")),
+            )
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/youtubei/v1/browse"))
+            .and(body_partial_json(json!({"browseId": "VLPLmusic123"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(fixture("playlist")))
+            .mount(&server)
+            .await;
+
+        let manager = offline_player(YoutubeAudioSourceManager::new(), &server);
+        let item = music_client(&server)
+            .load_playlist(&manager, "PLmusic123", Some("yPYZpwSpKmA"))
+            .await
+            .unwrap();
+
+        let Some(AudioItem::Playlist(playlist)) = item else {
+            panic!("expected a playlist, got {item:?}");
+        };
+
+        assert_eq!(playlist.name, "Rick Astley Essentials");
+        assert_eq!(playlist.tracks.len(), 3);
+        assert_eq!(playlist.tracks[1].info.duration, Duration::from_secs(205));
+        assert_eq!(playlist.tracks[2].info.title, "Cry for Help");
+        assert_eq!(playlist.selected_track, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_music_load_mix() {
+        let server = MockServer::start().await;
+        mock_endpoint(&server, "next", fixture("next")).await;
+
+        let manager = offline_player(YoutubeAudioSourceManager::new(), &server);
+        let item = music_client(&server)
+            .load_mix(&manager, "RDAMVMlYBUbBu4W08", Some("lYBUbBu4W08"))
+            .await
+            .unwrap();
+
+        let Some(AudioItem::Playlist(playlist)) = item else {
+            panic!("expected a playlist, got {item:?}");
+        };
+
+        assert_eq!(playlist.name, "Mix - Never Gonna Give You Up");
+        assert_eq!(playlist.tracks.len(), 2);
+        assert_eq!(playlist.tracks[1].info.author, "a-ha");
+        assert_eq!(playlist.selected_track, Some(0));
+    }
+}
+
+#[cfg(feature = "mock-testing")]
+mod mock_stream_tests {
+    use std::io::SeekFrom;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+    use url::Url;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+    use youtube_source_rs::stream::{UrlRefresher, YoutubePersistentHttpStream};
+
+    const SAMPLE: &[u8] = include_bytes!("fixtures/stream/sample.bin");
+    const CHUNK_SIZE: u64 = 10_000;
+
+    /// Serves `SAMPLE` honouring googlevideo's `range=start-end` query parameter
+    struct RangeResponder {
+        /// Requests that get cut off halfway through their range
+        truncated_requests: usize,
+        requests: AtomicUsize,
+    }
+
+    impl RangeResponder {
+        fn new() -> Self {
+            Self::truncating(0)
+        }
+
+        fn truncating(truncated_requests: usize) -> Self {
+            Self {
+                truncated_requests,
+                requests: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    fn requested_range(request: &Request) -> (usize, usize) {
+        let range = request
+            .url
+            .query_pairs()
+            .find(|(key, _)| key == "range")
+            .map(|(_, value)| value.into_owned())
+            .expect("stream requests carry a range parameter");
+        let (start, end) = range.split_once('-').unwrap();
+        let start: usize = start.parse().unwrap();
+        let end = end.parse::<usize>().map_or(SAMPLE.len(), |end| end + 1);
+        (start, end.min(SAMPLE.len()))
+    }
+
+    impl Respond for RangeResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let (start, mut end) = requested_range(request);
+            if self.requests.fetch_add(1, Ordering::SeqCst) < self.truncated_requests {
+                end = start + (end - start) / 2;
+            }
+            ResponseTemplate::new(200).set_body_bytes(&SAMPLE[start..end])
+        }
+    }
+
+    async fn media_server(responder: RangeResponder) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/videoplayback"))
+            .respond_with(responder)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn media_url(server: &MockServer) -> Url {
+        Url::parse(&format!("{}/videoplayback?itag=251", server.uri())).unwrap()
+    }
+
+    fn open(url: Url) -> YoutubePersistentHttpStream {
+        YoutubePersistentHttpStream::new(reqwest::Client::new(), url, Some(SAMPLE.len() as u64))
+            .with_chunk_size(CHUNK_SIZE)
+    }
+
+    async fn received_ranges(server: &MockServer) -> Vec<(usize, usize)> {
+        server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| request.url.path() == "/videoplayback")
+            .map(requested_range)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_reads_fixture_in_ranges() {
+        let server = media_server(RangeResponder::new()).await;
+        let mut stream = open(media_url(&server));
+
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).await.unwrap();
+
+        assert_eq!(data, SAMPLE);
+        assert_eq!(stream.position(), SAMPLE.len() as u64);
+        assert_eq!(
+            received_ranges(&server).await,
+            vec![
+                (0, 10_000),
+                (10_000, 20_000),
+                (20_000, 30_000),
+                (30_000, 40_000),
+                (40_000, SAMPLE.len())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_seeks_by_byte_offset() {
+        let server = media_server(RangeResponder::new()).await;
+        let mut stream = open(media_url(&server));
+
+        assert_eq!(stream.seek(SeekFrom::Start(31_337)).await.unwrap(), 31_337);
+        let mut chunk = vec![0; 1_000];
+        stream.read_exact(&mut chunk).await.unwrap();
+        assert_eq!(chunk, &SAMPLE[31_337..32_337]);
+
+        stream.seek(SeekFrom::End(-16)).await.unwrap();
+        let mut tail = Vec::new();
+        stream.read_to_end(&mut tail).await.unwrap();
+        assert_eq!(tail, &SAMPLE[SAMPLE.len() - 16..]);
+
+        // Seeking resumes with a fresh range starting at the new offset
+        let ranges = received_ranges(&server).await;
+        assert_eq!(ranges[0], (31_337, 41_337));
+        assert_eq!(ranges.last().unwrap().0, SAMPLE.len() - 16);
+    }
+
+    #[tokio::test]
+    async fn test_stream_reconnects_after_connection_drop() {
+        let server = media_server(RangeResponder::truncating(2)).await;
+        let mut stream = open(media_url(&server));
+
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).await.unwrap();
+
+        assert_eq!(data, SAMPLE);
+
+        // Each cut-off range is resumed with a new range from where the body stopped
+        let ranges = received_ranges(&server).await;
+        assert_eq!(
+            &ranges[..3],
+            &[(0, 10_000), (5_000, 15_000), (10_000, 20_000)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_refreshes_expired_url() {
+        let server = media_server(RangeResponder::new()).await;
+        Mock::given(method("GET"))
+            .and(path("/expired"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let fresh_url = media_url(&server);
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let refresher: UrlRefresher = {
+            let refreshes = refreshes.clone();
+            Arc::new(move || {
+                refreshes.fetch_add(1, Ordering::SeqCst);
+                let url = fresh_url.clone();
+                Box::pin(async move { Ok(url) })
+            })
+        };
+
+        let expired = Url::parse(&format!("{}/expired?itag=251", server.uri())).unwrap();
+        let mut stream = open(expired).with_url_refresher(refresher);
+
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).await.unwrap();
+
+        assert_eq!(data, SAMPLE);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(stream.url().path(), "/videoplayback");
+    }
+
+    #[tokio::test]
+    async fn test_stream_backs_off_after_server_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/videoplayback"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/videoplayback"))
+            .respond_with(RangeResponder::new())
+            .mount(&server)
+            .await;
+
+        let mut stream = open(media_url(&server));
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).await.unwrap();
+
+        assert_eq!(data, SAMPLE);
+        assert_eq!(&received_ranges(&server).await[..2], &[(0, 10_000); 2]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_expired_url_without_refresher_fails() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let mut stream = open(media_url(&server));
+        let mut data = Vec::new();
+        let err = stream.read_to_end(&mut data).await.unwrap_err();
+
+        assert!(err.to_string().contains("403"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_can_be_read_again_after_failed_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/videoplayback"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/videoplayback"))
+            .respond_with(RangeResponder::new())
+            .mount(&server)
+            .await;
+
+        let mut stream = open(media_url(&server));
+        let mut data = Vec::new();
+        let err = stream.read_to_end(&mut data).await.unwrap_err();
+        assert!(err.to_string().contains("404"));
+
+        // The failed request is not polled again; the next read reconnects
+        stream.read_to_end(&mut data).await.unwrap();
+        assert_eq!(data, SAMPLE);
+    }
+
+    #[tokio::test]
+    async fn test_stream_can_be_read_again_after_failed_refresh() {
+        let server = media_server(RangeResponder::new()).await;
+        Mock::given(method("GET"))
+            .and(path("/expired"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let fresh_url = media_url(&server);
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let refresher: UrlRefresher = {
+            let refreshes = refreshes.clone();
+            Arc::new(move || {
+                let url = fresh_url.clone();
+                let attempt = refreshes.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    match attempt {
+                        0 => Err(youtube_source_rs::YoutubeError::NetworkError(
+                            "player request failed".to_string(),
+                        )),
+                        _ => Ok(url),
+                    }
+                })
+            })
+        };
+
+        let expired = Url::parse(&format!("{}/expired?itag=251", server.uri())).unwrap();
+        let mut stream = open(expired).with_url_refresher(refresher);
+
+        let mut data = Vec::new();
+        let err = stream.read_to_end(&mut data).await.unwrap_err();
+        assert!(err.to_string().contains("player request failed"));
+
+        stream.read_to_end(&mut data).await.unwrap();
+        assert_eq!(data, SAMPLE);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+}
+
+#[cfg(feature = "mock-testing")]
+mod mock_cipher_solver_tests {
+    use serde_json::json;
+    use std::sync::Arc;
+    use url::Url;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use youtube_source_rs::cipher::{RemoteCipherSolver, SignatureCipherManager};
+    use youtube_source_rs::{RemoteCipherConfig, StreamFormat, YoutubeConfig};
+
+    /// Nothing listens on the discard port, so the player is never fetched
+    const PLAYER_URL: &str = "http://127.0.0.1:9/s/player/abc123/player_ias.vflset/en_US/base.js";
+
+    fn format(itag: u32, signature: &str, n: &str) -> StreamFormat {
+        StreamFormat {
+            info: None,
+            content_type: "audio/webm; codecs=\"opus\"".to_string(),
+            itag,
+            bitrate: 128000,
+            content_length: 0,
+            audio_channels: 2,
+            url: Url::parse(&format!("https://example.com/videoplayback?itag={itag}")).unwrap(),
+            n_parameter: Some(n.to_string()),
+            signature: Some(signature.to_string()),
+            signature_key: Some("sig".to_string()),
+            is_default_audio_track: true,
+            is_drc: false,
+            language: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_solver_resolves_format_urls() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/solve"))
+            .and(header("authorization", "Bearer secret"))
+            .and(body_json(json!({
+                "player_url": PLAYER_URL,
+                "signatures": ["sig-a", "sig-b"],
+                "n": ["n-shared"]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "signatures": ["solved-a", "solved-b"],
+                "n": ["solved-n"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = YoutubeConfig::new().set_remote_cipher(Some(
+            RemoteCipherConfig::new(format!("{}/solve", server.uri()))
+                .set_token(Some("secret".to_string())),
+        ));
+        let manager = SignatureCipherManager::new().with_config(&config).unwrap();
+
+        let formats = [
+            format(251, "sig-a", "n-shared"),
+            format(140, "sig-b", "n-shared"),
+        ];
+        let urls = manager
+            .resolve_format_urls(&Url::parse(PLAYER_URL).unwrap(), &formats)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            urls[0].as_str(),
+            "https://example.com/videoplayback?itag=251&sig=solved-a&n=solved-n"
+        );
+        assert_eq!(
+            urls[1].as_str(),
+            "https://example.com/videoplayback?itag=140&sig=solved-b&n=solved-n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remote_solver_rejects_bad_answers() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/short"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "signatures": [],
+                "n": ["solved-n"]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/unchanged"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "signatures": ["solved-a"],
+                "n": ["n-shared"]
+            })))
+            .mount(&server)
+            .await;
+
+        let player = Url::parse(PLAYER_URL).unwrap();
+        let format = format(251, "sig-a", "n-shared");
+        for (endpoint, expected) in [
+            ("/short", "Cipher solver answered 0 signatures"),
+            ("/unchanged", "N parameter transformation failed"),
+        ] {
+            let solver =
+                RemoteCipherSolver::new(Url::parse(&server.uri()).unwrap().join(endpoint).unwrap());
+            let manager = SignatureCipherManager::new().with_solver(Arc::new(solver));
+            let error = manager
+                .resolve_format_url(&player, &format)
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains(expected), "{endpoint}: {error}");
+        }
+    }
+}
+
+#[cfg(feature = "mock-testing")]
+mod mock_route_planner_tests {
+    use std::net::IpAddr;
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use youtube_source_rs::cipher::SignatureCipherManager;
+    use youtube_source_rs::http::{
+        RequestContext, RoutePlanner, RoutePlannerStrategy, YoutubeHttpClient,
+    };
+    use youtube_source_rs::YoutubeError;
+
+    #[tokio::test]
+    async fn test_rate_limited_address_is_rotated_out() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .respond_with(ResponseTemplate::new(429))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let planner =
+            Arc::new(RoutePlanner::new("127.0.0.8/30", RoutePlannerStrategy::RotateOnBan).unwrap());
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_route_planner(planner.clone());
+        let send = || async {
+            let request = http_client
+                .client()
+                .get(format!("{}/youtubei/v1/browse", server.uri()))
+                .build()
+                .unwrap();
+            http_client
+                .execute_with_context(request, RequestContext::default())
+                .await
+        };
+
+        let first: IpAddr = "127.0.0.8".parse().unwrap();
+        assert!(matches!(send().await, Err(YoutubeError::RateLimited(_))));
+        assert!(planner.is_failing(first));
+
+        // Sent from the next address of the block
+        assert!(send().await.unwrap().status().is_success());
+        let status = planner.status();
+        assert_eq!(status.current_address, Some("127.0.0.9".parse().unwrap()));
+        assert_eq!(status.failing_addresses.len(), 1);
+        assert_eq!(status.failing_addresses[0].address, first);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_player_script_marks_address_failing() {
+        let server = MockServer::start().await;
+        let player_path = "/s/player/3a1f9c2e/player_ias.vflset/en_US/base.js";
+        Mock::given(method("GET"))
+            .and(path(player_path))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&server)
+            .await;
+
+        let planner =
+            Arc::new(RoutePlanner::new("127.0.0.8/30", RoutePlannerStrategy::RotateOnBan).unwrap());
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_route_planner(planner.clone());
+        let manager = SignatureCipherManager::new().with_http_client(http_client.clone());
+        let url = url::Url::parse(&format!("{}{player_path}", server.uri())).unwrap();
+
+        assert!(matches!(
+            manager.get_advanced_cipher(&url).await,
+            Err(YoutubeError::RateLimited(_))
+        ));
+        assert!(planner.is_failing("127.0.0.8".parse().unwrap()));
+
+        // Backed off under its own key, not the WEB client's
+        let filter = http_client.filter();
+        assert!(filter.should_wait_for_rate_limit("WEB").await.is_none());
+        assert!(filter
+            .should_wait_for_rate_limit("PLAYER_SCRIPT")
+            .await
+            .is_some());
+    }
+}
+
+#[cfg(feature = "mock-testing")]
+mod mock_cookie_tests {
+    use std::sync::Arc;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use youtube_source_rs::http::{
+        CookieSequence, RequestContext, YoutubeCookieJar, YoutubeHttpClient,
+    };
+
+    #[tokio::test]
+    async fn test_cookies_are_kept_per_request_sequence() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .and(header("cookie", "SID=account"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("set-cookie", "YSC=sequence; Path=/"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/player"))
+            .and(header("cookie", "SID=account; YSC=sequence"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/next"))
+            .and(header("cookie", "SID=account"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let jar = Arc::new(YoutubeCookieJar::new());
+        jar.load_netscape("127.0.0.1\tFALSE\t/\tFALSE\t0\tSID\taccount\n")
+            .unwrap();
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_cookie_jar(jar)
+            .unwrap();
+        let send = |endpoint: &str, context: RequestContext| {
+            let request = http_client
+                .client()
+                .get(format!("{}/youtubei/v1/{endpoint}", server.uri()))
+                .build()
+                .unwrap();
+            http_client.execute_with_context(request, context)
+        };
+
+        let browse = send("browse", RequestContext::default()).await.unwrap();
+        assert!(browse.status().is_success());
+
+        // The cookie YouTube set is sent until the sequence ends with the
+        // player request, the account cookie after it too
+        let player = RequestContext {
+            is_player_request: true,
+            ..Default::default()
+        };
+        assert!(send("player", player).await.unwrap().status().is_success());
+        let next = send("next", RequestContext::default()).await.unwrap();
+        assert!(next.status().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_sequences_keep_their_own_cookies() {
+        let server = MockServer::start().await;
+        for sequence in ["a", "b"] {
+            Mock::given(method("GET"))
+                .and(path("/youtubei/v1/browse"))
+                .and(query_param("sequence", sequence))
+                .and(header("cookie", "SID=account"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("set-cookie", format!("YSC={sequence}; Path=/").as_str()),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/player"))
+            .and(query_param("sequence", "b"))
+            .and(header("cookie", "SID=account; YSC=b"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/next"))
+            .and(query_param("sequence", "a"))
+            .and(header("cookie", "SID=account; YSC=a"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/next"))
+            .and(query_param("sequence", "b"))
+            .and(header("cookie", "SID=account"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let jar = Arc::new(YoutubeCookieJar::new());
+        jar.load_netscape("127.0.0.1\tFALSE\t/\tFALSE\t0\tSID\taccount\n")
+            .unwrap();
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_cookie_jar(jar)
+            .unwrap();
+        let a = http_client.with_cookie_sequence(Arc::new(CookieSequence::new()));
+        let b = http_client.with_cookie_sequence(Arc::new(CookieSequence::new()));
+        let player = || RequestContext {
+            is_player_request: true,
+            ..Default::default()
+        };
+
+        // The player request ending b's sequence leaves a's cookies alone
+        for (sequence, http_client, endpoint, context) in [
+            ("a", &a, "browse", RequestContext::default()),
+            ("b", &b, "browse", RequestContext::default()),
+            ("b", &b, "player", player()),
+            ("a", &a, "next", RequestContext::default()),
+            ("b", &b, "next", RequestContext::default()),
+        ] {
+            let request = http_client
+                .client()
+                .get(format!(
+                    "{}/youtubei/v1/{endpoint}?sequence={sequence}",
+                    server.uri()
+                ))
+                .build()
+                .unwrap();
+            let response = http_client
+                .execute_with_context(request, context)
+                .await
+                .unwrap();
+            assert!(response.status().is_success(), "{endpoint} failed");
+        }
+    }
+}
+
+#[cfg(feature = "mock-testing")]
+mod mock_retry_tests {
+    use std::time::{Duration, Instant};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use youtube_source_rs::http::{RequestContext, RetryPolicy, YoutubeHttpClient};
+
+    async fn browse(
+        http_client: &YoutubeHttpClient,
+        server: &MockServer,
+    ) -> youtube_source_rs::Result<reqwest::Response> {
+        let request = http_client
+            .client()
+            .get(format!("{}/youtubei/v1/browse", server.uri()))
+            .build()
+            .unwrap();
+        http_client
+            .execute_with_context(request, RequestContext::default())
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http_client = YoutubeHttpClient::new().unwrap();
+        assert!(browse(&http_client, &server)
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+    }
+
+    #[tokio::test]
+    async fn test_last_server_error_is_returned() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .respond_with(ResponseTemplate::new(502))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let http_client = YoutubeHttpClient::new()
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new().set_max_attempts(2));
+        let response = browse(&http_client, &server).await.unwrap();
+        assert_eq!(response.status(), 502);
+    }
+
+    #[tokio::test]
+    async fn test_short_retry_after_is_waited_out() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let http_client = YoutubeHttpClient::new().unwrap();
+        let start = Instant::now();
+        assert!(browse(&http_client, &server)
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_long_retry_after_backs_the_client_off() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/youtubei/v1/browse"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "120"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http_client = YoutubeHttpClient::new().unwrap();
+        assert!(matches!(
+            browse(&http_client, &server).await,
+            Err(youtube_source_rs::YoutubeError::RateLimited(_))
+        ));
+
+        let cooldown = http_client
+            .filter()
+            .should_wait_for_rate_limit("")
+            .await
+            .unwrap();
+        assert!(cooldown > Duration::from_secs(60));
+    }
+}